@@ -1,5 +1,7 @@
-use lp::{Lp, Optimization};
-use rulinalg::matrix::{Matrix};
+use lp::{Lp, Optimization, SparseMatrix, Substitution};
+use parser::Components;
+use std::collections::BTreeMap;
+use std::f64::{INFINITY, NEG_INFINITY};
 use super::*;
 
 impl BuilderBase for Builder {
@@ -10,47 +12,109 @@ impl BuilderBase for Builder {
 			variable_indices: HashMap::new(),
 			constraints: vec![],
 			objective: None,
-			var_names: vec![]
+			var_names: vec![],
+			bounds: HashMap::new(),
+			integers: HashSet::new()
 		}
 	}
 
 	/// Adds a new user-defined Variable struct to this Builder struct.
-	fn add_variable(&mut self, variable: Variable) {
-		if !(self.variables.contains(&variable.name)) {
-			let num_variables = self.variables.len();
-			self.variable_indices.insert(variable.name.clone(), num_variables);
-			self.variables.insert(variable.name.clone());
-			self.var_names.push(variable.name.clone());
+	///
+	/// Adding a variable that already exists has no effect. Fails if the
+	/// name has the form `slack_#`, `excess_#` or `negative_#`.
+	fn add_variable(&mut self, variable: Variable) -> Result<(), BuildError> {
+		if is_reserved_name(&variable.name) {
+			return Err(BuildError::ReservedVariableName { variable: variable.name });
 		}
+
+		self.insert_variable(variable.name);
+		Ok(())
 	}
 
 	/// Adds a new user-defined Constraint struct to this Builder struct.
-	fn add_constraint(&mut self, constraint: Constraint) {
-		self.check_variables(&constraint.variables).expect("Unknown variable in constraint");
+	///
+	/// Fails if the constraint uses a variable that has not been added.
+	fn add_constraint(&mut self, constraint: Constraint) -> Result<(), BuildError> {
+		if let Some(variable) = self.find_unknown_variable(&constraint.variables) {
+			return Err(BuildError::UnknownVariable {
+				variable: variable,
+				constraint: constraint.name
+			});
+		}
+
 		self.constraints.push(constraint);
+		Ok(())
 	}
 
 	/// Adds a new user-defined Objective struct to this Builder struct.
-	fn add_objective(&mut self, objective: Objective) {
-		self.check_variables(&objective.variables).expect("Unknown variable in objective");
+	///
+	/// Fails if the objective uses a variable that has not been added
+	/// or if an objective has already been added.
+	fn add_objective(&mut self, objective: Objective) -> Result<(), BuildError> {
+		if let Some(variable) = self.find_unknown_variable(&objective.variables) {
+			return Err(BuildError::UnknownObjectiveVariable {
+				variable: variable,
+				objective: objective.name
+			});
+		}
 
-		let curr_objective = self.objective.take();
-		match curr_objective {
-			Some(_) => {
-				panic!("Attempting to add a second objective!");
+		match self.objective {
+			Some(ref existing) => {
+				Err(BuildError::DuplicateObjective {
+					existing: existing.name.clone(),
+					duplicate: objective.name
+				})
 			},
 			None => {
 				self.objective = Some(objective);
+				Ok(())
 			}
 		}
 	}
 
+	/// Sets the lower and upper bounds of an added variable, replacing
+	/// the default bounds of 0 and infinity.
+	///
+	/// The lower bound may be `f64::NEG_INFINITY` and the upper bound
+	/// `f64::INFINITY`. Fails if the variable has not been added, or if
+	/// the lower bound is above the upper bound or either bound excludes
+	/// every finite value.
+	fn set_bounds(&mut self, variable: &str, lower: f64, upper: f64) -> Result<(), BuildError> {
+		if !self.variables.contains(variable) {
+			return Err(BuildError::UnknownBoundVariable { variable: variable.to_string() });
+		}
+		if lower.is_nan() || upper.is_nan() || lower == INFINITY || upper == NEG_INFINITY || lower > upper {
+			return Err(BuildError::InvalidBounds {
+				variable: variable.to_string(),
+				lower: lower,
+				upper: upper
+			});
+		}
+
+		self.bounds.insert(variable.to_string(), (lower, upper));
+		Ok(())
+	}
+
+	/// Restricts an added variable to integer values.
+	///
+	/// Fails if the variable has not been added.
+	fn set_integer(&mut self, variable: &str) -> Result<(), BuildError> {
+		if !self.variables.contains(variable) {
+			return Err(BuildError::UnknownIntegerVariable { variable: variable.to_string() });
+		}
+
+		self.integers.insert(variable.to_string());
+		Ok(())
+	}
+
 	/// Constructor for Lp struct.
 	///
 	/// Requires this Builder struct as input.
 	///
 	/// Converts the user-defined parameters into standard form
-	/// in the process.
+	/// in the process. Variables with no lower bound are replaced by
+	/// their negation or split into two non-negative parts, as listed in
+	/// `Lp::substitutions`. Fails if no objective has been added.
 	///
 	/// # Examples
 	/// ```
@@ -102,17 +166,17 @@ impl BuilderBase for Builder {
 	/// 	let objective = gen_objective(obj_vec);
 	///
 	/// 	for v in vars {
-	/// 		lpb.add_variable(v);
+	/// 		lpb.add_variable(v).unwrap();
 	/// 	}
 	///
 	/// 	for c in constraints {
-	/// 		lpb.add_constraint(c);
+	/// 		lpb.add_constraint(c).unwrap();
 	/// 	}
 	///
 	///
-	/// 	lpb.add_objective(objective);
+	/// 	lpb.add_objective(objective).unwrap();
 	///
-	/// 	let lp = lpb.build_lp();
+	/// 	let lp = lpb.build_lp().unwrap();
 	///
 	/// 	let expected_A = matrix![
 	/// 			2.0,  -5.0,   0.0,   0.0,   1.0,   0.0;
@@ -123,45 +187,168 @@ impl BuilderBase for Builder {
 	/// 	let expected_b = vec![10., 15., 33.];
 	/// 	let expected_c = vec![1., 2., 3., 4., 0., 0.];
 	///
-	/// 	assert_matrix_eq!(lp.A, expected_A);
+	/// 	assert_matrix_eq!(lp.A.to_dense(), expected_A);
 	/// 	assert_eq!(lp.b, expected_b);
 	/// 	assert_eq!(lp.c, expected_c);
 	/// 	assert_eq!(lp.optimization, Optimization::Min);
 	/// # }
 	/// ```
-	fn build_lp(&mut self) -> Lp {
+	fn build_lp(&mut self) -> Result<Lp, BuildError> {
+		if self.objective.is_none() {
+			return Err(BuildError::MissingObjective);
+		}
+
+		let substitutions = self.find_substitutions();
 		let num_artificial_vars = self.convert_to_standard_form();
-		let A = self.generate_A();
+		let A = self.generate_A(&substitutions);
 		let b = self.generate_b();
-		let (c, opt) = self.generate_c();
+		let (c, opt) = self.generate_c(&substitutions);
+		let (lower, upper) = self.generate_bounds(&substitutions);
+		let integers = self.generate_integers();
 
-		Lp {
+		Ok(Lp {
 			A: A,
 			b: b,
 			c: c,
 			optimization: opt,
 			vars: self.var_names.clone(),
+			constraints: self.constraints.iter().map(|c| c.name.clone()).collect(),
+			lower: lower,
+			upper: upper,
+			substitutions: substitutions,
+			integers: integers,
 			num_artificial_vars: num_artificial_vars
 
-		}
+		})
 	}
 }
 
 impl Builder {
-	fn generate_A(&self) -> Matrix<f64> {
-		let num_variables = self.variables.len();
-		let num_constraints = self.constraints.len();
-		let mut A = vec![0.; num_constraints * num_variables];
+	/// The variables, constraints, objective, bounds and integer variables
+	/// added to this Builder, as the parser gives them.
+	///
+	/// The slack, excess and negative part variables that `build_lp` adds
+	/// are left out, and each constraint keeps the relation it was added
+	/// with. Fails if no objective has been added.
+	pub fn components(&self) -> Result<Components, BuildError> {
+		let objective = match self.objective {
+			Some(ref objective) => objective.clone(),
+			None => return Err(BuildError::MissingObjective)
+		};
+		let names: Vec<&String> = self.var_names.iter().filter(|name| !is_reserved_name(name)).collect();
+
+		let constraints = self.constraints.iter().map(|constraint| {
+			let mut relation = constraint.relation.clone();
+			for v in constraint.variables.iter().filter(|v| is_reserved_name(&v.name)) {
+				if v.name.starts_with("slack_") {
+					relation = Relation::LessThanOrEqual;
+				} else if v.name.starts_with("excess_") {
+					relation = Relation::GreaterThanOrEqual;
+				}
+			}
+			Constraint {
+				name: constraint.name.clone(),
+				variables: constraint.variables.iter().filter(|v| !is_reserved_name(&v.name)).cloned().collect(),
+				constant: constraint.constant,
+				relation: relation
+			}
+		}).collect();
+
+		Ok(Components {
+			variables: names.iter().map(|&name| Variable { name: name.clone(), coefficient: 0. }).collect(),
+			constraints: constraints,
+			objective: objective,
+			bounds: self.bounds.clone(),
+			integers: names.iter().filter(|&&name| self.integers.contains(name)).map(|&name| name.clone()).collect()
+		})
+	}
 
-		for row in 0 .. num_constraints {
-			let constraint = &self.constraints[row];
-			for ref var in &constraint.variables {
-				let index = row * num_variables + self.variable_indices[&var.name];
-				A[index] = var.coefficient;
+	fn insert_variable(&mut self, name: String) {
+		if !(self.variables.contains(&name)) {
+			let num_variables = self.variables.len();
+			self.variable_indices.insert(name.clone(), num_variables);
+			self.variables.insert(name.clone());
+			self.var_names.push(name);
+		}
+	}
+
+	// Adds the negative part of every free var before any slack or excess
+	// var is added. Repeated builds reuse the negative parts already added.
+	fn find_substitutions(&mut self) -> Vec<Substitution> {
+		let mut substitutions = vec![];
+		let mut negative_ct = 0;
+
+		for name in self.var_names.clone() {
+			let upper = match self.bounds.get(&name) {
+				Some(&(lower, upper)) if lower == NEG_INFINITY => upper,
+				_ => continue
+			};
+
+			let col = self.variable_indices[&name];
+			if upper.is_finite() {
+				substitutions.push(Substitution::Negated { col: col });
+			} else {
+				let negative = format!("negative_{}", negative_ct);
+				negative_ct += 1;
+				self.insert_variable(negative.clone());
+				substitutions.push(Substitution::Split {
+					positive: col,
+					negative: self.variable_indices[&negative]
+				});
+			}
+		}
+
+		substitutions
+	}
+
+	// Slack and excess vars keep the default bounds
+	fn generate_bounds(&self, substitutions: &Vec<Substitution>) -> (Vec<f64>, Vec<f64>) {
+		let (mut lower, mut upper): (Vec<f64>, Vec<f64>) = self.var_names.iter()
+			.map(|name| *self.bounds.get(name).unwrap_or(&(0., INFINITY)))
+			.unzip();
+
+		for substitution in substitutions {
+			match *substitution {
+				Substitution::Negated { col } => {
+					lower[col] = -upper[col];
+					upper[col] = INFINITY;
+				},
+				Substitution::Split { positive, negative } => {
+					lower[positive] = 0.;
+					lower[negative] = 0.;
+				}
+			}
+		}
+
+		(lower, upper)
+	}
+
+	fn generate_integers(&self) -> Vec<usize> {
+		let mut integers: Vec<usize> = self.integers.iter().map(|name| self.variable_indices[name]).collect();
+		integers.sort();
+		integers
+	}
+
+	// Only the variables each constraint mentions are stored
+	fn generate_A(&self, substitutions: &Vec<Substitution>) -> SparseMatrix {
+		let mut triplets = vec![];
+		for (row, constraint) in self.constraints.iter().enumerate() {
+			for (col, coefficient) in self.coefficients(&constraint.variables, substitutions) {
+				triplets.push((row, col, coefficient));
 			}
 		}
 
-		Matrix::new(num_constraints, num_variables, A)
+		SparseMatrix::from_triplets(self.constraints.len(), self.variables.len(), triplets)
+	}
+
+	// Column and coefficient of each variable, with the last coefficient
+	// given for a variable taking precedence
+	fn coefficients(&self, variables: &Vec<Variable>, substitutions: &Vec<Substitution>) -> BTreeMap<usize, f64> {
+		let mut coefficients = variables.iter()
+			.map(|var| (self.variable_indices[&var.name], var.coefficient))
+			.collect();
+		substitute(&mut coefficients, substitutions);
+		coefficients
 	}
 
 	fn generate_b(&self) -> Vec<f64> {
@@ -177,38 +364,32 @@ impl Builder {
 
 	}
 
-	fn generate_c(&self) -> (Vec<f64>, Optimization) {
+	fn generate_c(&self, substitutions: &Vec<Substitution>) -> (Vec<f64>, Optimization) {
 		let num_variables = self.variables.len();
 		let mut c = vec![0.; num_variables];
 
-		let opt = match self.objective {
-			None => {
-				panic!("No objective function!");
-			},
-			Some(ref obj) => {
-				for ref var in &obj.variables {
-					c[self.variable_indices[&var.name]] = var.coefficient;
-				}
+		let obj = self.objective.as_ref().expect("Objective checked in build_lp");
+		for (col, coefficient) in self.coefficients(&obj.variables, substitutions) {
+			c[col] = coefficient;
+		}
 
-				if obj.maximize {
-					Optimization::Max
-				} else {
-					Optimization::Min
-				}
-			}
+		let opt = if obj.maximize {
+			Optimization::Max
+		} else {
+			Optimization::Min
 		};
 
 		(c, opt)
 	}
 
-	fn check_variables(&self, variables: &Vec<Variable>) -> Option<()> {
+	fn find_unknown_variable(&self, variables: &Vec<Variable>) -> Option<String> {
 		for ref var in variables {
 			if !self.variables.contains(&var.name) {
-				return None
+				return Some(var.name.clone())
 			}
 		}
 
-		Some(())
+		None
 	}
 
 	fn convert_to_standard_form(&mut self) -> usize {
@@ -258,7 +439,7 @@ impl Builder {
 		}
 
 		for v in vars_to_add {
-			self.add_variable(v);
+			self.insert_variable(v.name);
 		}
 	}
 
@@ -283,7 +464,225 @@ impl Builder {
 		}
 
 		for v in vars_to_add {
-			self.add_variable(v);
+			self.insert_variable(v.name);
+		}
+	}
+}
+
+// Rewrites the coefficients of one row of A, or of c, for the substitutions
+fn substitute(coefficients: &mut BTreeMap<usize, f64>, substitutions: &Vec<Substitution>) {
+	for substitution in substitutions {
+		match *substitution {
+			Substitution::Negated { col } => {
+				if let Some(coefficient) = coefficients.get_mut(&col) {
+					*coefficient = -*coefficient;
+				}
+			},
+			Substitution::Split { positive, negative } => {
+				if let Some(coefficient) = coefficients.get(&positive).cloned() {
+					coefficients.insert(negative, -coefficient);
+				}
+			}
+		}
+	}
+}
+
+fn is_reserved_name(name: &str) -> bool {
+	for prefix in &["slack_", "excess_", "negative_"] {
+		if name.starts_with(prefix) {
+			let suffix = &name[prefix.len() ..];
+			if suffix.len() > 0 && suffix.chars().all(|c| c.is_digit(10)) {
+				return true;
+			}
+		}
+	}
+
+	false
+}
+
+#[cfg(test)]
+mod builder_tests {
+	use super::*;
+
+	#[test]
+	fn unknown_variable_test() {
+		let mut builder = Builder::new();
+		builder.add_variable(gen_var("a", 1.)).unwrap();
+
+		let constraint = Constraint {
+			name: "cap".to_string(),
+			variables: vec![gen_var("a", 1.), gen_var("b", 2.)],
+			constant: 4.,
+			relation: Relation::LessThanOrEqual
+		};
+		assert_eq!(builder.add_constraint(constraint), Err(BuildError::UnknownVariable {
+			variable: "b".to_string(),
+			constraint: "cap".to_string()
+		}));
+
+		let objective = gen_objective("profit", vec![gen_var("c", 1.)]);
+		assert_eq!(builder.add_objective(objective), Err(BuildError::UnknownObjectiveVariable {
+			variable: "c".to_string(),
+			objective: "profit".to_string()
+		}));
+	}
+
+	#[test]
+	fn bounds_test() {
+		let mut builder = Builder::new();
+		builder.add_variable(gen_var("a", 1.)).unwrap();
+		builder.add_variable(gen_var("b", 1.)).unwrap();
+		builder.add_constraint(Constraint {
+			name: "cap".to_string(),
+			variables: vec![gen_var("a", 1.), gen_var("b", 1.)],
+			constant: 4.,
+			relation: Relation::LessThanOrEqual
+		}).unwrap();
+		builder.add_objective(gen_objective("profit", vec![gen_var("a", 1.)])).unwrap();
+
+		assert_eq!(builder.set_bounds("c", 0., 1.), Err(BuildError::UnknownBoundVariable { variable: "c".to_string() }));
+		assert_eq!(builder.set_bounds("a", 2., 1.), Err(BuildError::InvalidBounds {
+			variable: "a".to_string(),
+			lower: 2.,
+			upper: 1.
+		}));
+		assert!(builder.set_bounds("a", INFINITY, INFINITY).is_err());
+		assert!(builder.set_bounds("a", NEG_INFINITY, NEG_INFINITY).is_err());
+		builder.set_bounds("a", -1., 3.).unwrap();
+		builder.set_bounds("b", 1., INFINITY).unwrap();
+
+		let lp = builder.build_lp().unwrap();
+		assert_eq!(lp.lower, vec![-1., 1., 0.]);
+		assert_eq!(lp.upper, vec![3., INFINITY, INFINITY]);
+		assert!(lp.substitutions.is_empty());
+	}
+
+	#[test]
+	fn substitutions_test() {
+		let mut builder = Builder::new();
+		builder.add_variable(gen_var("a", 1.)).unwrap();
+		builder.add_variable(gen_var("b", 1.)).unwrap();
+		builder.add_variable(gen_var("c", 1.)).unwrap();
+		builder.add_constraint(Constraint {
+			name: "cap".to_string(),
+			variables: vec![gen_var("a", 1.), gen_var("b", 2.), gen_var("c", 1.)],
+			constant: 4.,
+			relation: Relation::LessThanOrEqual
+		}).unwrap();
+		builder.add_objective(gen_objective("profit", vec![gen_var("a", 3.), gen_var("b", 1.)])).unwrap();
+
+		builder.set_free("a").unwrap();
+		builder.set_bounds("b", NEG_INFINITY, 2.).unwrap();
+		assert!(builder.add_variable(gen_var("negative_0", 1.)).is_err());
+
+		let lp = builder.build_lp().unwrap();
+		assert_eq!(lp.vars, vec!["a", "b", "c", "negative_0", "slack_0"]);
+		assert_eq!(lp.substitutions, vec![
+			Substitution::Split { positive: 0, negative: 3 },
+			Substitution::Negated { col: 1 }
+		]);
+		assert_matrix_eq!(lp.A.to_dense(), matrix![1., -2., 1., -1., 1.]);
+		assert_eq!(lp.c, vec![3., -1., 0., -3., 0.]);
+		assert_eq!(lp.lower, vec![0., -2., 0., 0., 0.]);
+		assert_eq!(lp.upper, vec![INFINITY; 5]);
+	}
+
+	#[test]
+	fn integers_test() {
+		let mut builder = Builder::new();
+		builder.add_variable(gen_var("a", 1.)).unwrap();
+		builder.add_variable(gen_var("b", 1.)).unwrap();
+		builder.add_variable(gen_var("c", 1.)).unwrap();
+		builder.add_constraint(Constraint {
+			name: "cap".to_string(),
+			variables: vec![gen_var("a", 1.), gen_var("b", 1.), gen_var("c", 1.)],
+			constant: 4.,
+			relation: Relation::LessThanOrEqual
+		}).unwrap();
+		builder.add_objective(gen_objective("profit", vec![gen_var("a", 1.)])).unwrap();
+
+		assert_eq!(builder.set_integer("d"), Err(BuildError::UnknownIntegerVariable { variable: "d".to_string() }));
+		builder.set_binary("c").unwrap();
+		builder.set_integer("a").unwrap();
+		builder.set_free("a").unwrap();
+
+		let lp = builder.build_lp().unwrap();
+		assert_eq!(lp.vars, vec!["a", "b", "c", "negative_0", "slack_0"]);
+		assert_eq!(lp.integers, vec![0, 2]);
+		assert_eq!(lp.lower[2], 0.);
+		assert_eq!(lp.upper[2], 1.);
+	}
+
+	#[test]
+	fn objective_errors_test() {
+		let mut builder = Builder::new();
+		builder.add_variable(gen_var("a", 1.)).unwrap();
+		assert_eq!(builder.build_lp(), Err(BuildError::MissingObjective));
+
+		builder.add_objective(gen_objective("first", vec![gen_var("a", 1.)])).unwrap();
+		assert_eq!(builder.add_objective(gen_objective("second", vec![gen_var("a", 2.)])), Err(BuildError::DuplicateObjective {
+			existing: "first".to_string(),
+			duplicate: "second".to_string()
+		}));
+		assert!(builder.build_lp().is_ok());
+	}
+
+	#[test]
+	fn reserved_name_test() {
+		let mut builder = Builder::new();
+		assert_eq!(builder.add_variable(gen_var("slack_0", 1.)), Err(BuildError::ReservedVariableName {
+			variable: "slack_0".to_string()
+		}));
+		assert!(builder.add_variable(gen_var("slack_time", 1.)).is_ok());
+		assert!(builder.add_variable(gen_var("excess_", 1.)).is_ok());
+	}
+
+	#[test]
+	fn components_test() {
+		let mut builder = Builder::new();
+		assert_eq!(builder.components().unwrap_err(), BuildError::MissingObjective);
+		for name in &["a", "b"] {
+			builder.add_variable(gen_var(name, 0.)).unwrap();
+		}
+		builder.set_free("a").unwrap();
+		builder.set_integer("b").unwrap();
+		builder.add_constraint(Constraint {
+			name: "low".to_string(),
+			variables: vec![gen_var("a", 1.), gen_var("b", 2.)],
+			constant: -1.,
+			relation: Relation::GreaterThanOrEqual
+		}).unwrap();
+		builder.add_constraint(Constraint {
+			name: "high".to_string(),
+			variables: vec![gen_var("a", 1.)],
+			constant: 3.,
+			relation: Relation::LessThanOrEqual
+		}).unwrap();
+		builder.add_objective(gen_objective("obj", vec![gen_var("a", 1.)])).unwrap();
+
+		let components = builder.components().unwrap();
+		assert_eq!(components.variables, vec![gen_var("a", 0.), gen_var("b", 0.)]);
+		assert_eq!(components.integers, vec!["b"]);
+		assert_eq!(components.bounds["a"], (NEG_INFINITY, INFINITY));
+
+		// Building adds slack, excess and negative part variables, which
+		// are left out
+		builder.build_lp().unwrap();
+		assert_eq!(builder.components().unwrap(), components);
+	}
+
+	fn gen_var(name: &str, coeff: f64) -> Variable {
+		Variable {
+			name: name.to_string(),
+			coefficient: coeff
+		}
+	}
+
+	fn gen_objective(name: &str, variables: Vec<Variable>) -> Objective {
+		Objective {
+			name: name.to_string(),
+			variables: variables,
+			maximize: true
 		}
 	}
-}
\ No newline at end of file
+}
@@ -6,6 +6,9 @@
 
 pub use std::collections::{HashMap, HashSet};
 pub use lp::Lp;
+use std::error::Error;
+use std::f64::{INFINITY, NEG_INFINITY};
+use std::fmt;
 
 mod impl_builder;
 
@@ -37,12 +40,55 @@ pub enum Relation {
 	GreaterThanOrEqual
 }
 
+/// Describes why a Builder rejected a component or could not build an Lp.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BuildError {
+	/// A constraint uses a variable that was never added.
+	UnknownVariable { variable: String, constraint: String },
+	/// The objective uses a variable that was never added.
+	UnknownObjectiveVariable { variable: String, objective: String },
+	/// A variable name clashes with the names used for slack, excess and
+	/// negative part variables.
+	ReservedVariableName { variable: String },
+	/// An objective was added when one already exists.
+	DuplicateObjective { existing: String, duplicate: String },
+	/// The Lp was built before any objective was added.
+	MissingObjective,
+	/// Bounds were set on a variable that was never added.
+	UnknownBoundVariable { variable: String },
+	/// A lower bound is above the upper bound, or a bound excludes every
+	/// finite value.
+	InvalidBounds { variable: String, lower: f64, upper: f64 },
+	/// A variable that was never added was marked as integer.
+	UnknownIntegerVariable { variable: String },
+}
+
 pub trait BuilderBase {
 	fn new() -> Self;
-	fn add_variable(&mut self, variable: Variable);
-	fn add_constraint(&mut self, constraint: Constraint);
-	fn add_objective(&mut self, objective: Objective);
-	fn build_lp(&mut self) -> Lp;
+	fn add_variable(&mut self, variable: Variable) -> Result<(), BuildError>;
+	fn add_constraint(&mut self, constraint: Constraint) -> Result<(), BuildError>;
+	fn add_objective(&mut self, objective: Objective) -> Result<(), BuildError>;
+	fn set_bounds(&mut self, variable: &str, lower: f64, upper: f64) -> Result<(), BuildError>;
+
+	/// Lets a variable take any value.
+	fn set_free(&mut self, variable: &str) -> Result<(), BuildError> {
+		self.set_bounds(variable, NEG_INFINITY, INFINITY)
+	}
+
+	/// Restricts a variable to non-positive values.
+	fn set_nonpositive(&mut self, variable: &str) -> Result<(), BuildError> {
+		self.set_bounds(variable, NEG_INFINITY, 0.)
+	}
+
+	fn set_integer(&mut self, variable: &str) -> Result<(), BuildError>;
+
+	/// Restricts a variable to the values 0 and 1.
+	fn set_binary(&mut self, variable: &str) -> Result<(), BuildError> {
+		self.set_integer(variable)?;
+		self.set_bounds(variable, 0., 1.)
+	}
+
+	fn build_lp(&mut self) -> Result<Lp, BuildError>;
 }
 
 #[derive(Debug)]
@@ -51,5 +97,40 @@ pub struct Builder {
 	variable_indices: HashMap<String, usize>,
 	constraints: Vec<Constraint>,
 	objective: Option<Objective>,
-	var_names: Vec<String>
-}
\ No newline at end of file
+	var_names: Vec<String>,
+	bounds: HashMap<String, (f64, f64)>,
+	integers: HashSet<String>
+}
+
+impl fmt::Display for BuildError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BuildError::UnknownVariable { ref variable, ref constraint } => {
+				write!(f, "unknown variable \"{}\" in constraint \"{}\"", variable, constraint)
+			},
+			BuildError::UnknownObjectiveVariable { ref variable, ref objective } => {
+				write!(f, "unknown variable \"{}\" in objective \"{}\"", variable, objective)
+			},
+			BuildError::ReservedVariableName { ref variable } => {
+				write!(f, "variable name \"{}\" is reserved for slack, excess and negative part variables", variable)
+			},
+			BuildError::DuplicateObjective { ref existing, ref duplicate } => {
+				write!(f, "objective \"{}\" added when objective \"{}\" already exists", duplicate, existing)
+			},
+			BuildError::MissingObjective => {
+				write!(f, "no objective function provided")
+			},
+			BuildError::UnknownBoundVariable { ref variable } => {
+				write!(f, "bounds set on unknown variable \"{}\"", variable)
+			},
+			BuildError::InvalidBounds { ref variable, lower, upper } => {
+				write!(f, "invalid bounds [{}, {}] on variable \"{}\"", lower, upper, variable)
+			},
+			BuildError::UnknownIntegerVariable { ref variable } => {
+				write!(f, "unknown variable \"{}\" marked as integer", variable)
+			}
+		}
+	}
+}
+
+impl Error for BuildError {}
@@ -0,0 +1,140 @@
+use super::*;
+use rulinalg::matrix::BaseMatrix;
+
+/// Finds an Irreducible Infeasible Subset of the constraints of an
+/// infeasible Lp by deletion filtering.
+///
+/// Returns the names of the constraints in the subset, in the order they
+/// appear in the Lp. The constraints are infeasible together, but removing
+/// any one of them leaves a feasible set. The objective is ignored.
+///
+/// # Examples
+/// ```
+/// # extern crate rulp;
+/// use rulp::builder::{Builder, BuilderBase};
+/// use rulp::parser::{Parser, ParserBase};
+/// use rulp::solver::SimplexOptions;
+/// use rulp::iis::find_iis;
+///
+/// # fn main() {
+/// let text_problem = "
+/// 	var x;
+/// 	var y;
+/// 	maximize obj: x + y;
+/// 	subject to low: x >= 10;
+/// 	subject to other: y <= 3;
+/// 	subject to high: x <= 5;
+/// ";
+/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+///
+/// let iis = find_iis(&lp, &SimplexOptions::default()).unwrap();
+/// assert_eq!(iis, vec!["low".to_string(), "high".to_string()]);
+/// # }
+/// ```
+pub fn find_iis(lp: &Lp, options: &SimplexOptions) -> Result<Vec<String>, IisError> {
+	let mut rows: Vec<usize> = (0 .. lp.A.rows()).collect();
+	if !is_infeasible(lp, &rows, options)? {
+		return Err(IisError::Feasible);
+	}
+
+	// Any row that can go without restoring feasibility is not needed
+	let mut i = 0;
+	while i < rows.len() {
+		if rows.len() == 1 {
+			break;
+		}
+
+		let mut candidate = rows.clone();
+		candidate.remove(i);
+		if is_infeasible(lp, &candidate, options)? {
+			rows = candidate;
+		} else {
+			i += 1;
+		}
+	}
+
+	Ok(rows.iter().map(|&row| lp.constraints[row].clone()).collect())
+}
+
+// Solves the rows of lp as a feasibility problem
+fn is_infeasible(lp: &Lp, rows: &Vec<usize>, options: &SimplexOptions) -> Result<bool, IisError> {
+	let sub_lp = Lp {
+		A: lp.A.select_rows(rows),
+		b: rows.iter().map(|&row| lp.b[row]).collect(),
+		c: vec![0.; lp.A.cols()],
+		optimization: lp.optimization.clone(),
+		vars: lp.vars.clone(),
+		constraints: rows.iter().map(|&row| lp.constraints[row].clone()).collect(),
+		lower: lp.lower.clone(),
+		upper: lp.upper.clone(),
+		substitutions: vec![],
+		integers: vec![],
+		num_artificial_vars: lp.num_artificial_vars.min(rows.len())
+	};
+
+	let solution = SimplexSolver::with_options(sub_lp, options.clone()).solve()?;
+	match solution.status {
+		Status::Infeasible => Ok(true),
+		Status::Optimal => Ok(false),
+		status => Err(IisError::Unsolved(status))
+	}
+}
+
+#[cfg(test)]
+mod iis_tests {
+	use super::*;
+	use lp::Optimization;
+	use std::f64::INFINITY;
+
+	#[test]
+	fn find_iis_test() {
+		// x + s1 = 5, x - e2 = 10, y + s3 = 3, x + y + s4 = 4
+		let A = matrix![1., 0., 1., 0., 0., 0.;
+						1., 0., 0., -1., 0., 0.;
+						0., 1., 0., 0., 1., 0.;
+						1., 1., 0., 0., 0., 1.];
+		let lp = Lp {
+			A: A.into(),
+			b: vec![5., 10., 3., 4.],
+			c: vec![1., 1., 0., 0., 0., 0.],
+			optimization: Optimization::Max,
+			vars: vec!["x", "y", "s1", "e2", "s3", "s4"].iter().map(|s| s.to_string()).collect(),
+			constraints: vec!["c1", "c2", "c3", "c4"].iter().map(|s| s.to_string()).collect(),
+			lower: vec![0.; 6],
+			upper: vec![INFINITY; 6],
+			substitutions: vec![],
+			integers: vec![],
+			num_artificial_vars: 3
+		};
+
+		// c1 and c4 each conflict with c2 on their own; filtering keeps the later one
+		let iis = find_iis(&lp, &SimplexOptions::default()).unwrap();
+		assert_eq!(iis, vec!["c2".to_string(), "c4".to_string()]);
+	}
+
+	#[test]
+	fn feasible_test() {
+		let lp = Lp {
+			A: matrix![1., 1.].into(),
+			b: vec![5.],
+			c: vec![1., 0.],
+			optimization: Optimization::Max,
+			vars: vec!["x".to_string(), "s1".to_string()],
+			constraints: vec!["c1".to_string()],
+			lower: vec![0.; 2],
+			upper: vec![INFINITY; 2],
+			substitutions: vec![],
+			integers: vec![],
+			num_artificial_vars: 1
+		};
+		assert_eq!(find_iis(&lp, &SimplexOptions::default()), Err(IisError::Feasible));
+
+		// 2x - e1 = 5 has no unit column so needs a Phase I pivot
+		let mut lp = lp;
+		lp.A.set(0, 0, 2.);
+		lp.A.set(0, 1, -1.);
+		let mut options = SimplexOptions::default();
+		options.max_iterations = Some(0);
+		assert_eq!(find_iis(&lp, &options), Err(IisError::Unsolved(Status::IterationLimit)));
+	}
+}
@@ -0,0 +1,44 @@
+//! The iis module.
+//!
+//! This code explains why an infeasible linear
+//! program has no solution by finding an Irreducible
+//! Infeasible Subset (IIS) of its constraints.
+
+use lp::Lp;
+use solver::{SimplexOptions, SimplexSolver, SolveError, SolverBase, Status};
+use std::error::Error;
+use std::fmt;
+
+mod impl_iis;
+
+pub use self::impl_iis::find_iis;
+
+/// Describes why `find_iis` could not produce an IIS.
+#[derive(Debug, PartialEq, Clone)]
+pub enum IisError {
+	/// The Lp was rejected by the solver.
+	Solve(SolveError),
+	/// The Lp is feasible, so it has no infeasible subset.
+	Feasible,
+	/// A solve stopped with a status other than Optimal or Infeasible,
+	/// e.g. a limit was hit, so the subset could not be reduced.
+	Unsolved(Status)
+}
+
+impl From<SolveError> for IisError {
+	fn from(e: SolveError) -> Self {
+		IisError::Solve(e)
+	}
+}
+
+impl fmt::Display for IisError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			IisError::Solve(ref e) => write!(f, "{}", e),
+			IisError::Feasible => write!(f, "the LP is feasible"),
+			IisError::Unsolved(ref status) => write!(f, "a subproblem stopped with status {:?}", status)
+		}
+	}
+}
+
+impl Error for IisError {}
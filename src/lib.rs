@@ -8,9 +8,15 @@ extern crate assert_approx_eq;
 #[macro_use]
 extern crate approx;
 
+extern crate num_bigint;
+extern crate num_rational;
+extern crate num_traits;
+
 
 pub mod builder;
+pub mod iis;
 pub mod lp;
+pub mod mip;
 pub mod parser;
 pub mod solver;
 
@@ -0,0 +1,75 @@
+use super::*;
+use num_traits::Zero;
+
+impl ExactLp {
+	/// Converts an Lp, taking every entry at its exact binary value.
+	///
+	/// A decimal such as `0.1` has no exact binary value, so an Lp parsed
+	/// from text converts to one with slightly different entries. Use
+	/// `Parser::exact_lp_from_text` to keep the decimals as written.
+	///
+	/// # Panics
+	/// If an entry of `A`, `b` or `c` or a lower bound is not finite.
+	pub fn from_lp(lp: &Lp) -> Self {
+		ExactLp::from_lp_with(lp, |value| {
+			BigRational::from_float(value).expect("Lp entries must be finite to convert exactly")
+		})
+	}
+
+	/// Converts an Lp, reading each finite entry through `convert`.
+	pub fn from_lp_with<F>(lp: &Lp, convert: F) -> Self where F: Fn(f64) -> BigRational {
+		let exact = |value: f64| if value == 0. { BigRational::zero() } else { convert(value) };
+
+		ExactLp {
+			A: lp.A.map(|&value| exact(value)),
+			b: lp.b.iter().map(|&value| exact(value)).collect(),
+			c: lp.c.iter().map(|&value| exact(value)).collect(),
+			optimization: lp.optimization.clone(),
+			vars: lp.vars.clone(),
+			constraints: lp.constraints.clone(),
+			lower: lp.lower.iter().map(|&value| exact(value)).collect(),
+			upper: lp.upper.iter().map(|&value| if value.is_finite() { Some(exact(value)) } else { None }).collect(),
+			substitutions: lp.substitutions.clone(),
+			num_artificial_vars: lp.num_artificial_vars
+		}
+	}
+}
+
+#[cfg(test)]
+mod exact_tests {
+	use super::*;
+	use num_bigint::BigInt;
+	use std::f64::INFINITY;
+
+	#[test]
+	fn from_lp_test() {
+		let lp = Lp {
+			A: SparseMatrix::from(matrix![0.5, 0., 1.]),
+			b: vec![0.1],
+			c: vec![1., -2., 0.],
+			optimization: Optimization::Max,
+			vars: vec!["x".to_string(), "y".to_string(), "slack_0".to_string()],
+			constraints: vec!["c".to_string()],
+			lower: vec![0., -1., 0.],
+			upper: vec![INFINITY, 3., INFINITY],
+			substitutions: vec![],
+			integers: vec![],
+			num_artificial_vars: 1
+		};
+		let ratio = |numer: i64, denom: i64| BigRational::new(BigInt::from(numer), BigInt::from(denom));
+
+		let exact = ExactLp::from_lp(&lp);
+		assert_eq!(exact.A.nnz(), 2);
+		assert_eq!(exact.A.column(0).collect::<Vec<_>>(), vec![(0, ratio(1, 2))]);
+		assert_eq!(exact.b[0], BigRational::from_float(0.1).unwrap());
+		assert!(exact.b[0] != ratio(1, 10));
+		assert_eq!(exact.upper, vec![None, Some(ratio(3, 1)), None]);
+
+		// Reading 0.1 as the decimal it was written as
+		let exact = ExactLp::from_lp_with(&lp, |value| {
+			if value == 0.1 { ratio(1, 10) } else { BigRational::from_float(value).unwrap() }
+		});
+		assert_eq!(exact.b[0], ratio(1, 10));
+		assert_eq!(exact.lower[1], ratio(-1, 1));
+	}
+}
@@ -0,0 +1,228 @@
+use super::*;
+use num_traits::Zero;
+use std::ops::Index;
+
+static ZERO: f64 = 0.;
+
+impl<T: Clone + Zero> SparseMatrix<T> {
+	/// An all-zero matrix of the given size.
+	pub fn zeros(rows: usize, cols: usize) -> Self {
+		SparseMatrix {
+			rows: rows,
+			cols: cols,
+			col_starts: vec![0; cols + 1],
+			row_indices: vec![],
+			values: vec![]
+		}
+	}
+
+	/// Builds a matrix from `(row, col, value)` entries in any order.
+	/// Entries at the same position are added together and zeros are not
+	/// stored.
+	///
+	/// # Panics
+	/// If an entry lies outside the matrix.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::SparseMatrix;
+	///
+	/// let A = SparseMatrix::from_triplets(2, 3, vec![(1, 2, 4.), (0, 0, 1.), (1, 2, 1.)]);
+	/// assert_eq!(A[[1, 2]], 5.);
+	/// assert_eq!(A[[0, 1]], 0.);
+	/// assert_eq!(A.nnz(), 2);
+	/// ```
+	pub fn from_triplets(rows: usize, cols: usize, mut triplets: Vec<(usize, usize, T)>) -> Self {
+		for &(row, col, _) in &triplets {
+			assert!(row < rows && col < cols, "entry ({}, {}) outside a {}x{} matrix", row, col, rows, cols);
+		}
+		triplets.sort_by(|a, b| (a.1, a.0).cmp(&(b.1, b.0)));
+
+		let mut matrix = SparseMatrix::zeros(rows, cols);
+		let mut i = 0;
+		for col in 0 .. cols {
+			while i < triplets.len() && triplets[i].1 == col {
+				let row = triplets[i].0;
+				let mut value = T::zero();
+				while i < triplets.len() && triplets[i].1 == col && triplets[i].0 == row {
+					value = value + triplets[i].2.clone();
+					i += 1;
+				}
+				if !value.is_zero() {
+					matrix.row_indices.push(row);
+					matrix.values.push(value);
+				}
+			}
+			matrix.col_starts[col + 1] = matrix.values.len();
+		}
+
+		matrix
+	}
+
+	pub fn rows(&self) -> usize {
+		self.rows
+	}
+
+	pub fn cols(&self) -> usize {
+		self.cols
+	}
+
+	/// Number of stored entries.
+	pub fn nnz(&self) -> usize {
+		self.values.len()
+	}
+
+	/// The stored `(row, value)` entries of a column, in increasing order of row.
+	pub fn column<'a>(&'a self, col: usize) -> impl Iterator<Item = (usize, T)> + 'a {
+		let range = self.col_starts[col] .. self.col_starts[col + 1];
+		self.row_indices[range.clone()].iter().cloned().zip(self.values[range].iter().cloned())
+	}
+
+	/// Every stored entry as `(row, col, value)`, column by column.
+	pub fn triplets<'a>(&'a self) -> impl Iterator<Item = (usize, usize, T)> + 'a {
+		(0 .. self.cols).flat_map(move |col| self.column(col).map(move |(row, value)| (row, col, value)))
+	}
+
+	/// Sets an entry, storing or removing it as needed.
+	pub fn set(&mut self, row: usize, col: usize, value: T) {
+		assert!(row < self.rows && col < self.cols, "entry ({}, {}) outside a {}x{} matrix", row, col, self.rows, self.cols);
+		match self.position(row, col) {
+			Ok(k) if value.is_zero() => {
+				self.row_indices.remove(k);
+				self.values.remove(k);
+				for start in &mut self.col_starts[col + 1 ..] {
+					*start -= 1;
+				}
+			},
+			Ok(k) => self.values[k] = value,
+			Err(_) if value.is_zero() => {},
+			Err(k) => {
+				self.row_indices.insert(k, row);
+				self.values.insert(k, value);
+				for start in &mut self.col_starts[col + 1 ..] {
+					*start += 1;
+				}
+			}
+		}
+	}
+
+	/// Appends a column with the given `(row, value)` entries.
+	pub fn push_column(&mut self, entries: &[(usize, T)]) {
+		let col = self.cols;
+		self.cols += 1;
+		self.col_starts.push(self.values.len());
+		for &(row, ref value) in entries {
+			self.set(row, col, value.clone());
+		}
+	}
+
+	/// Appends a row with the given `(col, value)` entries.
+	pub fn push_row(&mut self, entries: &[(usize, T)]) {
+		let row = self.rows;
+		let mut triplets: Vec<(usize, usize, T)> = self.triplets().collect();
+		triplets.extend(entries.iter().map(|&(col, ref value)| (row, col, value.clone())));
+		*self = SparseMatrix::from_triplets(self.rows + 1, self.cols, triplets);
+	}
+
+	/// The matrix made of the given rows, in the order given.
+	pub fn select_rows(&self, rows: &[usize]) -> Self {
+		let mut new_rows = vec![vec![]; self.rows];
+		for (new_row, &row) in rows.iter().enumerate() {
+			new_rows[row].push(new_row);
+		}
+		let triplets = self.triplets()
+			.flat_map(|(row, col, value)| new_rows[row].iter().map(|&new_row| (new_row, col, value.clone())).collect::<Vec<_>>())
+			.collect();
+		SparseMatrix::from_triplets(rows.len(), self.cols, triplets)
+	}
+
+	/// The matrix with `f` applied to each stored entry. Entries that
+	/// `f` takes to zero are still stored.
+	pub fn map<U, F>(&self, f: F) -> SparseMatrix<U> where F: Fn(&T) -> U {
+		SparseMatrix {
+			rows: self.rows,
+			cols: self.cols,
+			col_starts: self.col_starts.clone(),
+			row_indices: self.row_indices.clone(),
+			values: self.values.iter().map(f).collect()
+		}
+	}
+
+	// Index of the entry within row_indices, or where it would be inserted
+	fn position(&self, row: usize, col: usize) -> Result<usize, usize> {
+		let start = self.col_starts[col];
+		self.row_indices[start .. self.col_starts[col + 1]].binary_search(&row)
+			.map(|k| start + k)
+			.map_err(|k| start + k)
+	}
+}
+
+impl SparseMatrix {
+	pub fn to_dense(&self) -> Matrix<f64> {
+		let mut dense = Matrix::zeros(self.rows, self.cols);
+		for (row, col, value) in self.triplets() {
+			dense[[row, col]] = value;
+		}
+		dense
+	}
+}
+
+impl Index<[usize; 2]> for SparseMatrix {
+	type Output = f64;
+
+	fn index(&self, index: [usize; 2]) -> &f64 {
+		let [row, col] = index;
+		assert!(row < self.rows && col < self.cols, "entry ({}, {}) outside a {}x{} matrix", row, col, self.rows, self.cols);
+		match self.position(row, col) {
+			Ok(k) => &self.values[k],
+			Err(_) => &ZERO
+		}
+	}
+}
+
+impl From<Matrix<f64>> for SparseMatrix {
+	fn from(dense: Matrix<f64>) -> Self {
+		let mut triplets = vec![];
+		for row in 0 .. dense.rows() {
+			for col in 0 .. dense.cols() {
+				triplets.push((row, col, dense[[row, col]]));
+			}
+		}
+		SparseMatrix::from_triplets(dense.rows(), dense.cols(), triplets)
+	}
+}
+
+#[cfg(test)]
+mod sparse_tests {
+	use super::*;
+
+	#[test]
+	fn set_test() {
+		let mut A = SparseMatrix::from(matrix![1., 0.;
+											   0., 2.]);
+		A.set(1, 0, 3.);
+		A.set(1, 1, 0.);
+		A.set(0, 1, 0.);
+		assert_eq!(A.to_dense(), matrix![1., 0.;
+										 3., 0.]);
+		assert_eq!(A.nnz(), 2);
+		assert_eq!(A.column(0).collect::<Vec<_>>(), vec![(0, 1.), (1, 3.)]);
+		assert_eq!(A, SparseMatrix::from_triplets(2, 2, vec![(1, 0, 3.), (0, 0, 1.)]));
+	}
+
+	#[test]
+	fn reshape_test() {
+		let mut A = SparseMatrix::from(matrix![1., 2.;
+											   0., 3.]);
+		A.push_column(&[(1, 4.)]);
+		A.push_row(&[(0, 5.), (2, 6.)]);
+		assert_eq!(A.to_dense(), matrix![1., 2., 0.;
+										 0., 3., 4.;
+										 5., 0., 6.]);
+
+		let selected = A.select_rows(&[2, 0]);
+		assert_eq!(selected.to_dense(), matrix![5., 0., 6.;
+												1., 2., 0.]);
+		assert_eq!(SparseMatrix::<f64>::zeros(2, 0).select_rows(&[1]).rows(), 1);
+	}
+}
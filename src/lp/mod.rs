@@ -1,16 +1,77 @@
+use num_rational::BigRational;
 use rulinalg::matrix::{BaseMatrix, Matrix};
 use std::fmt;
 
+mod impl_exact;
+mod impl_sparse;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Lp {
-	pub A: Matrix<f64>,
+	pub A: SparseMatrix,
 	pub b: Vec<f64>,
 	pub c: Vec<f64>,
 	pub optimization: Optimization,
 	pub vars: Vec<String>,
+	/// Names of the constraints, one per row of `A`.
+	pub constraints: Vec<String>,
+	/// Lower bound of each column of `A`, 0 unless set otherwise.
+	pub lower: Vec<f64>,
+	/// Upper bound of each column of `A`, which may be infinite.
+	pub upper: Vec<f64>,
+	/// Columns standing in for variables with no lower bound. The solver
+	/// undoes these when it reports values.
+	pub substitutions: Vec<Substitution>,
+	/// Columns of `A` restricted to integer values. A split variable is
+	/// listed by its positive column and restricts the difference of its
+	/// two parts. Only the mip module's solvers enforce this; the
+	/// SimplexSolver solves the LP relaxation.
+	pub integers: Vec<usize>,
 	pub num_artificial_vars: usize,
 }
 
+/// An Lp whose data are exact rationals, as solved by the
+/// `ExactSimplexSolver`. Fields mean the same as those of `Lp`; an
+/// infinite upper bound is `None`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExactLp {
+	pub A: SparseMatrix<BigRational>,
+	pub b: Vec<BigRational>,
+	pub c: Vec<BigRational>,
+	pub optimization: Optimization,
+	pub vars: Vec<String>,
+	pub constraints: Vec<String>,
+	pub lower: Vec<BigRational>,
+	pub upper: Vec<Option<BigRational>>,
+	pub substitutions: Vec<Substitution>,
+	pub num_artificial_vars: usize
+}
+
+/// A matrix in compressed sparse column form, holding only its nonzero
+/// entries.
+///
+/// The entries of column `col` sit at `col_starts[col] .. col_starts[col + 1]`
+/// of `row_indices` and `values`, in increasing order of row. Indexing an
+/// `f64` entry that is not stored gives zero.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SparseMatrix<T = f64> {
+	rows: usize,
+	cols: usize,
+	col_starts: Vec<usize>,
+	row_indices: Vec<usize>,
+	values: Vec<T>
+}
+
+/// Replacement the Builder makes for a variable with no lower bound, so
+/// that every column of `A` has a finite lower bound.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Substitution {
+	/// Column `col` holds -x, for a variable with only an upper bound.
+	Negated { col: usize },
+	/// x = positive - negative, for a free variable. Column `positive`
+	/// takes the place of x and column `negative` is added by the Builder.
+	Split { positive: usize, negative: usize }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Optimization {
 	Min,
@@ -19,29 +80,26 @@ pub enum Optimization {
 
 impl fmt::Display for Lp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    	unsafe {
-    		let opt_str = if self.optimization == Optimization::Max {"max"} else {"min"};
-    		writeln!(f, "{} LP: {:>3} variables ({} artificial), {:>3} constraints\n", opt_str, self.c.len(), self.num_artificial_vars, self.b.len()).unwrap();
-    		for c in 0 .. self.A.cols() {
-    			write!(f, "{:>5.1}  ", self.c[c]).unwrap();
-    		}
-
-    		writeln!(f, "").unwrap();
-
-    		for _ in 0 .. self.A.cols() {
-    			write!(f, " - - - ").unwrap();
-    		}
-    		writeln!(f, "").unwrap();
-    		
-
-			for r in 0 .. self.A.rows() {
-				for c in 0 .. self.A.cols() - 1{
-					write!(f, "{:>5.1}, ", *self.A.get_unchecked([r, c])).unwrap();
-				}
-				writeln!(f, "{:>5.1} | {:>5.1}", *self.A.get_unchecked([r, self.A.cols() -1]), self.b[r]).unwrap();
+		let opt_str = if self.optimization == Optimization::Max {"max"} else {"min"};
+		writeln!(f, "{} LP: {:>3} variables ({} artificial), {:>3} constraints\n", opt_str, self.c.len(), self.num_artificial_vars, self.b.len()).unwrap();
+		for c in 0 .. self.A.cols() {
+			write!(f, "{:>5.1}  ", self.c[c]).unwrap();
+		}
+
+		writeln!(f, "").unwrap();
+
+		for _ in 0 .. self.A.cols() {
+			write!(f, " - - - ").unwrap();
+		}
+		writeln!(f, "").unwrap();
+
+		for r in 0 .. self.A.rows() {
+			for c in 0 .. self.A.cols() - 1{
+				write!(f, "{:>5.1}, ", self.A[[r, c]]).unwrap();
 			}
+			writeln!(f, "{:>5.1} | {:>5.1}", self.A[[r, self.A.cols() -1]], self.b[r]).unwrap();
+		}
 
-			write!(f, "")
-    	}
+		write!(f, "")
     }
 }
@@ -1,13 +1,18 @@
 extern crate clap;
-use clap::{Arg, App};
+use clap::{Arg, App, AppSettings, ArgMatches, SubCommand};
 
 extern crate rulp;
 use rulp::builder::{Builder, BuilderBase};
-use rulp::parser::{Parser, ParserBase};
-use rulp::solver::{SolverBase, SimplexSolver};
+use rulp::parser::{Parser, CplexParser, MpsParser, FixedMpsParser, ParserBase, Components, format_components};
+use rulp::lp::Lp;
+use rulp::lp::ExactLp;
+use rulp::solver::{SolverBase, SimplexSolver, RevisedSimplexSolver, InteriorPointSolver, ExactSimplexSolver, Presolve, SimplexOptions};
+use rulp::iis::find_iis;
+use rulp::mip::{BranchAndBound, MipSolverBase};
 
 use std::io::{Write};
 use std::fs::File;
+use std::process;
 
 fn main() {
 	let matches = App::new("myapp")
@@ -33,23 +38,268 @@ fn main() {
                            	.long("display")
                            	.help("displays the LP solution in the console")
                       		.required(false))
+                      	.arg(Arg::with_name("sensitivity")
+                           	.short("s")
+                           	.long("sensitivity")
+                           	.help("adds a sensitivity report to an optimal LP solution")
+                      		.conflicts_with("revised")
+                      		.required(false))
+                      	.arg(Arg::with_name("revised")
+                           	.short("r")
+                           	.long("revised")
+                           	.help("solves with the revised simplex method, which suits large sparse LPs")
+                      		.required(false))
+                      	.arg(Arg::with_name("interior")
+                           	.short("p")
+                           	.long("interior")
+                           	.help("solves with an interior point method, which suits large degenerate LPs")
+                      		.conflicts_with("revised")
+                      		.required(false))
+                      	.arg(Arg::with_name("exact")
+                           	.short("e")
+                           	.long("exact")
+                           	.help("solves an LP without integer variables exactly, reading decimals as rationals")
+                      		.conflicts_with_all(&["revised", "interior", "sensitivity"])
+                      		.required(false))
+                      	.arg(Arg::with_name("presolve")
+                           	.long("presolve")
+                           	.help("removes redundant rows and fixed variables of an LP without integer variables before solving")
+                      		.conflicts_with_all(&["exact", "sensitivity"])
+                      		.required(false))
+                      	.arg(Arg::with_name("cplex")
+                           	.short("c")
+                           	.long("cplex")
+                           	.help("reads the source file in the CPLEX LP format")
+                      		.global(true)
+                      		.required(false))
+                      	.arg(Arg::with_name("mps")
+                           	.short("m")
+                           	.long("mps")
+                           	.help("reads the source file in the free MPS format")
+                      		.conflicts_with("cplex")
+                      		.global(true)
+                      		.required(false))
+                      	.arg(Arg::with_name("fixed-mps")
+                           	.long("fixed-mps")
+                           	.help("reads the source file in the fixed MPS format")
+                      		.conflicts_with_all(&["cplex", "mps"])
+                      		.global(true)
+                      		.required(false))
+                      	.setting(AppSettings::SubcommandsNegateReqs)
+                      	.subcommand(SubCommand::with_name("iis")
+                      		.about("prints an irreducible set of conflicting constraints of an infeasible LP")
+                      		.arg(Arg::with_name("input")
+                           		.short("i")
+                           		.long("input")
+                           		.value_name("SOURCE")
+                           		.help("source path of file with infeasible LP")
+                           		.takes_value(true)
+                      			.required(true)))
+                      	.subcommand(SubCommand::with_name("fmt")
+                      		.about("rewrites an LP in canonical rulp syntax")
+                      		.arg(Arg::with_name("input")
+                           		.short("i")
+                           		.long("input")
+                           		.value_name("SOURCE")
+                           		.help("source path of file with LP")
+                           		.takes_value(true)
+                      			.required(true))
+                      		.arg(Arg::with_name("output")
+                           		.short("o")
+                           		.long("output")
+                           		.value_name("DESTINATION")
+                           		.help("destination path for the formatted LP, printed if not given")
+                           		.takes_value(true)
+                      			.required(false)))
                       	.get_matches();
 
+	if let Some(iis_matches) = matches.subcommand_matches("iis") {
+		let input_path = iis_matches.value_of("input").unwrap();
+		let lp = read_lp(input_path, input_format(&[&matches, iis_matches]));
+		match find_iis(&lp, &SimplexOptions::default()) {
+			Ok(constraints) => {
+				println!("Conflicting constraints:");
+				for constraint in constraints {
+					println!("{}", constraint);
+				}
+			},
+			Err(e) => {
+				writeln!(std::io::stderr(), "{}: {}", input_path, e).unwrap();
+				process::exit(1);
+			}
+		}
+		return;
+	}
+
+	if let Some(fmt_matches) = matches.subcommand_matches("fmt") {
+		let input_path = fmt_matches.value_of("input").unwrap();
+		let components = read_components(input_path, input_format(&[&matches, fmt_matches]));
+		let text = format_components(&components);
+		match fmt_matches.value_of("output") {
+			Some(output_path) => write_output(output_path, &text, false),
+			None => print!("{}", text)
+		}
+		return;
+	}
+
     let input_path = matches.value_of("input").unwrap();
     let output_path = matches.value_of("output").unwrap();
 
-    let mut input_file = File::open(input_path).unwrap();
+	// The exact solver reads the file on its own, since decimals like 0.1
+	// have no exact floating point value
+	if matches.is_present("exact") {
+		let lp = read_exact_lp(input_path, input_format(&[&matches]));
+		let output = match ExactSimplexSolver::new(lp).solve() {
+			Ok(solution) => format!("{:}", &solution),
+			Err(e) => {
+				writeln!(std::io::stderr(), "{}: {}", input_path, e).unwrap();
+				process::exit(1);
+			}
+		};
+		write_output(output_path, &output, matches.is_present("display"));
+		return;
+	}
 
-	let builder = Builder::new();
-	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let lp = read_lp(input_path, input_format(&[&matches]));
+
+	// Integer variables need the branch and bound driver, which takes none
+	// of the LP solver flags
+	if !lp.integers.is_empty() {
+		for flag in &["revised", "interior", "presolve", "sensitivity"] {
+			if matches.is_present(flag) {
+				writeln!(std::io::stderr(), "{}: --{} does not apply to an LP with integer variables", input_path, flag).unwrap();
+				process::exit(1);
+			}
+		}
+	}
 
-	let solver = SimplexSolver::new(lp);
-	let solution = solver.solve();
+	// Neither presolve nor the revised simplex method gives a sensitivity
+	// report. The interior point method gives one through its crossover.
+	let result = if !lp.integers.is_empty() {
+		BranchAndBound::new(lp).solve().map(|solution| format!("{:}", &solution))
+	} else if matches.is_present("presolve") {
+		Presolve::new(&lp).and_then(|presolve| {
+			if matches.is_present("revised") {
+				presolve.solve::<RevisedSimplexSolver>()
+			} else if matches.is_present("interior") {
+				presolve.solve::<InteriorPointSolver>()
+			} else {
+				presolve.solve::<SimplexSolver>()
+			}
+		}).map(|solution| format!("{:}", &solution))
+	} else if matches.is_present("revised") {
+		RevisedSimplexSolver::new(lp).solve().map(|solution| format!("{:}", &solution))
+	} else {
+		let result = if matches.is_present("interior") {
+			InteriorPointSolver::new(lp).solve()
+		} else {
+			SimplexSolver::new(lp).solve()
+		};
+		result.map(|solution| {
+			let mut output = format!("{:}", &solution);
+			if matches.is_present("sensitivity") {
+				if let Some(ref report) = solution.sensitivity {
+					output.push_str(&format!("\n{:}", report));
+				}
+			}
+			output
+		})
+	};
+	let output = match result {
+		Ok(output) => output,
+		Err(e) => {
+			writeln!(std::io::stderr(), "{}: {}", input_path, e).unwrap();
+			process::exit(1);
+		}
+	};
 
+	write_output(output_path, &output, matches.is_present("display"));
+}
+
+fn write_output(output_path: &str, output: &str, display: bool) {
 	let mut output_file = File::create(&output_path).unwrap();
-	output_file.write(format!("{:}", &solution).as_bytes()).expect("Failed to write to destination file");
+	output_file.write(output.as_bytes()).expect("Failed to write to destination file");
+
+	if display {
+		println!("{:}", output);
+	}
+}
+
+// Syntax of the source file
+enum Format {
+	Rulp,
+	Cplex,
+	Mps,
+	FixedMps
+}
 
-	if matches.is_present("display") {
-		println!("{:}", &solution);
+// The format flags may be given before a subcommand or after it, and only
+// appear in the matches of the one they follow
+fn input_format(matches: &[&ArgMatches]) -> Format {
+	let present = |flag: &str| matches.iter().any(|matches| matches.is_present(flag));
+	if present("cplex") {
+		Format::Cplex
+	} else if present("mps") {
+		Format::Mps
+	} else if present("fixed-mps") {
+		Format::FixedMps
+	} else {
+		Format::Rulp
+	}
+}
+
+fn read_components(input_path: &str, format: Format) -> Components {
+	let mut input_file = File::open(input_path).unwrap();
+
+	let result = match format {
+		Format::Rulp => Parser::parse_components_from_file(&mut input_file),
+		Format::Cplex => CplexParser::parse_components_from_file(&mut input_file),
+		Format::Mps => MpsParser::parse_components_from_file(&mut input_file),
+		Format::FixedMps => FixedMpsParser::parse_components_from_file(&mut input_file)
+	};
+	match result {
+		Ok(components) => components,
+		Err(e) => {
+			writeln!(std::io::stderr(), "{}: {}", input_path, e).unwrap();
+			process::exit(1);
+		}
+	}
+}
+
+fn read_lp(input_path: &str, format: Format) -> Lp {
+	let mut input_file = File::open(input_path).unwrap();
+
+	let builder = Builder::new();
+	let result = match format {
+		Format::Rulp => Parser::lp_from_file(&mut input_file, builder),
+		Format::Cplex => CplexParser::lp_from_file(&mut input_file, builder),
+		Format::Mps => MpsParser::lp_from_file(&mut input_file, builder),
+		Format::FixedMps => FixedMpsParser::lp_from_file(&mut input_file, builder)
+	};
+	match result {
+		Ok(lp) => lp,
+		Err(e) => {
+			writeln!(std::io::stderr(), "{}: {}", input_path, e).unwrap();
+			process::exit(1);
+		}
+	}
+}
+
+fn read_exact_lp(input_path: &str, format: Format) -> ExactLp {
+	let mut input_file = File::open(input_path).unwrap();
+
+	let builder = Builder::new();
+	let result = match format {
+		Format::Rulp => Parser::exact_lp_from_file(&mut input_file, builder),
+		Format::Cplex => CplexParser::exact_lp_from_file(&mut input_file, builder),
+		Format::Mps => MpsParser::exact_lp_from_file(&mut input_file, builder),
+		Format::FixedMps => FixedMpsParser::exact_lp_from_file(&mut input_file, builder)
+	};
+	match result {
+		Ok(lp) => lp,
+		Err(e) => {
+			writeln!(std::io::stderr(), "{}: {}", input_path, e).unwrap();
+			process::exit(1);
+		}
 	}
-}	
\ No newline at end of file
+}
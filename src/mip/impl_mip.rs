@@ -0,0 +1,503 @@
+use super::*;
+use std::f64::INFINITY;
+
+// A subproblem of the search: the Lp with tightened column bounds
+struct Node {
+	lower: Vec<f64>,
+	upper: Vec<f64>,
+	// Objective of the parent's relaxation in Max form, which no integral
+	// solution of this node can beat
+	bound: f64
+}
+
+impl MipSolverBase for BranchAndBound {
+	/// Constructor for BranchAndBound struct with default options.
+	fn new(lp: Lp) -> Self {
+		BranchAndBound::with_options(lp, MipOptions::default())
+	}
+
+	/// Searches for the best solution of the Lp whose `integers` columns
+	/// take integer values.
+	///
+	/// Each node solves the LP relaxation of the Lp with tightened bounds,
+	/// and branches on the integer variable whose value is furthest from
+	/// integral. Nodes are explored depth first. An unbounded relaxation
+	/// makes the whole search Unbounded.
+	///
+	/// Up to `cut_rounds` rounds of Gomory cuts are first added to the root
+	/// relaxation, each re-optimized from the previous basis by the dual
+	/// simplex method. With enough rounds a pure integer Lp may be solved
+	/// by cuts alone.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate rulp;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::mip::{BranchAndBound, MipSolverBase};
+	/// use rulp::solver::Status;
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x integer;
+	/// 	var y integer;
+	/// 	maximize obj: x + y;
+	/// 	subject to first: 2*x + 2*y <= 9;
+	/// 	subject to second: x <= 1.5;
+	/// ";
+	/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// let solution = BranchAndBound::new(lp).solve().unwrap();
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// assert_eq!(solution.objective, Some(4.));
+	/// assert_eq!(solution.gap, Some(0.));
+	/// # }
+	/// ```
+	fn solve(&self) -> Result<MipSolution, SolveError> {
+		let mut nodes = 0;
+		let mut iterations = 0;
+		let (lp, basis) = self.add_root_cuts(&mut nodes, &mut iterations)?;
+
+		// Cut slacks are appended after every column of the original Lp
+		let cols = self.lp.A.cols();
+		let mut solution = BranchAndBound::with_options(lp, self.options.clone()).search(basis, nodes, iterations)?;
+		solution.lp = self.lp.clone();
+		solution.values = solution.values.map(|mut values| {
+			values.truncate(cols);
+			values
+		});
+
+		Ok(solution)
+	}
+}
+
+impl BranchAndBound {
+	/// Constructor for BranchAndBound struct with non-default options.
+	pub fn with_options(lp: Lp, options: MipOptions) -> Self {
+		BranchAndBound {
+			lp: lp,
+			options: options
+		}
+	}
+
+	// Adds rounds of cuts to the root relaxation until a limit is hit or
+	// no cut is found. Returns the strengthened Lp with the optimal basis
+	// of its relaxation when it is known.
+	fn add_root_cuts(&self, nodes: &mut usize, iterations: &mut usize) -> Result<(Lp, Option<Basis>), SolveError> {
+		let mut lp = self.lp.clone();
+		let mut basis = None;
+		let mut rounds = 0;
+		while self.options.cut_rounds.map_or(true, |limit| rounds < limit) {
+			let options = match self.relaxation_options(*nodes, *iterations) {
+				Some(options) => options,
+				None => break
+			};
+			let mut solver = SimplexSolver::with_options(lp.clone(), options);
+			solver.warm_start = basis.take();
+			let solution = solver.solve()?;
+			*nodes += 1;
+			*iterations += solution.iterations;
+			if solution.status != Status::Optimal {
+				break;
+			}
+
+			let optimal = solution.basis.expect("Optimal solutions have a basis");
+			let values = solution.values.expect("Optimal solutions have values");
+			let cuts = match self.branching_column(&column_values(&lp, &values)) {
+				Some(_) => solver.gomory_cuts(&optimal, self.options.integrality_tolerance)?,
+				None => vec![]
+			};
+			if cuts.is_empty() {
+				basis = Some(optimal);
+				break;
+			}
+
+			// The slack of each cut starts basic at a negative value
+			let mut statuses = optimal.statuses;
+			statuses.extend(cuts.iter().map(|_| VarStatus::Basic));
+			basis = Some(Basis { statuses: statuses });
+			lp = add_cuts(&lp, &cuts);
+			rounds += 1;
+		}
+
+		Ok((lp, basis))
+	}
+
+	// Options for the next relaxation, or None once the node or iteration
+	// limit is reached
+	fn relaxation_options(&self, nodes: usize, iterations: usize) -> Option<SimplexOptions> {
+		let remaining = self.options.iteration_limit.map(|limit| limit.saturating_sub(iterations));
+		if self.options.node_limit.map_or(false, |limit| nodes >= limit) || remaining == Some(0) {
+			return None;
+		}
+
+		let mut options = self.options.simplex.clone();
+		options.max_iterations = match (options.max_iterations, remaining) {
+			(Some(max), Some(remaining)) => Some(max.min(remaining)),
+			(max, remaining) => max.or(remaining)
+		};
+		Some(options)
+	}
+
+	// Branch and bound from the root, whose relaxation may be warm started
+	fn search(&self, mut root_basis: Option<Basis>, mut nodes: usize, mut iterations: usize) -> Result<MipSolution, SolveError> {
+		let sense = match self.lp.optimization {
+			Optimization::Max => 1.,
+			Optimization::Min => -1.
+		};
+
+		let mut open = vec![Node {
+			lower: self.lp.lower.clone(),
+			upper: self.lp.upper.clone(),
+			bound: INFINITY
+		}];
+		// Objective in Max form and values of the best integral solution
+		let mut incumbent: Option<(f64, Vec<f64>)> = None;
+		let mut stopped = None;
+
+		while let Some(node) = open.pop() {
+			if self.is_pruned(node.bound, &incumbent) {
+				continue;
+			}
+			let options = match self.relaxation_options(nodes, iterations) {
+				Some(options) => options,
+				None => {
+					let node_limit = self.options.node_limit.map_or(false, |limit| nodes >= limit);
+					open.push(node);
+					stopped = Some(if node_limit { Status::NodeLimit } else { Status::IterationLimit });
+					break;
+				}
+			};
+			let mut relaxation = self.lp.clone();
+			relaxation.lower = node.lower.clone();
+			relaxation.upper = node.upper.clone();
+
+			let mut solver = SimplexSolver::with_options(relaxation, options);
+			solver.warm_start = root_basis.take();
+			let solution = solver.solve()?;
+			nodes += 1;
+			iterations += solution.iterations;
+			match solution.status {
+				Status::Optimal => {},
+				Status::Infeasible => continue,
+				Status::Unbounded => {
+					open.clear();
+					incumbent = None;
+					stopped = Some(Status::Unbounded);
+					break;
+				},
+				status => {
+					open.push(node);
+					stopped = Some(status);
+					break;
+				}
+			}
+
+			let objective = sense * solution.objective.expect("Optimal solutions have an objective");
+			if self.is_pruned(objective, &incumbent) {
+				continue;
+			}
+			let values = solution.values.expect("Optimal solutions have values");
+			let columns = column_values(&self.lp, &values);
+			let (col, value) = match self.branching_column(&columns) {
+				Some(branch) => branch,
+				None => {
+					incumbent = Some((objective, self.round_integers(values)));
+					continue;
+				}
+			};
+
+			// The child on the side nearer the value is explored first
+			let mut down = Node { lower: node.lower.clone(), upper: node.upper.clone(), bound: objective };
+			self.restrict(&mut down, col, value.floor(), true);
+			let mut up = Node { lower: node.lower, upper: node.upper, bound: objective };
+			self.restrict(&mut up, col, value.ceil(), false);
+			let children = if value - value.floor() > 0.5 { vec![down, up] } else { vec![up, down] };
+			for mut child in children {
+				if self.round_bounds(&mut child) {
+					open.push(child);
+				}
+			}
+		}
+
+		let status = match stopped {
+			Some(status) => status,
+			None if incumbent.is_some() => Status::Optimal,
+			None => Status::Infeasible
+		};
+		let best_bound = match status {
+			Status::Optimal => incumbent.as_ref().map(|&(objective, _)| objective),
+			Status::Infeasible | Status::Unbounded => None,
+			_ => {
+				let bound = open.iter().fold(incumbent.as_ref().map_or(-INFINITY, |&(objective, _)| objective),
+					|best, node| best.max(node.bound));
+				if bound.is_finite() { Some(bound) } else { None }
+			}
+		};
+
+		let objective = incumbent.as_ref().map(|&(objective, _)| sense * objective);
+		let gap = match (objective, best_bound) {
+			(_, _) if status == Status::Optimal => Some(0.),
+			(Some(objective), Some(bound)) => Some((sense * bound - objective).abs() / objective.abs().max(1.)),
+			_ => None
+		};
+
+		Ok(MipSolution {
+			lp: self.lp.clone(),
+			values: incumbent.map(|(_, values)| values),
+			objective: objective,
+			best_bound: best_bound.map(|bound| sense * bound),
+			gap: gap,
+			status: status,
+			nodes: nodes,
+			iterations: iterations
+		})
+	}
+
+	// A node is pruned once it cannot beat the incumbent by more than the gap tolerance
+	fn is_pruned(&self, bound: f64, incumbent: &Option<(f64, Vec<f64>)>) -> bool {
+		match *incumbent {
+			Some((objective, _)) => bound - objective <= self.options.gap_tolerance * objective.abs().max(1.),
+			None => false
+		}
+	}
+
+	// Rounds the bounds of integer columns inwards, which tightens any
+	// fractional bounds given in the Lp. Returns false if some column is
+	// left without a value.
+	fn round_bounds(&self, node: &mut Node) -> bool {
+		let tol = self.options.integrality_tolerance;
+		for &col in &self.lp.integers {
+			node.lower[col] = (node.lower[col] - tol).ceil();
+			node.upper[col] = (node.upper[col] + tol).floor();
+		}
+
+		node.lower.iter().zip(node.upper.iter()).all(|(lower, upper)| lower <= upper)
+	}
+
+	// Picks the integer var whose value is furthest from integral, along
+	// with that value
+	fn branching_column(&self, columns: &Vec<f64>) -> Option<(usize, f64)> {
+		let mut best = None;
+		let mut best_distance = self.options.integrality_tolerance;
+		for &col in &self.lp.integers {
+			let value = match self.split_negative(col) {
+				Some(negative) => columns[col] - columns[negative],
+				None => columns[col]
+			};
+			let fraction = value - value.floor();
+			let distance = fraction.min(1. - fraction);
+			if distance > best_distance {
+				best = Some((col, value));
+				best_distance = distance;
+			}
+		}
+
+		best
+	}
+
+	// Limits the var in col to at most (down) or at least (up) an integer.
+	// Either part of a split var can carry the limit, so long as the
+	// other part is pinned to zero when the limit has the wrong sign for it.
+	fn restrict(&self, node: &mut Node, col: usize, limit: f64, down: bool) {
+		match (self.split_negative(col), down) {
+			(None, true) => node.upper[col] = node.upper[col].min(limit),
+			(None, false) => node.lower[col] = node.lower[col].max(limit),
+			(Some(_), true) if limit >= 0. => node.upper[col] = node.upper[col].min(limit),
+			(Some(negative), true) => {
+				node.upper[col] = 0.;
+				node.lower[negative] = node.lower[negative].max(-limit);
+			},
+			(Some(negative), false) if limit <= 0. => node.upper[negative] = node.upper[negative].min(-limit),
+			(Some(negative), false) => {
+				node.upper[negative] = 0.;
+				node.lower[col] = node.lower[col].max(limit);
+			}
+		}
+	}
+
+	fn split_negative(&self, col: usize) -> Option<usize> {
+		self.lp.substitutions.iter().filter_map(|substitution| match *substitution {
+			Substitution::Split { positive, negative } if positive == col => Some(negative),
+			_ => None
+		}).next()
+	}
+
+	fn round_integers(&self, mut values: Vec<f64>) -> Vec<f64> {
+		for &col in &self.lp.integers {
+			values[col] = values[col].round();
+		}
+
+		values
+	}
+}
+
+/// Adds each cut to a copy of the Lp as a `cut_#` constraint with its own
+/// `slack_#` column, numbered by the row of the cut.
+///
+/// The slack columns are appended after every existing column, so a Basis
+/// of the Lp extended with a Basic status for each slack spans the new Lp.
+pub fn add_cuts(lp: &Lp, cuts: &Vec<Cut>) -> Lp {
+	let rows = lp.A.rows();
+	let cols = lp.A.cols();
+	let mut triplets: Vec<(usize, usize, f64)> = lp.A.triplets().collect();
+
+	let mut cut_lp = lp.clone();
+	for (k, cut) in cuts.iter().enumerate() {
+		for (col, &coefficient) in cut.coefficients.iter().enumerate() {
+			triplets.push((rows + k, col, coefficient));
+		}
+		triplets.push((rows + k, cols + k, 1.));
+
+		cut_lp.b.push(cut.rhs);
+		cut_lp.c.push(0.);
+		cut_lp.vars.push(format!("slack_{}", rows + k));
+		cut_lp.constraints.push(format!("cut_{}", rows + k));
+		cut_lp.lower.push(0.);
+		cut_lp.upper.push(INFINITY);
+	}
+	cut_lp.A = SparseMatrix::from_triplets(rows + cuts.len(), cols + cuts.len(), triplets);
+	cut_lp.num_artificial_vars += cuts.len();
+
+	cut_lp
+}
+
+// Solutions report each substituted var in place of its column, but the
+// search branches on the columns themselves
+fn column_values(lp: &Lp, values: &Vec<f64>) -> Vec<f64> {
+	let mut columns = values.clone();
+	for substitution in &lp.substitutions {
+		match *substitution {
+			Substitution::Negated { col } => {
+				columns[col] = -columns[col];
+			},
+			Substitution::Split { positive, negative } => {
+				columns[positive] += columns[negative];
+			}
+		}
+	}
+
+	columns
+}
+
+#[cfg(test)]
+mod mip_tests {
+	use super::*;
+	use assert_approx_eq::*;
+	use std::f64::INFINITY;
+
+	// max 5a + 4b s.t. 6a + 4b <= 24, a + 2b <= 6 with a, b integer. The
+	// relaxation is optimal at a = 3, b = 1.5.
+	fn create_knapsack_lp() -> Lp {
+		Lp {
+			A: matrix![6., 4., 1., 0.;
+					   1., 2., 0., 1.].into(),
+			b: vec![24., 6.],
+			c: vec![5., 4., 0., 0.],
+			optimization: Optimization::Max,
+			vars: vec!["a", "b", "slack_0", "slack_1"].iter().map(|v| v.to_string()).collect(),
+			constraints: vec!["c1".to_string(), "c2".to_string()],
+			lower: vec![0.; 4],
+			upper: vec![INFINITY; 4],
+			substitutions: vec![],
+			integers: vec![0, 1],
+			num_artificial_vars: 2
+		}
+	}
+
+	#[test]
+	fn solve_test() {
+		let solution = BranchAndBound::new(create_knapsack_lp()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 20.);
+		assert_approx_eq!(solution.best_bound.unwrap(), 20.);
+		assert_eq!(solution.gap, Some(0.));
+
+		let values = solution.values.unwrap();
+		assert_eq!(&values[.. 2], &[4., 0.]);
+		assert!(solution.nodes > 1);
+	}
+
+	#[test]
+	fn cut_rounds_test() {
+		let mut options = MipOptions::default();
+		options.cut_rounds = None;
+		let solution = BranchAndBound::with_options(create_knapsack_lp(), options).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 20.);
+
+		// The cut slacks are left out of the reported values
+		let values = solution.values.unwrap();
+		assert_eq!(values.len(), 4);
+		assert_eq!(&values[.. 2], &[4., 0.]);
+
+		// A single round tightens the root bound below 21
+		let mut options = MipOptions::default();
+		options.cut_rounds = Some(1);
+		options.node_limit = Some(2);
+		let solution = BranchAndBound::with_options(create_knapsack_lp(), options).solve().unwrap();
+		assert!(solution.best_bound.unwrap() < 21. - 1e-6);
+	}
+
+	#[test]
+	fn add_cuts_test() {
+		let cut = Cut { coefficients: vec![1., 1., 0., 0.], rhs: 4. };
+		let lp = add_cuts(&create_knapsack_lp(), &vec![cut]);
+		assert_eq!(lp.A.to_dense(), matrix![6., 4., 1., 0., 0.;
+								 1., 2., 0., 1., 0.;
+								 1., 1., 0., 0., 1.]);
+		assert_eq!(lp.b, vec![24., 6., 4.]);
+		assert_eq!(lp.vars[4], "slack_2");
+		assert_eq!(lp.constraints[2], "cut_2");
+		assert_eq!(lp.num_artificial_vars, 3);
+	}
+
+	#[test]
+	fn infeasible_test() {
+		// 2a == 1 has no integer solution although its relaxation does
+		let lp = Lp {
+			A: matrix![2.].into(),
+			b: vec![1.],
+			c: vec![1.],
+			optimization: Optimization::Min,
+			vars: vec!["a".to_string()],
+			constraints: vec!["half".to_string()],
+			lower: vec![0.],
+			upper: vec![INFINITY],
+			substitutions: vec![],
+			integers: vec![0],
+			num_artificial_vars: 0
+		};
+		let solution = BranchAndBound::new(lp).solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert_eq!(solution.values, None);
+		assert_eq!(solution.nodes, 3);
+	}
+
+	#[test]
+	fn node_limit_test() {
+		let mut options = MipOptions::default();
+		options.node_limit = Some(1);
+		let solution = BranchAndBound::with_options(create_knapsack_lp(), options).solve().unwrap();
+
+		// Only the root relaxation is solved, which is fractional
+		assert_eq!(solution.status, Status::NodeLimit);
+		assert_eq!(solution.nodes, 1);
+		assert_eq!(solution.values, None);
+		assert_approx_eq!(solution.best_bound.unwrap(), 21.);
+		assert_eq!(solution.gap, None);
+
+		let mut options = MipOptions::default();
+		options.iteration_limit = Some(1);
+		let solution = BranchAndBound::with_options(create_knapsack_lp(), options).solve().unwrap();
+		assert_eq!(solution.status, Status::IterationLimit);
+		assert_eq!(solution.iterations, 1);
+	}
+
+	#[test]
+	fn invalid_integers_test() {
+		let mut lp = create_knapsack_lp();
+		lp.integers = vec![6];
+		assert_eq!(BranchAndBound::new(lp).solve(), Err(SolveError::InvalidInteger { col: 6 }));
+	}
+}
@@ -0,0 +1,145 @@
+//! The mip module.
+//!
+//! This code solves mixed integer linear programs,
+//! whose integer columns are listed in `Lp::integers`,
+//! by branch and bound over their LP relaxations,
+//! optionally strengthened by Gomory cuts.
+
+use lp::{Lp, Optimization, SparseMatrix, Substitution};
+use solver::{Basis, Cut, SimplexOptions, SimplexSolver, SolveError, SolverBase, Status, VarStatus};
+use std::fmt;
+
+mod impl_mip;
+
+pub use self::impl_mip::add_cuts;
+
+/// Options controlling a BranchAndBound search.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MipOptions {
+	/// Options for the SimplexSolver run on every relaxation. The time
+	/// limit applies to each relaxation separately.
+	pub simplex: SimplexOptions,
+	/// Maximum number of relaxations solved.
+	pub node_limit: Option<usize>,
+	/// Maximum number of pivots over all relaxations.
+	pub iteration_limit: Option<usize>,
+	/// Maximum number of rounds of Gomory cuts added to the root relaxation
+	/// before branching. `None` adds cuts until the root relaxation is
+	/// integral or no more cuts can be derived.
+	pub cut_rounds: Option<usize>,
+	/// Distance from the nearest integer within which a value counts as integral.
+	pub integrality_tolerance: f64,
+	/// Gap between the best bound and the incumbent at which the incumbent
+	/// is accepted as optimal, relative to the incumbent's objective or
+	/// absolute when that objective is below 1 in magnitude.
+	pub gap_tolerance: f64
+}
+
+/// Result of a BranchAndBound search.
+///
+/// When a limit is hit the values are those of the best integral
+/// solution found so far, if any.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MipSolution {
+	lp: Lp,
+	/// Values of the incumbent, the best integral solution found.
+	pub values: Option<Vec<f64>>,
+	/// Objective of the incumbent.
+	pub objective: Option<f64>,
+	/// Best objective any integral solution can reach, taken from the
+	/// relaxations of the nodes left open.
+	pub best_bound: Option<f64>,
+	/// `|best_bound - objective| / max(|objective|, 1)`, so absolute for
+	/// an objective below 1 in magnitude as `gap_tolerance` is, or zero
+	/// once the incumbent is proved optimal.
+	pub gap: Option<f64>,
+	pub status: Status,
+	/// Number of relaxations solved, including those of cut rounds.
+	pub nodes: usize,
+	/// Number of pivots over all relaxations.
+	pub iterations: usize
+}
+
+pub trait MipSolverBase {
+	fn new(lp: Lp) -> Self;
+	fn solve(&self) -> Result<MipSolution, SolveError>;
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BranchAndBound {
+	lp: Lp,
+	pub options: MipOptions
+}
+
+impl Default for MipOptions {
+	fn default() -> Self {
+		MipOptions {
+			simplex: SimplexOptions::default(),
+			node_limit: None,
+			iteration_limit: None,
+			cut_rounds: Some(0),
+			integrality_tolerance: 1e-6,
+			gap_tolerance: 1e-6
+		}
+	}
+}
+
+impl MipSolution {
+	fn write_values(&self, f: &mut fmt::Formatter) {
+		if let Some(ref vals) = self.values {
+			for i in 0 .. vals.len() - self.lp.num_artificial_vars {
+				let negative_part = self.lp.substitutions.iter().any(|substitution| match *substitution {
+					Substitution::Split { negative, .. } => negative == i,
+					_ => false
+				});
+				if !negative_part {
+					writeln!(f, "{:}: {:?}", self.lp.vars[i], vals[i]).unwrap();
+				}
+			}
+		}
+	}
+}
+
+impl fmt::Display for MipSolution {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "").unwrap();
+		match self.status {
+			Status::Optimal => {
+				if let Some(obj) = self.objective {
+					writeln!(f, "Optimal objective: {:}", obj).unwrap();
+				}
+				self.write_values(f);
+			},
+			Status::IterationLimit | Status::TimeLimit | Status::Cancelled | Status::NodeLimit => {
+				let reason = match self.status {
+					Status::IterationLimit => "Iteration limit reached",
+					Status::TimeLimit => "Time limit reached",
+					Status::NodeLimit => "Node limit reached",
+					_ => "Cancelled"
+				};
+				writeln!(f, "{}", reason).unwrap();
+				if let Some(obj) = self.objective {
+					writeln!(f, "Best objective: {:}", obj).unwrap();
+				}
+				if let Some(bound) = self.best_bound {
+					writeln!(f, "Best bound: {:}", bound).unwrap();
+				}
+				if let Some(gap) = self.gap {
+					writeln!(f, "Gap: {:}", gap).unwrap();
+				}
+				self.write_values(f);
+			},
+			Status::Infeasible => {
+				writeln!(f, "Infeasible").unwrap();
+			},
+			Status::Unbounded => {
+				writeln!(f, "Unbounded").unwrap();
+			},
+			Status::NumericalFailure => {
+				writeln!(f, "Numerical failure").unwrap();
+			}
+		}
+
+		write!(f, "")
+	}
+}
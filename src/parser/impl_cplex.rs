@@ -0,0 +1,1033 @@
+use super::*;
+use super::impl_parser::{exact_lp, ExactModel, ExactRow, Statement};
+use builder::Relation;
+use lp::{Optimization, Substitution};
+use num_traits::{One, Zero};
+use std::collections::HashSet;
+use std::f64::{INFINITY, NEG_INFINITY};
+use utils::read_file_contents;
+
+// Section headers, matched case-insensitively at the start of a line
+const SECTIONS: &[(&str, Section)] = &[
+	("maximize", Section::Objective(true)),
+	("maximum", Section::Objective(true)),
+	("max", Section::Objective(true)),
+	("minimize", Section::Objective(false)),
+	("minimum", Section::Objective(false)),
+	("min", Section::Objective(false)),
+	("subject to", Section::Constraints),
+	("such that", Section::Constraints),
+	("s.t.", Section::Constraints),
+	("st.", Section::Constraints),
+	("st", Section::Constraints),
+	("bounds", Section::Bounds),
+	("bound", Section::Bounds),
+	("generals", Section::General),
+	("general", Section::General),
+	("gen", Section::General),
+	("binaries", Section::Binary),
+	("binary", Section::Binary),
+	("bin", Section::Binary),
+	("end", Section::End)
+];
+
+// Written lines are broken before the term that would take them past this
+const LINE_LENGTH: usize = 72;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Section {
+	Objective(bool),
+	Constraints,
+	Bounds,
+	General,
+	Binary,
+	End
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Kind {
+	Section(Section),
+	Name,
+	Number,
+	Colon,
+	Sign,
+	Relation
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Token<'a> {
+	kind: Kind,
+	text: &'a str,
+	line: usize
+}
+
+// A bound with its exact value, which is None for an infinite bound
+type Bound = (f64, Option<BigRational>);
+
+// Terms with their coefficients read exactly
+type ExactTerms = Vec<(String, BigRational)>;
+
+// The parts of a CPLEX LP file, each with the line it was read from, and
+// the rows and objective also read exactly
+struct Model {
+	variables: Vec<(Variable, usize)>,
+	constraints: Vec<(Constraint, ExactRow, usize)>,
+	objective: Option<(Objective, ExactTerms, usize)>,
+	bounds: HashMap<String, ((Bound, Bound), usize)>,
+	integers: Vec<(String, usize)>
+}
+
+struct Reader<'a> {
+	source: &'a str,
+	lines: Vec<&'a str>,
+	tokens: Vec<Token<'a>>,
+	next: usize,
+	declared: HashSet<String>,
+	model: Model
+}
+
+impl ParserBase for CplexParser {
+	/// Constructor for Components struct.
+	///
+	/// Takes CPLEX LP text to be parsed. Variables are listed in the order
+	/// they first appear.
+	fn parse_components_from_text(text: &str) -> Result<Components, ParseError> {
+		let model = Reader::new(text)?.read()?;
+		let objective = match model.objective {
+			Some((objective, _, _)) => objective,
+			None => return Err(missing_objective(text))
+		};
+
+		Ok(Components {
+			variables: model.variables.into_iter().map(|(v, _)| v).collect(),
+			constraints: model.constraints.into_iter().map(|(c, _, _)| c).collect(),
+			objective: objective,
+			bounds: model.bounds.into_iter().map(|(name, (((lower, _), (upper, _)), _))| (name, (lower, upper))).collect(),
+			integers: model.integers.into_iter().map(|(name, _)| name).collect()
+		})
+	}
+
+	/// Constructor for Components struct.
+	///
+	/// Takes a CPLEX LP file to be read.
+	fn parse_components_from_file(file: &mut File) -> Result<Components, ParseError> {
+		Self::parse_components_from_text(&read_file_contents(file))
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// Takes CPLEX LP text to be parsed and a Builder struct. Errors give
+	/// the line they occurred on as their statement.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::lp::Optimization;
+	/// use rulp::parser::{CplexParser, ParserBase};
+	///
+	/// let text_problem = "
+	/// 	\\ Pack the most valuable items into a bag holding 10 kg
+	/// 	Maximize
+	/// 	 value: 10 tent + 7 stove + 5 camera
+	/// 	Subject To
+	/// 	 weight: 5 tent + 4 stove + 3 camera <= 10
+	/// 	Bounds
+	/// 	 camera <= 2
+	/// 	Binary
+	/// 	 tent stove
+	/// 	End
+	/// ";
+	/// let lp = CplexParser::lp_from_text(text_problem, Builder::new()).unwrap();
+	/// assert_eq!(lp.optimization, Optimization::Max);
+	/// assert_eq!(lp.vars, vec!["tent", "stove", "camera", "slack_0"]);
+	/// assert_eq!(lp.upper[.. 3], [1., 1., 2.]);
+	/// assert_eq!(lp.integers, vec![0, 1]);
+	/// ```
+	fn lp_from_text<B: BuilderBase>(text: &str, mut builder: B) -> Result<Lp, ParseError> {
+		let model = Reader::new(text)?.read()?;
+		let statement = |line: usize| Statement {
+			index: line,
+			source: text,
+			text: text.split('\n').nth(line).unwrap_or("")
+		};
+
+		for &(ref v, line) in &model.variables {
+			builder.add_variable(v.clone()).map_err(|e| statement(line).build_error(e))?;
+		}
+		for &(ref v, _) in &model.variables {
+			if let Some(&(((lower, _), (upper, _)), line)) = model.bounds.get(&v.name) {
+				builder.set_bounds(&v.name, lower, upper).map_err(|e| statement(line).build_error(e))?;
+			}
+		}
+		for &(ref name, line) in &model.integers {
+			builder.set_integer(name).map_err(|e| statement(line).build_error(e))?;
+		}
+		for &(ref c, _, line) in &model.constraints {
+			builder.add_constraint(c.clone()).map_err(|e| statement(line).build_error(e))?;
+		}
+		match model.objective {
+			Some((ref o, _, line)) => builder.add_objective(o.clone()).map_err(|e| statement(line).build_error(e))?,
+			None => return Err(missing_objective(text))
+		}
+
+		let lines = text.split('\n').count();
+		builder.build_lp().map_err(|e| Statement::end_of(text, lines).build_error(e))
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// Takes a CPLEX LP file to be read and a Builder struct.
+	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<Lp, ParseError> {
+		Self::lp_from_text(&read_file_contents(file), builder)
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Builds the Lp as `lp_from_text` does, reading every number as the
+	/// exact decimal written. A coefficient given for the same variable
+	/// more than once in a row is summed exactly.
+	fn exact_lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<ExactLp, ParseError> {
+		let lp = Self::lp_from_text(text, builder)?;
+		let model = Reader::new(text)?.read()?;
+		let objective = model.objective.expect("Objective checked in lp_from_text").1;
+
+		Ok(exact_lp(&lp, &ExactModel {
+			rows: model.constraints.into_iter().map(|(_, row, _)| row).collect(),
+			objective: objective,
+			bounds: model.bounds.into_iter().map(|(name, (((_, lower), (_, upper)), _))| (name, (lower, upper))).collect()
+		}))
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Takes a CPLEX LP file to be read and a Builder struct.
+	fn exact_lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<ExactLp, ParseError> {
+		Self::exact_lp_from_text(&read_file_contents(file), builder)
+	}
+}
+
+impl<'a> Reader<'a> {
+	fn new(source: &'a str) -> Result<Self, ParseError> {
+		let lines: Vec<&str> = source.split('\n').collect();
+		let mut tokens = vec![];
+		for (index, &line) in lines.iter().enumerate() {
+			let statement = Statement { index: index, source: source, text: line };
+			let mut rest = match line.find('\\') {
+				Some(i) => &line[.. i],
+				None => line
+			}.trim_start();
+
+			if let Some(&(keyword, section)) = SECTIONS.iter().find(|&&(keyword, _)| is_section(rest, keyword)) {
+				tokens.push(Token { kind: Kind::Section(section), text: &rest[.. keyword.len()], line: index });
+				if section == Section::End {
+					break;
+				}
+				rest = &rest[keyword.len() ..];
+			}
+			tokenize(&statement, rest, &mut tokens)?;
+		}
+
+		Ok(Reader {
+			source: source,
+			lines: lines,
+			tokens: tokens,
+			next: 0,
+			declared: HashSet::new(),
+			model: Model {
+				variables: vec![],
+				constraints: vec![],
+				objective: None,
+				bounds: HashMap::new(),
+				integers: vec![]
+			}
+		})
+	}
+
+	fn read(mut self) -> Result<Model, ParseError> {
+		let mut section = None;
+		while let Some(token) = self.peek() {
+			if let Kind::Section(next) = token.kind {
+				self.next += 1;
+				section = Some(next);
+				if let Section::Objective(maximize) = next {
+					self.read_objective(maximize, token)?;
+				}
+				continue;
+			}
+
+			match section {
+				Some(Section::Constraints) => self.read_constraint()?,
+				Some(Section::Bounds) => self.read_bound()?,
+				Some(Section::General) | Some(Section::Binary) => {
+					let name = self.expect(Kind::Name)?;
+					self.declare(name);
+					self.model.integers.push((name.text.to_string(), name.line));
+					if section == Some(Section::Binary) {
+						self.set_bounds(name, Some((0., Some(BigRational::zero()))), Some((1., Some(BigRational::one()))));
+					}
+				},
+				_ => return Err(self.statement(token.line).unknown_statement())
+			}
+		}
+		Ok(self.model)
+	}
+
+	fn read_objective(&mut self, maximize: bool, header: Token<'a>) -> Result<(), ParseError> {
+		let name = self.read_label().unwrap_or_else(|| "obj".to_string());
+		let (variables, exact) = self.read_terms()?;
+		match self.peek() {
+			Some(Token { kind: Kind::Section(_), .. }) | None => {},
+			Some(_) => return Err(self.unexpected())
+		}
+
+		if let Some((ref existing, _, _)) = self.model.objective {
+			return Err(self.statement(header.line).build_error(BuildError::DuplicateObjective {
+				existing: existing.name.clone(),
+				duplicate: name
+			}));
+		}
+		self.model.objective = Some((Objective {
+			name: name,
+			variables: variables,
+			maximize: maximize
+		}, exact, header.line));
+		Ok(())
+	}
+
+	// Reads `name: terms relation constant`, naming unnamed constraints
+	// `c1`, `c2` and so on
+	fn read_constraint(&mut self) -> Result<(), ParseError> {
+		let line = self.tokens[self.next].line;
+		let number = self.model.constraints.len() + 1;
+		let name = self.read_label().unwrap_or_else(|| format!("c{}", number));
+		let (variables, exact) = self.read_terms()?;
+		let relation = relation_of(self.expect(Kind::Relation)?);
+		let (constant, exact_constant) = match self.read_value()? {
+			(constant, Some(exact)) => (constant, exact),
+			(_, None) => {
+				return Err(self.statement(self.tokens[self.next - 1].line).malformed_term(self.tokens[self.next - 1].text));
+			}
+		};
+
+		self.model.constraints.push((Constraint {
+			name: name,
+			variables: variables,
+			constant: constant,
+			relation: relation
+		}, ExactRow {
+			variables: exact,
+			constant: exact_constant,
+			range: None
+		}, line));
+		Ok(())
+	}
+
+	// Reads `x free`, `x relation value`, `value relation x` or
+	// `value relation x relation value`
+	fn read_bound(&mut self) -> Result<(), ParseError> {
+		let first = self.tokens[self.next];
+		if first.kind == Kind::Name && !is_infinity(first.text) {
+			self.next += 1;
+			self.declare(first);
+			match self.peek() {
+				Some(token) if token.kind == Kind::Name && token.text.eq_ignore_ascii_case("free") => {
+					self.next += 1;
+					self.set_bounds(first, Some((NEG_INFINITY, None)), Some((INFINITY, None)));
+				},
+				_ => {
+					let relation = relation_of(self.expect(Kind::Relation)?);
+					let value = self.read_value()?;
+					self.bound(first, relation, value);
+				}
+			}
+			return Ok(());
+		}
+
+		let value = self.read_value()?;
+		// `value <= x` bounds x as `x >= value` does
+		let relation = match relation_of(self.expect(Kind::Relation)?) {
+			Relation::LessThanOrEqual => Relation::GreaterThanOrEqual,
+			Relation::GreaterThanOrEqual => Relation::LessThanOrEqual,
+			Relation::Equal => Relation::Equal
+		};
+		let name = self.expect(Kind::Name)?;
+		self.declare(name);
+		self.bound(name, relation, value);
+
+		if let Some(token) = self.peek() {
+			if token.kind == Kind::Relation {
+				self.next += 1;
+				let value = self.read_value()?;
+				self.bound(name, relation_of(token), value);
+			}
+		}
+		Ok(())
+	}
+
+	// Applies `x relation value` to the bounds of x
+	fn bound(&mut self, name: Token<'a>, relation: Relation, value: Bound) {
+		match relation {
+			Relation::LessThanOrEqual => self.set_bounds(name, None, Some(value)),
+			Relation::GreaterThanOrEqual => self.set_bounds(name, Some(value), None),
+			Relation::Equal => self.set_bounds(name, Some(value.clone()), Some(value))
+		}
+	}
+
+	fn set_bounds(&mut self, name: Token<'a>, lower: Option<Bound>, upper: Option<Bound>) {
+		let entry = self.model.bounds.entry(name.text.to_string())
+			.or_insert((((0., Some(BigRational::zero())), (INFINITY, None)), name.line));
+		entry.1 = name.line;
+		if let Some(lower) = lower {
+			(entry.0).0 = lower;
+		}
+		if let Some(upper) = upper {
+			(entry.0).1 = upper;
+		}
+	}
+
+	// Reads a sum of terms such as `3 x - 2.5 y + z`, summing the
+	// coefficients of a variable given more than once, both in floating
+	// point and exactly
+	fn read_terms(&mut self) -> Result<(Vec<Variable>, ExactTerms), ParseError> {
+		let mut terms: Vec<Variable> = vec![];
+		let mut exact: ExactTerms = vec![];
+		loop {
+			let starts_term = match self.peek() {
+				Some(token) if token.kind == Kind::Sign => true,
+				Some(token) if terms.is_empty() => token.kind == Kind::Number || (token.kind == Kind::Name && !self.at_label()),
+				_ => false
+			};
+			if !starts_term {
+				return Ok((terms, exact));
+			}
+
+			let negative = self.read_signs();
+			let (mut coefficient, mut exact_coefficient) = match self.accept(Kind::Number) {
+				Some(token) => self.number(token)?,
+				None => (1., BigRational::one())
+			};
+			if negative {
+				coefficient = -coefficient;
+				exact_coefficient = -exact_coefficient;
+			}
+			let name = self.expect(Kind::Name)?;
+			self.declare(name);
+
+			match terms.iter().position(|v| v.name == name.text) {
+				Some(position) => {
+					terms[position].coefficient += coefficient;
+					exact[position].1 = exact[position].1.clone() + exact_coefficient;
+				},
+				None => {
+					terms.push(Variable {
+						name: name.text.to_string(),
+						coefficient: coefficient
+					});
+					exact.push((name.text.to_string(), exact_coefficient));
+				}
+			}
+		}
+	}
+
+	// Reads a number or infinity with any signs before it
+	fn read_value(&mut self) -> Result<Bound, ParseError> {
+		let negative = self.read_signs();
+		let sign = if negative { -1. } else { 1. };
+		match self.peek() {
+			Some(token) if token.kind == Kind::Name && is_infinity(token.text) => {
+				self.next += 1;
+				Ok((sign * INFINITY, None))
+			},
+			_ => {
+				let token = self.expect(Kind::Number)?;
+				let (value, exact) = self.number(token)?;
+				Ok(if negative { (-value, Some(-exact)) } else { (value, Some(exact)) })
+			}
+		}
+	}
+
+	// Reads any signs, returning whether they negate what follows
+	fn read_signs(&mut self) -> bool {
+		let mut negative = false;
+		while let Some(token) = self.accept(Kind::Sign) {
+			if token.text == "-" {
+				negative = !negative;
+			}
+		}
+		negative
+	}
+
+	fn read_label(&mut self) -> Option<String> {
+		if self.at_label() {
+			self.next += 2;
+			Some(self.tokens[self.next - 2].text.to_string())
+		} else {
+			None
+		}
+	}
+
+	fn at_label(&self) -> bool {
+		match (self.tokens.get(self.next), self.tokens.get(self.next + 1)) {
+			(Some(name), Some(colon)) => name.kind == Kind::Name && colon.kind == Kind::Colon,
+			_ => false
+		}
+	}
+
+	// Reads a number both as an f64 and exactly
+	fn number(&self, token: Token<'a>) -> Result<(f64, BigRational), ParseError> {
+		let statement = self.statement(token.line);
+		Ok((statement.parse_number(token.text)?, statement.parse_rational(token.text)?))
+	}
+
+	fn declare(&mut self, name: Token<'a>) {
+		if self.declared.insert(name.text.to_string()) {
+			self.model.variables.push((Variable {
+				name: name.text.to_string(),
+				coefficient: 0.
+			}, name.line));
+		}
+	}
+
+	fn peek(&self) -> Option<Token<'a>> {
+		self.tokens.get(self.next).cloned()
+	}
+
+	fn accept(&mut self, kind: Kind) -> Option<Token<'a>> {
+		match self.peek() {
+			Some(token) if token.kind == kind => {
+				self.next += 1;
+				Some(token)
+			},
+			_ => None
+		}
+	}
+
+	fn expect(&mut self, kind: Kind) -> Result<Token<'a>, ParseError> {
+		self.accept(kind).ok_or_else(|| self.unexpected())
+	}
+
+	// A MalformedTerm for the next token, or for the end of the text
+	fn unexpected(&self) -> ParseError {
+		match self.peek() {
+			Some(token) => self.statement(token.line).malformed_term(token.text),
+			None => {
+				let end = Statement::end_of(self.source, self.lines.len());
+				end.malformed_term(end.text)
+			}
+		}
+	}
+
+	fn statement(&self, line: usize) -> Statement<'a> {
+		Statement {
+			index: line,
+			source: self.source,
+			text: self.lines[line]
+		}
+	}
+}
+
+/// Writes Components in the CPLEX LP file format.
+///
+/// Variables that appear in no row and keep the default bounds are given
+/// an explicit `>= 0` bound, so that the file still declares them.
+///
+/// # Examples
+/// ```
+/// use rulp::parser::{components_to_cplex, CplexParser, Parser, ParserBase};
+///
+/// let components = Parser::parse_components_from_text("
+/// 	var x integer free;
+/// 	var y;
+/// 	minimize cost: 2*x + y;
+/// 	subject to floor: x + -1*y >= -1.5;
+/// ").unwrap();
+/// let text = components_to_cplex(&components);
+/// assert_eq!(text, "Minimize\n cost: 2 x + y\nSubject To\n floor: x - y >= -1.5\n\
+/// 				  Bounds\n x free\nGeneral\n x\nEnd\n");
+/// assert_eq!(CplexParser::parse_components_from_text(&text).unwrap().constraints, components.constraints);
+/// ```
+pub fn components_to_cplex(components: &Components) -> String {
+	let objective = &components.objective;
+	let first = components.variables.first().map(|v| v.name.as_str());
+	let mut text = String::new();
+
+	text.push_str(if objective.maximize { "Maximize\n" } else { "Minimize\n" });
+	write_row(&mut text, &objective.name, &objective.variables, None, None);
+	text.push_str("Subject To\n");
+	for constraint in &components.constraints {
+		let relation = match constraint.relation {
+			Relation::LessThanOrEqual => "<=",
+			Relation::GreaterThanOrEqual => ">=",
+			Relation::Equal => "="
+		};
+		write_row(&mut text, &constraint.name, &constraint.variables, first, Some((relation, constraint.constant)));
+	}
+
+	let used: HashSet<&str> = objective.variables.iter()
+		.chain(components.constraints.iter().flat_map(|c| c.variables.iter()))
+		.map(|v| v.name.as_str())
+		.collect();
+	let mut bounds = vec![];
+	let mut generals = vec![];
+	let mut binaries = vec![];
+	for v in &components.variables {
+		let name = v.name.as_str();
+		let (lower, upper) = *components.bounds.get(name).unwrap_or(&(0., INFINITY));
+		let integer = components.integers.iter().any(|i| i == name);
+		if integer && (lower, upper) == (0., 1.) {
+			binaries.push(name);
+			continue;
+		}
+		if integer {
+			generals.push(name);
+		}
+
+		let bound = if lower == upper {
+			format!("{} = {}", name, number(lower))
+		} else if (lower, upper) == (NEG_INFINITY, INFINITY) {
+			format!("{} free", name)
+		} else if upper == INFINITY && (lower != 0. || !used.contains(name)) {
+			format!("{} >= {}", name, number(lower))
+		} else if upper == INFINITY {
+			continue;
+		} else {
+			format!("{} <= {} <= {}", number(lower), name, number(upper))
+		};
+		bounds.push(bound);
+	}
+
+	for &(header, ref lines) in &[("Bounds", bounds), ("General", generals.iter().map(|s| s.to_string()).collect()),
+								  ("Binary", binaries.iter().map(|s| s.to_string()).collect())] {
+		if !lines.is_empty() {
+			text.push_str(header);
+			text.push('\n');
+			for line in lines {
+				text.push_str(&format!(" {}\n", line));
+			}
+		}
+	}
+	text.push_str("End\n");
+	text
+}
+
+/// Writes an Lp in the CPLEX LP file format.
+///
+/// The Lp is written as the variables and constraints it was built from:
+/// substitutions are undone and each slack or excess column becomes the
+/// relation of its row. The objective, whose name an Lp does not keep, is
+/// called `obj`. It lists every variable, with a zero coefficient if it
+/// has none, so that the columns are read back in the same order.
+pub fn lp_to_cplex(lp: &Lp) -> String {
+	let mut components = lp_components(lp);
+	let objective: Vec<Variable> = components.variables.iter().map(|v| {
+		components.objective.variables.iter()
+			.find(|o| o.name == v.name)
+			.cloned()
+			.unwrap_or(Variable { name: v.name.clone(), coefficient: 0. })
+	}).collect();
+	components.objective.variables = objective;
+	components_to_cplex(&components)
+}
+
+/// The Components an Lp was built from, as `lp_to_cplex` writes them. A
+/// row whose slack is bounded on both sides becomes two constraints,
+/// `name_lower` with its lower end and `name` with its upper end.
+pub fn lp_components(lp: &Lp) -> Components {
+	let cols = lp.A.cols();
+	let mut sign = vec![1.; cols];
+	let mut split = HashSet::new();
+	let mut negative_parts = HashSet::new();
+	for substitution in &lp.substitutions {
+		match *substitution {
+			Substitution::Negated { col } => sign[col] = -1.,
+			Substitution::Split { positive, negative } => {
+				split.insert(positive);
+				negative_parts.insert(negative);
+			}
+		}
+	}
+
+	// The Builder adds a slack or excess column with a single entry for
+	// each inequality, after every other column
+	let mut slacks: Vec<Option<(f64, usize)>> = vec![None; lp.A.rows()];
+	let mut is_slack = vec![false; cols];
+	for col in cols.saturating_sub(lp.num_artificial_vars) .. cols {
+		let entries: Vec<(usize, f64)> = lp.A.column(col).collect();
+		if entries.len() == 1 && lp.c[col] == 0. && slacks[entries[0].0].is_none() {
+			slacks[entries[0].0] = Some((entries[0].1, col));
+			is_slack[col] = true;
+		}
+	}
+	let structural = |col: usize| !is_slack[col] && !negative_parts.contains(&col);
+
+	let variable = |col: usize, coefficient: f64| Variable {
+		name: lp.vars[col].clone(),
+		coefficient: coefficient
+	};
+	let mut rows = vec![vec![]; lp.A.rows()];
+	for (row, col, value) in lp.A.triplets() {
+		if structural(col) {
+			rows[row].push(variable(col, sign[col] * value));
+		}
+	}
+
+	let mut constraints = vec![];
+	for (row, variables) in rows.into_iter().enumerate() {
+		let name = lp.constraints[row].clone();
+		let b = lp.b[row];
+		let (lower, upper) = match slacks[row] {
+			Some((coefficient, col)) => {
+				let ends = (b - coefficient * lp.lower[col], b - coefficient * lp.upper[col]);
+				(ends.0.min(ends.1), ends.0.max(ends.1))
+			},
+			None => (b, b)
+		};
+
+		let constraint = |name: String, constant: f64, relation: Relation| Constraint {
+			name: name,
+			variables: variables.clone(),
+			constant: constant,
+			relation: relation
+		};
+		if lower == upper {
+			constraints.push(constraint(name, lower, Relation::Equal));
+		} else if upper == INFINITY && lower != NEG_INFINITY {
+			constraints.push(constraint(name, lower, Relation::GreaterThanOrEqual));
+		} else if lower == NEG_INFINITY && upper != INFINITY {
+			constraints.push(constraint(name, upper, Relation::LessThanOrEqual));
+		} else if lower != NEG_INFINITY {
+			constraints.push(constraint(format!("{}_lower", name), lower, Relation::GreaterThanOrEqual));
+			constraints.push(constraint(name, upper, Relation::LessThanOrEqual));
+		}
+	}
+
+	let mut bounds = HashMap::new();
+	for col in (0 .. cols).filter(|&col| structural(col)) {
+		let col_bounds = if split.contains(&col) {
+			(NEG_INFINITY, INFINITY)
+		} else if sign[col] < 0. {
+			(-lp.upper[col], -lp.lower[col])
+		} else {
+			(lp.lower[col], lp.upper[col])
+		};
+		if col_bounds != (0., INFINITY) {
+			bounds.insert(lp.vars[col].clone(), col_bounds);
+		}
+	}
+
+	Components {
+		variables: (0 .. cols).filter(|&col| structural(col)).map(|col| variable(col, 0.)).collect(),
+		constraints: constraints,
+		objective: Objective {
+			name: "obj".to_string(),
+			variables: (0 .. cols).filter(|&col| structural(col) && lp.c[col] != 0.)
+				.map(|col| variable(col, sign[col] * lp.c[col]))
+				.collect(),
+			maximize: lp.optimization == Optimization::Max
+		},
+		bounds: bounds,
+		integers: lp.integers.iter().filter(|&&col| structural(col)).map(|&col| lp.vars[col].clone()).collect()
+	}
+}
+
+// Writes ` name: terms relation constant`, standing in a zero multiple of
+// `first` for an empty constraint
+fn write_row(text: &mut String, name: &str, variables: &[Variable], first: Option<&str>, rhs: Option<(&str, f64)>) {
+	let mut line = format!(" {}:", name);
+	let zero = first.map(|first| Variable { name: first.to_string(), coefficient: 0. });
+	let variables = match zero {
+		Some(ref zero) if variables.is_empty() && rhs.is_some() => ::std::slice::from_ref(zero),
+		_ => variables
+	};
+
+	for (i, v) in variables.iter().enumerate() {
+		let sign = match (i, v.coefficient < 0.) {
+			(0, true) => "-",
+			(0, false) => "",
+			(_, true) => "- ",
+			(_, false) => "+ "
+		};
+		let term = if v.coefficient.abs() == 1. {
+			format!("{}{}", sign, v.name)
+		} else {
+			format!("{}{} {}", sign, number(v.coefficient.abs()), v.name)
+		};
+		if line.len() + term.len() + 1 > LINE_LENGTH && i > 0 {
+			text.push_str(&line);
+			text.push('\n');
+			line = "  ".to_string();
+		}
+		line.push(' ');
+		line.push_str(&term);
+	}
+	if let Some((relation, constant)) = rhs {
+		line.push_str(&format!(" {} {}", relation, number(constant)));
+	}
+	text.push_str(&line);
+	text.push('\n');
+}
+
+/// Shortest text reading back as `value`, in exponent form for very large
+/// and very small magnitudes.
+pub fn number(value: f64) -> String {
+	if value == INFINITY {
+		"inf".to_string()
+	} else if value == NEG_INFINITY {
+		"-inf".to_string()
+	} else if value != 0. && (value.abs() >= 1e16 || value.abs() < 1e-5) {
+		format!("{:e}", value)
+	} else {
+		format!("{}", value)
+	}
+}
+
+// Splits the text of a line after any section header into tokens
+fn tokenize<'a>(statement: &Statement<'a>, text: &'a str, tokens: &mut Vec<Token<'a>>) -> Result<(), ParseError> {
+	let mut start = 0;
+	while let Some(c) = text[start ..].chars().next() {
+		let rest = &text[start ..];
+		if c.is_whitespace() {
+			start += c.len_utf8();
+			continue;
+		}
+
+		let (kind, length) = match c {
+			':' => (Kind::Colon, 1),
+			'+' | '-' => (Kind::Sign, 1),
+			'<' | '>' | '=' => {
+				let length = rest.find(|c| c != '<' && c != '>' && c != '=').unwrap_or(rest.len());
+				if relation(&rest[.. length]).is_none() {
+					return Err(statement.malformed_term(&rest[.. length]));
+				}
+				(Kind::Relation, length)
+			},
+			c if c.is_digit(10) || c == '.' => (Kind::Number, number_length(rest)),
+			c if is_name_char(c) => (Kind::Name, rest.find(|c| !is_name_char(c)).unwrap_or(rest.len())),
+			c => return Err(statement.malformed_term(&rest[.. c.len_utf8()]))
+		};
+		tokens.push(Token { kind: kind, text: &rest[.. length], line: statement.index });
+		start += length;
+	}
+	Ok(())
+}
+
+// Length of the digits, point and exponent at the start of `text`
+fn number_length(text: &str) -> usize {
+	let bytes = text.as_bytes();
+	let digits = |from: usize| bytes[from ..].iter().take_while(|&&b| b.is_ascii_digit() || b == b'.').count();
+	let mantissa = digits(0);
+	match bytes.get(mantissa) {
+		Some(&b'e') | Some(&b'E') => {
+			let sign = match bytes.get(mantissa + 1) {
+				Some(&b'+') | Some(&b'-') => 1,
+				_ => 0
+			};
+			match digits(mantissa + 1 + sign) {
+				0 => mantissa,
+				exponent => mantissa + 1 + sign + exponent
+			}
+		},
+		_ => mantissa
+	}
+}
+
+fn is_name_char(c: char) -> bool {
+	c.is_alphanumeric() || "_.!\"#$%&()/,;?@'{}|~[]^`".contains(c)
+}
+
+fn is_section(line: &str, keyword: &str) -> bool {
+	match line.get(.. keyword.len()) {
+		Some(start) => start.eq_ignore_ascii_case(keyword) && match line[keyword.len() ..].chars().next() {
+			Some(c) => c.is_whitespace(),
+			None => true
+		},
+		None => false
+	}
+}
+
+fn is_infinity(text: &str) -> bool {
+	text.eq_ignore_ascii_case("inf") || text.eq_ignore_ascii_case("infinity")
+}
+
+fn relation(text: &str) -> Option<Relation> {
+	match text {
+		"<" | "<=" | "=<" => Some(Relation::LessThanOrEqual),
+		">" | ">=" | "=>" => Some(Relation::GreaterThanOrEqual),
+		"=" => Some(Relation::Equal),
+		_ => None
+	}
+}
+
+fn relation_of(token: Token) -> Relation {
+	relation(token.text).expect("Relations checked in tokenize")
+}
+
+fn missing_objective(text: &str) -> ParseError {
+	let end = Statement::end_of(text, text.split('\n').count());
+	ParseError::MissingObjective {
+		statement: end.index,
+		span: end.span_of(end.text),
+		text: String::new()
+	}
+}
+
+
+#[cfg(test)]
+mod cplex_tests {
+	use super::*;
+	use builder::Builder;
+	use num_bigint::BigInt;
+	use solver::{SimplexSolver, SolverBase};
+
+	const TEXT: &str = "\\ Every section of the format
+MAXIMIZE
+ profit: 3 x + 2y
+   - z + x
+Subject To
+ cap: x + y + z <= 10
+ -x + 2.5e-1 y >= -4
+ link: x - z = 1
+Bounds
+ -inf <= y <= 5
+ z free
+ 0.5 <= x
+ w = 2
+Generals
+ y
+Binary
+ b
+End
+ anything after End is ignored";
+
+	fn variable(name: &str, coefficient: f64) -> Variable {
+		Variable {
+			name: name.to_string(),
+			coefficient: coefficient
+		}
+	}
+
+	#[test]
+	fn read_test() {
+		let components = CplexParser::parse_components_from_text(TEXT).unwrap();
+
+		let names: Vec<_> = components.variables.iter().map(|v| v.name.as_str()).collect();
+		assert_eq!(names, vec!["x", "y", "z", "w", "b"]);
+		assert_eq!(components.objective, Objective {
+			name: "profit".to_string(),
+			variables: vec![variable("x", 4.), variable("y", 2.), variable("z", -1.)],
+			maximize: true
+		});
+		assert_eq!(components.constraints[1], Constraint {
+			name: "c2".to_string(),
+			variables: vec![variable("x", -1.), variable("y", 0.25)],
+			constant: -4.,
+			relation: Relation::GreaterThanOrEqual
+		});
+		assert_eq!(components.constraints[2].relation, Relation::Equal);
+		assert_eq!(components.bounds["y"], (NEG_INFINITY, 5.));
+		assert_eq!(components.bounds["z"], (NEG_INFINITY, INFINITY));
+		assert_eq!(components.bounds["x"], (0.5, INFINITY));
+		assert_eq!(components.bounds["w"], (2., 2.));
+		assert_eq!(components.bounds["b"], (0., 1.));
+		assert_eq!(components.integers, vec!["y", "b"]);
+	}
+
+	#[test]
+	fn write_test() {
+		let components = CplexParser::parse_components_from_text(TEXT).unwrap();
+		let text = components_to_cplex(&components);
+		assert_eq!(text, "Maximize
+ profit: 4 x + 2 y - z
+Subject To
+ cap: x + y + z <= 10
+ c2: -x + 0.25 y >= -4
+ link: x - z = 1
+Bounds
+ x >= 0.5
+ -inf <= y <= 5
+ z free
+ w = 2
+General
+ y
+Binary
+ b
+End
+");
+		assert_eq!(CplexParser::parse_components_from_text(&text).unwrap(), components);
+
+		assert_eq!(number(1e-7), "1e-7");
+		assert_eq!(number(-2.5e20), "-2.5e20");
+		assert_eq!(number(123456.5), "123456.5");
+	}
+
+	#[test]
+	fn lp_round_trip_test() {
+		let text = "
+			var a integer free;
+			var b <= 0;
+			var c binary;
+			var unused;
+			minimize cost: 2*a + -1*b + c;
+			subject to low: a + b >= -7;
+			subject to high: a + -3*c <= 4.5;
+			subject to fix: b + c == -1;
+		";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let written = lp_to_cplex(&lp);
+		let read = CplexParser::lp_from_text(&written, Builder::new()).unwrap();
+		assert_eq!(read, lp);
+		assert_eq!(lp_to_cplex(&read), written);
+
+		let expected = SimplexSolver::new(lp).solve().unwrap();
+		let solution = SimplexSolver::new(read).solve().unwrap();
+		assert_eq!(solution.objective, expected.objective);
+	}
+
+	#[test]
+	fn exact_lp_test() {
+		let text = "Minimize\n obj: 0.1 x\nSubject To\n c1: x >= 0.3\nEnd\n";
+		let lp = CplexParser::exact_lp_from_text(text, Builder::new()).unwrap();
+		assert_eq!(lp.c[0], BigRational::new(BigInt::from(1), BigInt::from(10)));
+		assert_eq!(lp.b[0], BigRational::new(BigInt::from(3), BigInt::from(10)));
+
+		// Repeated terms are summed without rounding, as are bounds read
+		let text = "Minimize\n obj: 0.1 x + 0.2 x - -0.1 y\nSubject To\n c1: x + y >= 0.3\n\
+					Bounds\n -0.5 <= y <= 0.7\nEnd\n";
+		let lp = CplexParser::exact_lp_from_text(text, Builder::new()).unwrap();
+		assert_eq!(lp.c[.. 2].to_vec(), vec![
+			BigRational::new(BigInt::from(3), BigInt::from(10)),
+			BigRational::new(BigInt::from(1), BigInt::from(10))
+		]);
+		assert_eq!(lp.lower[1], BigRational::new(BigInt::from(-1), BigInt::from(2)));
+		assert_eq!(lp.upper[1], Some(BigRational::new(BigInt::from(7), BigInt::from(10))));
+	}
+
+	#[test]
+	fn error_test() {
+		let text = "Minimize\n obj: x\nSubject To\n c1: x + 2 3 <= 4\nEnd";
+		assert_eq!(CplexParser::parse_components_from_text(text).unwrap_err(), ParseError::MalformedTerm {
+			statement: 3,
+			span: Span { line: 4, column: 12, end_line: 4, end_column: 12 },
+			text: "3".to_string()
+		});
+
+		let text = "x + y <= 4\nMinimize\n obj: x";
+		match CplexParser::parse_components_from_text(text).unwrap_err() {
+			ParseError::UnknownStatement { statement, .. } => assert_eq!(statement, 0),
+			err => panic!("Unexpected error {:?}", err)
+		}
+
+		let text = "Subject To\n c1: x <= 4\n";
+		assert_eq!(CplexParser::parse_components_from_text(text).unwrap_err(), ParseError::MissingObjective {
+			statement: 3,
+			span: Span { line: 3, column: 1, end_line: 3, end_column: 1 },
+			text: String::new()
+		});
+
+		let text = "Minimize\n obj: x\nBounds\n 3 <= x <= 2\n";
+		match CplexParser::lp_from_text(text, Builder::new()).unwrap_err() {
+			ParseError::Build { statement, error: BuildError::InvalidBounds { .. }, .. } => assert_eq!(statement, 3),
+			err => panic!("Unexpected error {:?}", err)
+		}
+	}
+}
@@ -0,0 +1,184 @@
+use super::*;
+use super::impl_cplex::number;
+use builder::Relation;
+use std::f64::{INFINITY, NEG_INFINITY};
+
+/// Writes Components in rulp's own syntax, as `rulp fmt` does.
+///
+/// Variables are declared first, then the objective and the constraints,
+/// each in the order of the Components. Numbers are written as the
+/// shortest decimal that reads back as the same value, and coefficients of
+/// 1 are left out. A sum with no terms is written as `0`. Bounds are
+/// declared with the variable, as in `var x >= 2 <= 5`.
+///
+/// Parsing the text gives back equal Components, and formatting them again
+/// gives the same text.
+///
+/// # Examples
+/// ```
+/// use rulp::parser::{format_components, Parser, ParserBase};
+///
+/// let text = "var a integer;var b   free;
+///     maximize obj: 3.0*a + -1*b;
+///     subject to cap: a + 0.50*b <= 4e1;";
+/// let components = Parser::parse_components_from_text(text).unwrap();
+/// let formatted = format_components(&components);
+/// assert_eq!(formatted, "\
+/// var a integer;
+/// var b free;
+///
+/// maximize obj: 3*a + -b;
+///
+/// subject to cap: a + 0.5*b <= 40;
+/// ");
+/// assert_eq!(Parser::parse_components_from_text(&formatted).unwrap(), components);
+/// ```
+pub fn format_components(components: &Components) -> String {
+	let mut text = String::new();
+
+	for v in &components.variables {
+		let name = v.name.as_str();
+		let bounds = *components.bounds.get(name).unwrap_or(&(0., INFINITY));
+		let integer = components.integers.iter().any(|i| i == name);
+		let kind = match (integer, bounds) {
+			(true, (lower, upper)) if lower == 0. && upper == 1. => " binary",
+			(true, _) => " integer",
+			(false, _) => ""
+		};
+		// `<= u` alone leaves the variable unbounded below
+		let declaration = match bounds {
+			(lower, upper) if lower == 0. && upper == 1. && integer => String::new(),
+			(lower, upper) if lower == NEG_INFINITY && upper == INFINITY => " free".to_string(),
+			(lower, upper) if lower == 0. && upper == INFINITY => String::new(),
+			(lower, upper) => {
+				let mut declaration = String::new();
+				if lower != NEG_INFINITY {
+					declaration.push_str(&format!(" >= {}", number(lower)));
+				}
+				if upper != INFINITY {
+					declaration.push_str(&format!(" <= {}", number(upper)));
+				}
+				declaration
+			}
+		};
+		text.push_str(&format!("var {}{}{};\n", name, kind, declaration));
+	}
+
+	let objective = &components.objective;
+	text.push_str(&format!("\n{} {}: {};\n\n",
+		if objective.maximize { "maximize" } else { "minimize" },
+		objective.name,
+		terms(&objective.variables)));
+
+	for constraint in &components.constraints {
+		let relation = match constraint.relation {
+			Relation::LessThanOrEqual => "<=",
+			Relation::GreaterThanOrEqual => ">=",
+			Relation::Equal => "=="
+		};
+		text.push_str(&format!("subject to {}: {} {} {};\n",
+			constraint.name, terms(&constraint.variables), relation, number(constraint.constant)));
+	}
+	text
+}
+
+// Writes `3*x + -y + 0.5*z`, or `0` for no terms
+fn terms(variables: &[Variable]) -> String {
+	if variables.is_empty() {
+		return "0".to_string();
+	}
+
+	variables.iter().map(|v| {
+		let sign = if v.coefficient < 0. { "-" } else { "" };
+		if v.coefficient.abs() == 1. {
+			format!("{}{}", sign, v.name)
+		} else {
+			format!("{}{}*{}", sign, number(v.coefficient.abs()), v.name)
+		}
+	}).collect::<Vec<_>>().join(" + ")
+}
+
+#[cfg(test)]
+mod format_tests {
+	use super::*;
+	use builder::{Builder, BuilderBase};
+
+	#[test]
+	fn idempotent_test() {
+		let text = "
+			# A comment that is dropped;
+			var  a binary;
+			var b <= 0;
+			var c integer free;
+			var d;
+			minimize cost: 0.10*a + -1.0*b + 1e-7*c + -d + 2.5e17*d;
+			subject to first: a + b + c >= -3;
+			subject to second: -1*a + 0*d == 0.25;
+			subject to : c <= 1000000;
+		";
+		let components = Parser::parse_components_from_text(text).unwrap();
+		let formatted = format_components(&components);
+		assert_eq!(formatted, "\
+var a binary;
+var b <= 0;
+var c integer free;
+var d;
+
+minimize cost: 0.1*a + -b + 1e-7*c + -d + 2.5e17*d;
+
+subject to first: a + b + c >= -3;
+subject to second: -a + 0*d == 0.25;
+subject to : c <= 1000000;
+");
+		let reparsed = Parser::parse_components_from_text(&formatted).unwrap();
+		assert_eq!(reparsed, components);
+		assert_eq!(format_components(&reparsed), formatted);
+	}
+
+	#[test]
+	fn bounds_test() {
+		let mut builder = Builder::new();
+		for name in &["x", "y", "z"] {
+			builder.add_variable(Variable { name: name.to_string(), coefficient: 0. }).unwrap();
+		}
+		builder.set_bounds("x", 2., 5.).unwrap();
+		builder.set_bounds("y", -3., INFINITY).unwrap();
+		builder.set_integer("z").unwrap();
+		builder.add_objective(Objective { name: "obj".to_string(), variables: vec![], maximize: true }).unwrap();
+
+		let components = builder.components().unwrap();
+		let formatted = format_components(&components);
+		assert_eq!(formatted, "\
+var x >= 2 <= 5;
+var y >= -3;
+var z integer;
+
+maximize obj: 0;
+
+");
+		assert_eq!(Parser::parse_components_from_text(&formatted).unwrap(), components);
+		assert_eq!(Parser::lp_from_text(&formatted, Builder::new()).unwrap(), builder.build_lp().unwrap());
+	}
+
+	#[test]
+	fn ranges_test() {
+		// A ranged MPS row comes with its own `_lower` row, and a variable
+		// bounded on both sides is declared with both bounds
+		let text = "ROWS\n N obj\n L x\nCOLUMNS\n x x 1\nRHS\n x 4\nRANGES\n x 2\nBOUNDS\n LO b x 1\n UP b x 3\nENDATA";
+		let components = MpsParser::parse_components_from_text(text).unwrap();
+		let formatted = format_components(&components);
+		assert_eq!(formatted, "\
+var x >= 1 <= 3;
+
+minimize obj: 0;
+
+subject to x_lower: x >= 2;
+subject to x: x <= 4;
+");
+		assert_eq!(Parser::parse_components_from_text(&formatted).unwrap(), components);
+
+		let empty = Parser::parse_components_from_text("maximize obj: 0;").unwrap();
+		assert!(empty.objective.variables.is_empty());
+		assert_eq!(format_components(&empty), "\nmaximize obj: 0;\n\n");
+	}
+}
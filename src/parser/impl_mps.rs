@@ -0,0 +1,826 @@
+use super::*;
+use super::impl_cplex::{lp_components, number};
+use super::impl_parser::{exact_lp, ExactModel, ExactRow, Statement};
+use builder::Relation;
+use num_traits::{One, Signed, Zero};
+use std::collections::HashSet;
+use std::f64::{INFINITY, NEG_INFINITY};
+use utils::read_file_contents;
+
+// Columns of the six fields of a fixed format line, counting from 0
+const FIXED_FIELDS: [(usize, usize); 6] = [(1, 3), (4, 12), (14, 22), (24, 36), (39, 47), (49, 61)];
+// Bounds at least this large are infinite, as is usual in MPS files
+const MPS_INFINITY: f64 = 1e30;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Section {
+	Name,
+	ObjSense,
+	Rows,
+	Columns,
+	Rhs,
+	Ranges,
+	Bounds
+}
+
+// A row of the ROWS section, with no relation for a free `N` row. Each
+// number is also kept exactly, with `exact` holding the coefficients of
+// `variables`.
+struct Row {
+	name: String,
+	relation: Option<Relation>,
+	variables: Vec<Variable>,
+	exact: Vec<BigRational>,
+	constant: (f64, BigRational),
+	range: Option<(f64, BigRational)>,
+	line: usize
+}
+
+impl Row {
+	// Ends of the interval the terms of the row may lie in, given its
+	// constant and RANGES entry
+	fn range<T: Clone + PartialOrd + Signed>(&self, constant: &T, range: &T) -> (T, T) {
+		let (constant, range) = (constant.clone(), range.clone());
+		match self.relation {
+			Some(Relation::GreaterThanOrEqual) => (constant.clone(), constant + range.abs()),
+			Some(Relation::Equal) if range >= T::zero() => (constant.clone(), constant + range),
+			Some(Relation::Equal) => (constant.clone() + range, constant),
+			_ => (constant.clone() - range.abs(), constant)
+		}
+	}
+}
+
+// A bound with its exact value, which is None for an infinite bound
+type Bound = (f64, Option<BigRational>);
+
+// The parts of an MPS file, each with the line it was read from
+struct Model {
+	maximize: bool,
+	rows: Vec<Row>,
+	objective: Option<usize>,
+	variables: Vec<(Variable, usize)>,
+	bounds: HashMap<String, ((Bound, Bound), usize)>,
+	integers: Vec<(String, usize)>
+}
+
+impl ParserBase for MpsParser {
+	/// Constructor for Components struct.
+	///
+	/// Takes free MPS text to be parsed. A ranged row becomes two
+	/// constraints, `name_lower` with its lower end and `name` with its
+	/// upper end.
+	fn parse_components_from_text(text: &str) -> Result<Components, ParseError> {
+		components(text, false)
+	}
+
+	/// Constructor for Components struct.
+	///
+	/// Takes a free MPS file to be read.
+	fn parse_components_from_file(file: &mut File) -> Result<Components, ParseError> {
+		Self::parse_components_from_text(&read_file_contents(file))
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// Takes free MPS text to be parsed and a Builder struct. Errors give
+	/// the line they occurred on as their statement.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{MpsParser, ParserBase};
+	///
+	/// let text_problem = "NAME bakery
+	/// ROWS
+	///  N profits
+	///  L flour
+	///  G demand
+	/// COLUMNS
+	///  bagels profits -3 flour 12
+	///  bagels demand 1
+	///  doughnuts profits -1.25 flour 6.5
+	/// RHS
+	///  rhs flour 400 demand 12
+	/// RANGES
+	///  range demand 8
+	/// BOUNDS
+	///  UP bnd doughnuts 50
+	/// ENDATA
+	/// ";
+	/// let lp = MpsParser::lp_from_text(text_problem, Builder::new()).unwrap();
+	/// assert_eq!(lp.vars, vec!["bagels", "doughnuts", "slack_0", "excess_0"]);
+	/// assert_eq!(lp.b, vec![400., 12.]);
+	/// // The excess of demand is at most 8, so 12 <= bagels <= 20
+	/// assert_eq!(lp.upper[1 ..], [50., std::f64::INFINITY, 8.]);
+	/// ```
+	fn lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<Lp, ParseError> {
+		lp(text, false, builder)
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// Takes a free MPS file to be read and a Builder struct.
+	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<Lp, ParseError> {
+		Self::lp_from_text(&read_file_contents(file), builder)
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Builds the Lp as `lp_from_text` does, reading every number as the
+	/// exact decimal written.
+	fn exact_lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<ExactLp, ParseError> {
+		exact(text, false, builder)
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Takes a free MPS file to be read and a Builder struct.
+	fn exact_lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<ExactLp, ParseError> {
+		Self::exact_lp_from_text(&read_file_contents(file), builder)
+	}
+}
+
+impl ParserBase for FixedMpsParser {
+	/// Constructor for Components struct.
+	///
+	/// Takes fixed MPS text to be parsed. A ranged row becomes two
+	/// constraints, as for `MpsParser`.
+	fn parse_components_from_text(text: &str) -> Result<Components, ParseError> {
+		components(text, true)
+	}
+
+	/// Constructor for Components struct.
+	///
+	/// Takes a fixed MPS file to be read.
+	fn parse_components_from_file(file: &mut File) -> Result<Components, ParseError> {
+		Self::parse_components_from_text(&read_file_contents(file))
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// Takes fixed MPS text to be parsed and a Builder struct.
+	fn lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<Lp, ParseError> {
+		lp(text, true, builder)
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// Takes a fixed MPS file to be read and a Builder struct.
+	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<Lp, ParseError> {
+		Self::lp_from_text(&read_file_contents(file), builder)
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Takes fixed MPS text to be parsed and a Builder struct.
+	fn exact_lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<ExactLp, ParseError> {
+		exact(text, true, builder)
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Takes a fixed MPS file to be read and a Builder struct.
+	fn exact_lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<ExactLp, ParseError> {
+		Self::exact_lp_from_text(&read_file_contents(file), builder)
+	}
+}
+
+fn components(text: &str, fixed: bool) -> Result<Components, ParseError> {
+	let model = read(text, fixed)?;
+	let objective = match model.objective {
+		Some(objective) => &model.rows[objective],
+		None => return Err(missing_objective(text))
+	};
+
+	let mut constraints = vec![];
+	for row in model.rows.iter().filter(|row| row.relation.is_some()) {
+		let constraint = |name: String, constant: f64, relation: Relation| Constraint {
+			name: name,
+			variables: row.variables.clone(),
+			constant: constant,
+			relation: relation
+		};
+		match (row.range.clone(), row.relation.clone()) {
+			(Some((range, _)), _) => {
+				let (lower, upper) = row.range(&row.constant.0, &range);
+				constraints.push(constraint(format!("{}_lower", row.name), lower, Relation::GreaterThanOrEqual));
+				constraints.push(constraint(row.name.clone(), upper, Relation::LessThanOrEqual));
+			},
+			(None, Some(relation)) => constraints.push(constraint(row.name.clone(), row.constant.0, relation)),
+			(None, None) => {}
+		}
+	}
+
+	Ok(Components {
+		variables: model.variables.iter().map(|&(ref v, _)| v.clone()).collect(),
+		constraints: constraints,
+		objective: Objective {
+			name: objective.name.clone(),
+			variables: objective.variables.clone(),
+			maximize: model.maximize
+		},
+		bounds: model.bounds.iter().map(|(name, &(((lower, _), (upper, _)), _))| (name.clone(), (lower, upper))).collect(),
+		integers: model.integers.iter().map(|&(ref name, _)| name.clone()).collect()
+	})
+}
+
+// Builds the Lp of MPS text. A ranged row is built as an inequality whose
+// slack or excess is then bounded by the range.
+fn lp<B: BuilderBase>(text: &str, fixed: bool, mut builder: B) -> Result<Lp, ParseError> {
+	let model = read(text, fixed)?;
+	let lines: Vec<&str> = text.split('\n').collect();
+	let statement = |line: usize| Statement {
+		index: line,
+		source: text,
+		text: lines[line]
+	};
+	let objective = match model.objective {
+		Some(objective) => &model.rows[objective],
+		None => return Err(missing_objective(text))
+	};
+
+	for &(ref v, line) in &model.variables {
+		builder.add_variable(v.clone()).map_err(|e| statement(line).build_error(e))?;
+	}
+	for &(ref v, _) in &model.variables {
+		if let Some(&(((lower, _), (upper, _)), line)) = model.bounds.get(&v.name) {
+			builder.set_bounds(&v.name, lower, upper).map_err(|e| statement(line).build_error(e))?;
+		}
+	}
+	for &(ref name, line) in &model.integers {
+		builder.set_integer(name).map_err(|e| statement(line).build_error(e))?;
+	}
+
+	let mut ranges = vec![];
+	for row in model.rows.iter().filter(|row| row.relation.is_some()) {
+		let (relation, constant) = match (row.range.clone(), row.relation.clone()) {
+			(Some((range, _)), Some(Relation::Equal)) => {
+				ranges.push(Some(range.abs()));
+				(Relation::LessThanOrEqual, row.range(&row.constant.0, &range).1)
+			},
+			(range, Some(relation)) => {
+				ranges.push(range.map(|(range, _)| range.abs()));
+				(relation, row.constant.0)
+			},
+			(_, None) => unreachable!()
+		};
+		builder.add_constraint(Constraint {
+			name: row.name.clone(),
+			variables: row.variables.clone(),
+			constant: constant,
+			relation: relation
+		}).map_err(|e| statement(row.line).build_error(e))?;
+	}
+	builder.add_objective(Objective {
+		name: objective.name.clone(),
+		variables: objective.variables.clone(),
+		maximize: model.maximize
+	}).map_err(|e| statement(objective.line).build_error(e))?;
+
+	let mut lp = builder.build_lp().map_err(|e| Statement::end_of(text, lines.len()).build_error(e))?;
+	let cols = lp.A.cols();
+	for col in cols.saturating_sub(lp.num_artificial_vars) .. cols {
+		let entries: Vec<(usize, f64)> = lp.A.column(col).collect();
+		if let (1, Some(&Some(range))) = (entries.len(), ranges.get(entries[0].0)) {
+			lp.upper[col] = range;
+		}
+	}
+	Ok(lp)
+}
+
+// Builds the ExactLp of MPS text from the rows as lp adds them
+fn exact<B: BuilderBase>(text: &str, fixed: bool, builder: B) -> Result<ExactLp, ParseError> {
+	let lp = lp(text, fixed, builder)?;
+	let model = read(text, fixed)?;
+	let terms = |row: &Row| -> Vec<(String, BigRational)> {
+		row.variables.iter().zip(row.exact.iter()).map(|(v, value)| (v.name.clone(), value.clone())).collect()
+	};
+
+	let rows = model.rows.iter().filter(|row| row.relation.is_some()).map(|row| {
+		let constant = match (&row.range, &row.relation) {
+			(&Some((_, ref range)), &Some(Relation::Equal)) => row.range(&row.constant.1, range).1,
+			_ => row.constant.1.clone()
+		};
+		ExactRow {
+			variables: terms(row),
+			constant: constant,
+			range: row.range.as_ref().map(|&(_, ref range)| range.abs())
+		}
+	}).collect();
+
+	let objective = &model.rows[model.objective.expect("Objective checked in lp")];
+	Ok(exact_lp(&lp, &ExactModel {
+		rows: rows,
+		objective: terms(objective),
+		bounds: model.bounds.iter().map(|(name, &(((_, ref lower), (_, ref upper)), _))| {
+			(name.clone(), (lower.clone(), upper.clone()))
+		}).collect()
+	}))
+}
+
+fn read(text: &str, fixed: bool) -> Result<Model, ParseError> {
+	let mut model = Model {
+		maximize: false,
+		rows: vec![],
+		objective: None,
+		variables: vec![],
+		bounds: HashMap::new(),
+		integers: vec![]
+	};
+	let mut rows: HashMap<String, usize> = HashMap::new();
+	let mut columns: HashSet<String> = HashSet::new();
+	// Position of each (row, column) entry in the variables of its row
+	let mut entries: HashMap<(usize, String), usize> = HashMap::new();
+	let mut lower_set: HashSet<String> = HashSet::new();
+	let mut in_integers = false;
+	let mut section = None;
+
+	for (index, line) in text.split('\n').enumerate() {
+		let statement = Statement { index: index, source: text, text: line };
+		if line.trim().is_empty() || line.starts_with('*') {
+			continue;
+		}
+
+		if !line.starts_with(char::is_whitespace) {
+			let mut words = line.split_whitespace();
+			section = Some(match words.next().unwrap_or("").to_uppercase().as_str() {
+				"NAME" => Section::Name,
+				"ROWS" => Section::Rows,
+				"COLUMNS" => Section::Columns,
+				"RHS" => Section::Rhs,
+				"RANGES" => Section::Ranges,
+				"BOUNDS" => Section::Bounds,
+				"OBJSENSE" => {
+					if let Some(sense) = words.next() {
+						model.maximize = objective_sense(&statement, sense)?;
+					}
+					Section::ObjSense
+				},
+				"ENDATA" => break,
+				_ => return Err(statement.unknown_statement())
+			});
+			continue;
+		}
+
+		let fields = fields(line, fixed);
+		match section {
+			Some(Section::ObjSense) if fields.len() == 1 => {
+				model.maximize = objective_sense(&statement, fields[0])?;
+			},
+			Some(Section::Rows) => {
+				if fields.len() != 2 {
+					return Err(statement.malformed_term(line.trim()));
+				}
+				let relation = match fields[0].to_uppercase().as_str() {
+					"N" => None,
+					"L" => Some(Relation::LessThanOrEqual),
+					"G" => Some(Relation::GreaterThanOrEqual),
+					"E" => Some(Relation::Equal),
+					_ => return Err(statement.malformed_term(fields[0]))
+				};
+				if relation.is_none() && model.objective.is_none() {
+					model.objective = Some(model.rows.len());
+				}
+				rows.insert(fields[1].to_string(), model.rows.len());
+				model.rows.push(Row {
+					name: fields[1].to_string(),
+					relation: relation,
+					variables: vec![],
+					exact: vec![],
+					constant: (0., BigRational::zero()),
+					range: None,
+					line: index
+				});
+			},
+			Some(Section::Columns) if fields.len() == 3 && fields[1] == "'MARKER'" => {
+				in_integers = match fields[2] {
+					"'INTORG'" => true,
+					"'INTEND'" => false,
+					_ => return Err(statement.malformed_term(fields[2]))
+				};
+			},
+			Some(Section::Columns) if fields.len() == 3 || fields.len() == 5 => {
+				let column = fields[0];
+				if columns.insert(column.to_string()) {
+					model.variables.push((Variable {
+						name: column.to_string(),
+						coefficient: 0.
+					}, index));
+					if in_integers {
+						model.integers.push((column.to_string(), index));
+					}
+				}
+				for pair in fields[1 ..].chunks(2) {
+					let row = find_row(&statement, &rows, pair[0])?;
+					let (value, exact) = parse(&statement, pair[1])?;
+					if model.rows[row].relation.is_none() && Some(row) != model.objective {
+						continue;
+					}
+					let key = (row, column.to_string());
+					let row = &mut model.rows[row];
+					match entries.get(&key) {
+						Some(&position) => {
+							row.variables[position].coefficient += value;
+							row.exact[position] = row.exact[position].clone() + exact;
+						},
+						None => {
+							entries.insert(key, row.variables.len());
+							row.variables.push(Variable {
+								name: column.to_string(),
+								coefficient: value
+							});
+							row.exact.push(exact);
+						}
+					}
+				}
+			},
+			Some(Section::Rhs) | Some(Section::Ranges) if !fields.is_empty() && fields.len() <= 5 => {
+				// The name of the RHS or RANGES vector is optional
+				let pairs = if fields.len() % 2 == 1 { &fields[1 ..] } else { &fields[..] };
+				for pair in pairs.chunks(2) {
+					let row = find_row(&statement, &rows, pair[0])?;
+					let value = parse(&statement, pair[1])?;
+					// The objective constant an RHS entry gives is dropped,
+					// as an Lp has none
+					if model.rows[row].relation.is_none() {
+						continue;
+					}
+					if section == Some(Section::Rhs) {
+						model.rows[row].constant = value;
+					} else {
+						model.rows[row].range = Some(value);
+					}
+				}
+			},
+			Some(Section::Bounds) if fields.len() >= 2 && fields.len() <= 4 => {
+				let kind = fields[0].to_uppercase();
+				let needs_value = match kind.as_str() {
+					"UP" | "LO" | "FX" | "LI" | "UI" => true,
+					"FR" | "MI" | "PL" | "BV" => false,
+					_ => return Err(statement.malformed_term(fields[0]))
+				};
+				// The name of the BOUNDS vector is optional, and BV may be
+				// given a value
+				let (column, value) = match (needs_value, fields.len()) {
+					(true, 4) => (fields[2], Some(fields[3])),
+					(true, 3) => (fields[1], Some(fields[2])),
+					(false, 2) => (fields[1], None),
+					(false, 3) if columns.contains(fields[2]) => (fields[2], None),
+					(false, 3) => (fields[1], None),
+					(false, 4) => (fields[2], None),
+					_ => return Err(statement.malformed_term(line.trim()))
+				};
+				if !columns.contains(column) {
+					return Err(statement.malformed_term(column));
+				}
+				let bound: Bound = match value {
+					Some(value) => {
+						let (value, exact) = parse(&statement, value)?;
+						if value >= MPS_INFINITY {
+							(INFINITY, None)
+						} else if value <= -MPS_INFINITY {
+							(NEG_INFINITY, None)
+						} else {
+							(value, Some(exact))
+						}
+					},
+					None => (0., None)
+				};
+
+				let entry = model.bounds.entry(column.to_string())
+					.or_insert((((0., Some(BigRational::zero())), (INFINITY, None)), index));
+				entry.1 = index;
+				let bounds = &mut entry.0;
+				match kind.as_str() {
+					"UP" | "UI" => {
+						// An upper bound below 0 frees a variable with no
+						// lower bound given
+						if bound.0 < 0. && !lower_set.contains(column) {
+							bounds.0 = (NEG_INFINITY, None);
+						}
+						bounds.1 = bound;
+					},
+					"LO" | "LI" => bounds.0 = bound,
+					"FX" => *bounds = (bound.clone(), bound),
+					"FR" => *bounds = ((NEG_INFINITY, None), (INFINITY, None)),
+					"MI" => bounds.0 = (NEG_INFINITY, None),
+					"PL" => bounds.1 = (INFINITY, None),
+					_ => *bounds = ((0., Some(BigRational::zero())), (1., Some(BigRational::one())))
+				}
+				if kind.as_str() == "LO" || kind.as_str() == "LI" || kind.as_str() == "FX" {
+					lower_set.insert(column.to_string());
+				}
+				if (kind.as_str() == "BV" || kind.as_str() == "LI" || kind.as_str() == "UI")
+					&& !model.integers.iter().any(|&(ref name, _)| name == column) {
+					model.integers.push((column.to_string(), index));
+				}
+			},
+			_ => return Err(statement.malformed_term(line.trim()))
+		}
+	}
+	Ok(model)
+}
+
+// Splits a data line into its non-empty fields. Fixed format fields lie at
+// set columns and may hold spaces; free format fields are split by
+// whitespace.
+fn fields(line: &str, fixed: bool) -> Vec<&str> {
+	if !fixed {
+		return line.split_whitespace().collect();
+	}
+	FIXED_FIELDS.iter()
+		.filter_map(|&(start, end)| line.get(start .. end.min(line.len())))
+		.map(|field| field.trim())
+		.filter(|field| !field.is_empty())
+		.collect()
+}
+
+fn find_row(statement: &Statement, rows: &HashMap<String, usize>, name: &str) -> Result<usize, ParseError> {
+	rows.get(name).cloned().ok_or_else(|| statement.malformed_term(name))
+}
+
+// Reads a number both as an f64 and exactly
+fn parse(statement: &Statement, literal: &str) -> Result<(f64, BigRational), ParseError> {
+	Ok((statement.parse_number(literal)?, statement.parse_rational(literal)?))
+}
+
+fn objective_sense(statement: &Statement, sense: &str) -> Result<bool, ParseError> {
+	match sense.to_uppercase().as_str() {
+		"MAX" | "MAXIMIZE" => Ok(true),
+		"MIN" | "MINIMIZE" => Ok(false),
+		_ => Err(statement.malformed_term(sense))
+	}
+}
+
+fn missing_objective(text: &str) -> ParseError {
+	let end = Statement::end_of(text, text.split('\n').count());
+	ParseError::MissingObjective {
+		statement: end.index,
+		span: end.span_of(end.text),
+		text: String::new()
+	}
+}
+
+/// Writes Components in the MPS file format.
+///
+/// Fields are laid out in the columns of fixed MPS, so the text reads as
+/// fixed MPS if every name has at most 8 characters and every number at
+/// most 12, and as free MPS otherwise. A maximization is written with an
+/// `OBJSENSE` section, and a variable in no row with a zero objective
+/// coefficient. Rows are found by name, so constraint names must be unique.
+///
+/// # Examples
+/// ```
+/// use rulp::parser::{components_to_mps, Parser, ParserBase};
+///
+/// let components = Parser::parse_components_from_text("
+/// 	var x integer;
+/// 	var y free;
+/// 	minimize cost: 2*x + y;
+/// 	subject to floor: x + -1*y >= -1.5;
+/// ").unwrap();
+/// assert_eq!(components_to_mps(&components), "\
+/// NAME
+/// ROWS
+///  N  cost
+///  G  floor
+/// COLUMNS
+///     MARKER    'MARKER'                 'INTORG'
+///     x         cost      2
+///     x         floor     1
+///     MARKER    'MARKER'                 'INTEND'
+///     y         cost      1
+///     y         floor     -1
+/// RHS
+///     RHS       floor     -1.5
+/// BOUNDS
+///  FR BND       y
+/// ENDATA
+/// ");
+/// ```
+pub fn components_to_mps(components: &Components) -> String {
+	let objective = &components.objective;
+	let mut text = "NAME\n".to_string();
+	if objective.maximize {
+		text.push_str("OBJSENSE\n    MAX\n");
+	}
+
+	text.push_str(&format!("ROWS\n N  {}\n", objective.name));
+	for constraint in &components.constraints {
+		let kind = match constraint.relation {
+			Relation::LessThanOrEqual => "L",
+			Relation::GreaterThanOrEqual => "G",
+			Relation::Equal => "E"
+		};
+		text.push_str(&format!(" {}  {}\n", kind, constraint.name));
+	}
+
+	let mut entries: HashMap<&str, Vec<(&str, f64)>> = HashMap::new();
+	for v in &objective.variables {
+		entries.entry(&v.name).or_default().push((&objective.name, v.coefficient));
+	}
+	for constraint in &components.constraints {
+		for v in &constraint.variables {
+			entries.entry(&v.name).or_default().push((&constraint.name, v.coefficient));
+		}
+	}
+	text.push_str("COLUMNS\n");
+	let mut in_integers = false;
+	for v in &components.variables {
+		let integer = components.integers.contains(&v.name);
+		if integer != in_integers {
+			let marker = if integer { "'INTORG'" } else { "'INTEND'" };
+			text.push_str(&format!("    MARKER    'MARKER'                 {}\n", marker));
+			in_integers = integer;
+		}
+		match entries.get(v.name.as_str()) {
+			Some(column) => for &(row, value) in column {
+				text.push_str(&format!("    {:<8}  {:<8}  {}\n", v.name, row, number(value)));
+			},
+			None => text.push_str(&format!("    {:<8}  {:<8}  0\n", v.name, objective.name))
+		}
+	}
+	if in_integers {
+		text.push_str("    MARKER    'MARKER'                 'INTEND'\n");
+	}
+
+	let rhs: Vec<_> = components.constraints.iter().filter(|c| c.constant != 0.).collect();
+	if !rhs.is_empty() {
+		text.push_str("RHS\n");
+		for constraint in rhs {
+			text.push_str(&format!("    RHS       {:<8}  {}\n", constraint.name, number(constraint.constant)));
+		}
+	}
+
+	let mut bounds = vec![];
+	for v in &components.variables {
+		let (lower, upper) = *components.bounds.get(&v.name).unwrap_or(&(0., INFINITY));
+		let bound = |kind: &str, value: Option<f64>| match value {
+			Some(value) => format!(" {} BND       {:<8}  {}\n", kind, v.name, number(value)),
+			None => format!(" {} BND       {}\n", kind, v.name)
+		};
+		if components.integers.contains(&v.name) && (lower, upper) == (0., 1.) {
+			bounds.push(bound("BV", None));
+		} else if lower == upper {
+			bounds.push(bound("FX", Some(lower)));
+		} else if (lower, upper) == (NEG_INFINITY, INFINITY) {
+			bounds.push(bound("FR", None));
+		} else {
+			if lower == NEG_INFINITY {
+				bounds.push(bound("MI", None));
+			} else if lower != 0. || upper < 0. {
+				bounds.push(bound("LO", Some(lower)));
+			}
+			if upper != INFINITY {
+				bounds.push(bound("UP", Some(upper)));
+			}
+		}
+	}
+	if !bounds.is_empty() {
+		text.push_str("BOUNDS\n");
+		text.push_str(&bounds.concat());
+	}
+	text.push_str("ENDATA\n");
+	text
+}
+
+/// Writes an Lp in the MPS file format, as the variables and constraints
+/// it was built from. See `lp_to_cplex`.
+pub fn lp_to_mps(lp: &Lp) -> String {
+	components_to_mps(&lp_components(lp))
+}
+
+
+#[cfg(test)]
+mod mps_tests {
+	use super::*;
+	use builder::Builder;
+	use num_bigint::BigInt;
+	use solver::{SimplexSolver, SolverBase};
+
+	const FIXED: &str = "NAME          SPACES
+ROWS
+ N  COST
+ G  AT LEAST
+ L  LIM
+COLUMNS
+    MY VAR    COST                 1   AT LEAST             1
+    MY VAR    LIM                  1
+    OTHER     COST                 2   AT LEAST             1
+RHS
+              AT LEAST            -2   LIM               -1.5
+RANGES
+    RNG       LIM                  4
+BOUNDS
+ FR           MY VAR
+ UP BND       OTHER                1
+ENDATA";
+
+	#[test]
+	fn fixed_test() {
+		let components = FixedMpsParser::parse_components_from_text(FIXED).unwrap();
+		let names: Vec<_> = components.variables.iter().map(|v| v.name.as_str()).collect();
+		assert_eq!(names, vec!["MY VAR", "OTHER"]);
+		assert_eq!(components.constraints[0].name, "AT LEAST");
+		assert_eq!(components.constraints[0].constant, -2.);
+		assert_eq!(components.constraints[1], Constraint {
+			name: "LIM_lower".to_string(),
+			variables: vec![Variable { name: "MY VAR".to_string(), coefficient: 1. }],
+			constant: -5.5,
+			relation: Relation::GreaterThanOrEqual
+		});
+		assert_eq!(components.constraints[2].constant, -1.5);
+		assert_eq!(components.bounds["MY VAR"], (NEG_INFINITY, INFINITY));
+		assert_eq!(components.bounds["OTHER"], (0., 1.));
+
+		// Free MPS splits the names with spaces
+		assert!(MpsParser::parse_components_from_text(FIXED).is_err());
+
+		// min x + 2 y with x + y >= -2, -5.5 <= x <= -1.5 and y <= 1
+		let lp = FixedMpsParser::lp_from_text(FIXED, Builder::new()).unwrap();
+		let solution = SimplexSolver::new(lp).solve().unwrap();
+		assert_eq!(solution.objective, Some(-2.));
+	}
+
+	#[test]
+	fn bounds_test() {
+		let text = "ROWS\n N obj\nCOLUMNS\n a obj 1\n b obj 1\n c obj 1\n d obj 1\n e obj 1\n\
+					BOUNDS\n UP bnd a -2\n LO bnd b -3\n UP bnd b -2\n MI c\n PL bnd c\n BV bnd d\n LI e 2\n UP e 1e30\nENDATA";
+		let components = MpsParser::parse_components_from_text(text).unwrap();
+		assert_eq!(components.bounds["a"], (NEG_INFINITY, -2.));
+		assert_eq!(components.bounds["b"], (-3., -2.));
+		assert_eq!(components.bounds["c"], (NEG_INFINITY, INFINITY));
+		assert_eq!(components.bounds["d"], (0., 1.));
+		assert_eq!(components.bounds["e"], (2., INFINITY));
+		assert_eq!(components.integers, vec!["d", "e"]);
+	}
+
+	#[test]
+	fn exact_test() {
+		let ratio = |numer: i64, denom: i64| BigRational::new(BigInt::from(numer), BigInt::from(denom));
+		let text = "ROWS\n N obj\n L cap\n E fix\nCOLUMNS\n x obj 0.1 cap 0.1\n x cap 0.2\n y fix 1\n\
+					RHS\n cap 0.3 fix 1\nRANGES\n fix -0.1\nBOUNDS\n UP bnd y 0.7\nENDATA";
+		let lp = MpsParser::exact_lp_from_text(text, Builder::new()).unwrap();
+
+		// Repeated entries are summed without rounding
+		assert_eq!(lp.A.column(0).collect::<Vec<_>>(), vec![(0, ratio(3, 10))]);
+		assert_eq!(lp.b, vec![ratio(3, 10), ratio(1, 1)]);
+		assert_eq!(lp.c[0], ratio(1, 10));
+		assert_eq!(lp.upper[1], Some(ratio(7, 10)));
+
+		// The ranged row's slack is bounded by the range
+		assert_eq!(lp.upper[3], Some(ratio(1, 10)));
+	}
+
+	#[test]
+	fn write_test() {
+		let text = "
+			var a integer free;
+			var b <= 0;
+			var c binary;
+			var unused;
+			maximize profit: 2*a + -1*b + c;
+			subject to low: a + b >= -7;
+			subject to high: a + -3*c <= 4.5;
+			subject to fix: b + c == -1;
+		";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let written = lp_to_mps(&lp);
+		assert_eq!(MpsParser::lp_from_text(&written, Builder::new()).unwrap(), lp);
+		assert_eq!(FixedMpsParser::lp_from_text(&written, Builder::new()).unwrap(), lp);
+
+		// A ranged row is written as two rows that read back the same way
+		let text = "ROWS\n N obj\n L cap\n E fix\nCOLUMNS\n x obj 1 cap 1\n x fix 1\nRHS\n cap 4 fix 1\nRANGES\n cap 2 fix -3\nENDATA";
+		let ranged = MpsParser::parse_components_from_text(text).unwrap();
+		assert_eq!(ranged.constraints.len(), 4);
+		assert_eq!(MpsParser::parse_components_from_text(&components_to_mps(&ranged)).unwrap(), ranged);
+	}
+
+	#[test]
+	fn error_test() {
+		let text = "ROWS\n N obj\n L c1\nCOLUMNS\n x obj 1 c2 1\nENDATA";
+		assert_eq!(MpsParser::parse_components_from_text(text).unwrap_err(), ParseError::MalformedTerm {
+			statement: 4,
+			span: Span { line: 5, column: 10, end_line: 5, end_column: 11 },
+			text: "c2".to_string()
+		});
+
+		let text = "ROWS\n L c1\nCOLUMNS\n x c1 1\nENDATA\n";
+		match MpsParser::parse_components_from_text(text).unwrap_err() {
+			ParseError::MissingObjective { statement, .. } => assert_eq!(statement, 6),
+			err => panic!("Unexpected error {:?}", err)
+		}
+
+		let text = "ROWS\n N obj\nCOLUMNS\n x obj 1\nBOUNDS\n SC bnd x 4\nENDATA\n";
+		match MpsParser::parse_components_from_text(text).unwrap_err() {
+			ParseError::MalformedTerm { statement, text, .. } => assert_eq!((statement, text.as_str()), (5, "SC")),
+			err => panic!("Unexpected error {:?}", err)
+		}
+
+		let text = "ROWS\n N obj\nSOS\nENDATA\n";
+		match MpsParser::parse_components_from_text(text).unwrap_err() {
+			ParseError::UnknownStatement { statement, .. } => assert_eq!(statement, 2),
+			err => panic!("Unexpected error {:?}", err)
+		}
+	}
+}
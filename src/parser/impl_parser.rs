@@ -1,5 +1,10 @@
 use super::*;
 use builder::Relation;
+use lp::{SparseMatrix, Substitution};
+use num_bigint::BigInt;
+use num_traits::{pow, One, Zero};
+use std::collections::BTreeMap;
+use std::f64::{INFINITY, NEG_INFINITY};
 use utils::read_file_contents;
 
 #[derive(Debug, PartialEq)]
@@ -12,7 +17,7 @@ enum LineType {
 
 #[derive(Debug, PartialEq)]
 enum Component {
-	Variable(Variable),
+	Variable(Variable, Option<(f64, f64)>, bool),
 	Constraint(Constraint),
 	Objective(Objective),
 	Comment
@@ -23,7 +28,7 @@ impl ParserBase for Parser {
 	/// Constructor for Components struct.
 	///
 	/// Takes a string input to be parsed.
-	fn parse_components_from_text(text: &str) -> Components {
+	fn parse_components_from_text(text: &str) -> Result<Components, ParseError> {
 		let p = Parser::new();
 		p.get_components(text)
 	}
@@ -31,13 +36,15 @@ impl ParserBase for Parser {
 	/// Constructor for Components struct.
 	/// 
 	/// Takes a file input to be read.
-	fn parse_components_from_file(file: &mut File) -> Components {
+	fn parse_components_from_file(file: &mut File) -> Result<Components, ParseError> {
 		Self::parse_components_from_text(&read_file_contents(file))
 	}
 
 	/// Constructor for Lp struct.
 	///
 	/// Takes a string input to be parsed and a Builder struct.
+	/// Returns a ParseError describing the first invalid statement
+	/// if the text cannot be parsed or the Builder rejects it.
 	///
 	/// # Examples
 	/// ```
@@ -63,7 +70,7 @@ impl ParserBase for Parser {
 	///
 	///		";
 	///		let builder = Builder::new();
-	///		let lp = Parser::lp_from_text(text_problem, builder);
+	///		let lp = Parser::lp_from_text(text_problem, builder).unwrap();
 	///
 	/// 	let expected_A = matrix![
 	/// 			20.0,   6.0,   3.0, 1.0, 0., 0., 0.;
@@ -75,63 +82,115 @@ impl ParserBase for Parser {
 	/// 	let expected_b = vec![182., 10., 0., 0.];
 	/// 	let expected_c = vec![100000., 40000., 18000., 0., 0., 0., 0.];
 	///
-	/// 	assert_matrix_eq!(lp.A, expected_A);
+	/// 	assert_matrix_eq!(lp.A.to_dense(), expected_A);
 	/// 	assert_eq!(lp.b, expected_b);
 	/// 	assert_eq!(lp.c, expected_c);
 	/// 	assert_eq!(lp.optimization, Optimization::Max);
 	/// # }
 	/// ```
-	fn lp_from_text<B: BuilderBase>(text: &str, mut builder: B) -> Lp {
-		let components = Self::parse_components_from_text(text);
-
-		for v in components.variables {
-			builder.add_variable(v);
+	fn lp_from_text<B: BuilderBase>(text: &str, mut builder: B) -> Result<Lp, ParseError> {
+		let p = Parser::new();
+		let statements = p.get_statements(text)?;
+
+		for &(ref statement, ref component) in &statements {
+			if let Component::Variable(ref v, bounds, integer) = *component {
+				builder.add_variable(v.clone()).map_err(|e| statement.build_error(e))?;
+				if let Some((lower, upper)) = bounds {
+					builder.set_bounds(&v.name, lower, upper).map_err(|e| statement.build_error(e))?;
+				}
+				if integer {
+					builder.set_integer(&v.name).map_err(|e| statement.build_error(e))?;
+				}
+			}
 		}
 
-		for c in components.constraints {
-			builder.add_constraint(c);
+		for &(ref statement, ref component) in &statements {
+			if let Component::Constraint(ref c) = *component {
+				builder.add_constraint(c.clone()).map_err(|e| statement.build_error(e))?;
+			}
 		}
 
-		builder.add_objective(components.objective);
+		for &(ref statement, ref component) in &statements {
+			if let Component::Objective(ref o) = *component {
+				builder.add_objective(o.clone()).map_err(|e| statement.build_error(e))?;
+			}
+		}
 
-		builder.build_lp()
+		builder.build_lp().map_err(|e| Statement::end_of(text, statements.len()).build_error(e))
 	}
 
 	/// Constructor for Lp struct.
 	///
 	/// Takes a file input to be read and a Builder struct.
-	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Lp {
+	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<Lp, ParseError> {
 		Self::lp_from_text(&read_file_contents(file), builder)
 	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Builds the Lp as `lp_from_text` does, but reads every coefficient,
+	/// constant and bound as the exact decimal written, e.g. `0.1` as 1/10,
+	/// without going through floating point.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate num_rational;
+	/// # extern crate rulp;
+	/// use num_rational::BigRational;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	minimize cost: 0.1*x;
+	/// 	subject to floor: x >= 2.5e-1;
+	/// ";
+	/// let lp = Parser::exact_lp_from_text(text_problem, Builder::new()).unwrap();
+	/// assert_eq!(lp.c[0], "1/10".parse::<BigRational>().unwrap());
+	/// assert_eq!(lp.b[0], "1/4".parse::<BigRational>().unwrap());
+	/// # }
+	/// ```
+	fn exact_lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<ExactLp, ParseError> {
+		let lp = Self::lp_from_text(text, builder)?;
+		let model = Parser::new().exact_model(text)?;
+		Ok(exact_lp(&lp, &model))
+	}
+
+	/// Constructor for ExactLp struct.
+	///
+	/// Takes a file input to be read and a Builder struct.
+	fn exact_lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<ExactLp, ParseError> {
+		Self::exact_lp_from_text(&read_file_contents(file), builder)
+	}
 }
 
 impl Parser {
 	fn new() -> Self {
 		Parser {
-			variable_declaration_regex: Regex::new(r"var\s+(?P<name>\w+)\s*").unwrap(),
-			variable_regex: Regex::new(r"((?:\s*(?P<sign>-)?\s*)(?P<coeff>\d+\.?\d*)\s*\*\s*)?(?P<name>\w+)").unwrap(),
-			objective_regex: Regex::new(r"(?P<type>minimize|maximize)\s+(?P<name>\w+)\s*:\s*(?P<equation>[^;]*)").unwrap(),
-			equation_component_regex: Regex::new(r"^(?P<vars>[\w\s\*\.\+-]*)\s*((?P<type>==|<=|>=)\s*(?P<constant>\d+\.?\d*)\s*)?$").unwrap(),
-			constraint_regex: Regex::new(r"subject to (?P<name>\w*):\s*(?P<terms>[^=><]+?)\s*(?P<type>==|<=|>=)\s*?(?P<constant>\d+\.?\d*)\s*?").unwrap()
+			variable_declaration_regex: Regex::new(r"^var\s+(?P<name>\w+)(?:\s+(?P<kind>integer|binary))?(?:\s+(?P<free>free)|(?:\s*>=\s*(?P<lower>[^\s<>=]+))?(?:\s*<=\s*(?P<upper>\S+))?)$").unwrap(),
+			variable_regex: Regex::new(r"(?s)^(?P<sign>-)?\s*(?:(?P<coeff>[^*]+?)\s*\*\s*)?(?P<name>\w+)$").unwrap(),
+			objective_regex: Regex::new(r"(?s)^(?P<type>minimize|maximize)\s+(?P<name>\w+)\s*:\s*(?P<equation>.*)$").unwrap(),
+			constraint_regex: Regex::new(r"(?s)^subject\s+to\s+(?P<name>\w*)\s*:\s*(?P<terms>[^=><]*?)\s*(?P<type>==|<=|>=)\s*(?P<constant>.*)$").unwrap()
 		}
 	}
 
-	fn get_components(&self, text: &str) -> Components {
-		let components: Vec<Component> = text
-			.split(';')
-			.map(|line| line.trim())
-			.filter(|line| line.len() > 0)
-			.map(|line| self.component_from_line(line))
-			.filter(|component| *component != Component::Comment)
-			.collect();
-
+	fn get_components(&self, text: &str) -> Result<Components, ParseError> {
 		let mut variables = vec![];
 		let mut constraints = vec![];
 		let mut objective = None;
-
-		for c in components {
-			match c {
-				Component::Variable(var) => {
+		let mut bounds = HashMap::new();
+		let mut integers = vec![];
+
+		for (_, component) in self.get_statements(text)? {
+			match component {
+				Component::Variable(var, var_bounds, integer) => {
+					if let Some(var_bounds) = var_bounds {
+						bounds.insert(var.name.clone(), var_bounds);
+					}
+					if integer {
+						integers.push(var.name.clone());
+					}
 					variables.push(var);
 				},
 				Component::Constraint(con) => {
@@ -144,52 +203,152 @@ impl Parser {
 			}
 		}
 
-		Components {
+		Ok(Components {
 			variables: variables,
 			constraints: constraints,
-			objective: objective.expect("No objective function provided!")
+			objective: objective.expect("Objective checked in get_statements"),
+			bounds: bounds,
+			integers: integers
+		})
+	}
+
+	/// Parses every statement of the text. Fails unless exactly one
+	/// objective is declared.
+	fn get_statements<'a>(&self, text: &'a str) -> Result<Vec<(Statement<'a>, Component)>, ParseError> {
+		let statements = text
+			.split(';')
+			.map(|line| line.trim())
+			.filter(|line| line.len() > 0)
+			.enumerate()
+			.map(|(index, line)| Statement { index: index, source: text, text: line });
+
+		let mut parsed = vec![];
+		let mut objective_name: Option<String> = None;
+
+		for statement in statements {
+			let component = self.component_from_statement(&statement)?;
+			if let Component::Objective(ref obj) = component {
+				if let Some(existing) = objective_name {
+					return Err(statement.build_error(BuildError::DuplicateObjective {
+						existing: existing,
+						duplicate: obj.name.clone()
+					}));
+				}
+				objective_name = Some(obj.name.clone());
+			}
+
+			parsed.push((statement, component));
+		}
+
+		if objective_name.is_none() {
+			let end = Statement::end_of(text, parsed.len());
+			return Err(ParseError::MissingObjective {
+				statement: end.index,
+				span: end.span_of(end.text),
+				text: String::new()
+			});
 		}
+
+		Ok(parsed)
 	}
 
-	fn component_from_line(&self, line: &str) -> Component {
-		match self.get_line_type(line) {
+	fn component_from_statement(&self, statement: &Statement) -> Result<Component, ParseError> {
+		let line_type = match self.get_line_type(statement.text) {
+			Some(line_type) => line_type,
+			None => return Err(statement.unknown_statement())
+		};
+
+		match line_type {
 			LineType::Variable => {
-				Component::Variable(self.parse_variable_declaration(line))
+				Ok(Component::Variable(
+					self.parse_variable_declaration(statement)?,
+					self.parse_variable_bounds(statement)?,
+					self.is_integer_declaration(statement)
+				))
 			},
 			LineType::Constraint => {
-				Component::Constraint(self.parse_constraint(line))
+				Ok(Component::Constraint(self.parse_constraint(statement)?))
 			},
 			LineType::Objective => {
-				Component::Objective(self.parse_objective(line))
+				Ok(Component::Objective(self.parse_objective(statement)?))
 			},
-			LineType::Comment => Component::Comment,
+			LineType::Comment => Ok(Component::Comment),
 		}
 	}
 
-	fn get_line_type(&self, line: &str) -> LineType {
+	fn get_line_type(&self, line: &str) -> Option<LineType> {
 		if line.contains("#") {
-			return LineType::Comment;
-		} else if line.contains("var") {
-			return LineType::Variable;
-		} else if line.contains("minimize") || line.contains("maximize") {
-			return LineType::Objective;
-		} else if line.contains("subject to") {
-			return LineType::Constraint;
-		} 
-
-		panic!("Unknown line type for \"{:?}\"", line);
+			Some(LineType::Comment)
+		} else if starts_with_keyword(line, "var") {
+			Some(LineType::Variable)
+		} else if starts_with_keyword(line, "minimize") || starts_with_keyword(line, "maximize") {
+			Some(LineType::Objective)
+		} else if starts_with_keyword(line, "subject") {
+			Some(LineType::Constraint)
+		} else {
+			None
+		}
 	}
 
-	fn parse_variable_declaration(&self, data: &str) -> Variable {
-		let caps = self.variable_declaration_regex.captures(data).unwrap();
-		return Variable {
+	fn parse_variable_declaration(&self, statement: &Statement) -> Result<Variable, ParseError> {
+		let caps = match self.variable_declaration_regex.captures(statement.text) {
+			Some(caps) => caps,
+			None => return Err(statement.malformed_term(statement.text))
+		};
+
+		Ok(Variable {
 			name: caps["name"].to_string(),
 			coefficient: 0.,
-		}	
+		})
 	}
 
-	fn parse_constraint(&self, data: &str) -> Constraint {
-		let caps = self.constraint_regex.captures(data).unwrap();
+	// Reads the bounds of `var x free`, `var x binary`, `var x >= 2`,
+	// `var x <= 5` or `var x >= 2 <= 5`, where all but `binary` may follow
+	// `integer`. An upper bound with no lower one leaves x unbounded below,
+	// as in `var x <= 0`. Other variables keep the Builder's default bounds.
+	fn parse_variable_bounds(&self, statement: &Statement) -> Result<Option<(f64, f64)>, ParseError> {
+		let caps = match self.variable_declaration_regex.captures(statement.text) {
+			Some(caps) => caps,
+			None => return Err(statement.malformed_term(statement.text))
+		};
+
+		if caps.name("kind").map_or(false, |kind| kind.as_str() == "binary") {
+			// Binary variables already have both bounds
+			return match caps.name("free").or(caps.name("lower")).or(caps.name("upper")) {
+				Some(extra) => Err(statement.malformed_term(extra.as_str())),
+				None => Ok(Some((0., 1.)))
+			};
+		}
+		if caps.name("free").is_some() {
+			return Ok(Some((NEG_INFINITY, INFINITY)));
+		}
+		if caps.name("lower").is_none() && caps.name("upper").is_none() {
+			return Ok(None);
+		}
+		let lower = match caps.name("lower") {
+			Some(lower) => statement.parse_number(lower.as_str())?,
+			None => NEG_INFINITY
+		};
+		let upper = match caps.name("upper") {
+			Some(upper) => statement.parse_number(upper.as_str())?,
+			None => INFINITY
+		};
+		Ok(Some((lower, upper)))
+	}
+
+	fn is_integer_declaration(&self, statement: &Statement) -> bool {
+		match self.variable_declaration_regex.captures(statement.text) {
+			Some(caps) => caps.name("kind").is_some(),
+			None => false
+		}
+	}
+
+	fn parse_constraint(&self, statement: &Statement) -> Result<Constraint, ParseError> {
+		let caps = match self.constraint_regex.captures(statement.text) {
+			Some(caps) => caps,
+			None => return Err(statement.malformed_term(statement.text))
+		};
+
 		let name = caps["name"].to_string();
 		let relation = if caps["type"].contains("<") {
 			Relation::LessThanOrEqual
@@ -198,35 +357,111 @@ impl Parser {
 		} else {
 			Relation::Equal
 		};
-			
-		let constant = caps["constant"].parse::<f64>().unwrap();
-		let variables = self.parse_objective_vars(&caps["terms"]);
 
-		Constraint {
+		let constant = statement.parse_number(caps.name("constant").unwrap().as_str())?;
+		let variables = self.parse_objective_vars(statement, caps.name("terms").unwrap().as_str())?;
+
+		Ok(Constraint {
 			name: name,
 			variables: variables,
 			constant: constant,
 			relation: relation
-		}
-
+		})
 	}
 
-	fn parse_objective(&self, data: &str) -> Objective {
-		let caps = self.objective_regex.captures(data).expect("Invalid objective!");
+	fn parse_objective(&self, statement: &Statement) -> Result<Objective, ParseError> {
+		let caps = match self.objective_regex.captures(statement.text) {
+			Some(caps) => caps,
+			None => return Err(statement.malformed_term(statement.text))
+		};
 
-		Objective {
+		Ok(Objective {
 			name: caps["name"].to_string(),
-			variables: self.parse_objective_vars(&caps["equation"]),
+			variables: self.parse_objective_vars(statement, caps.name("equation").unwrap().as_str())?,
 			maximize: caps["type"].contains("maximize")
+		})
+	}
+
+	// Reads the numbers of the text exactly, in the order lp_from_text
+	// hands them to the Builder
+	fn exact_model(&self, text: &str) -> Result<ExactModel, ParseError> {
+		let mut model = ExactModel {
+			rows: vec![],
+			objective: vec![],
+			bounds: HashMap::new()
+		};
+
+		for (statement, component) in self.get_statements(text)? {
+			match component {
+				Component::Variable(ref v, Some(_), _) => {
+					let caps = self.variable_declaration_regex.captures(statement.text).expect("Declaration checked in get_statements");
+					let bounds = if caps.name("kind").map_or(false, |kind| kind.as_str() == "binary") {
+						(Some(BigRational::zero()), Some(BigRational::one()))
+					} else {
+						let lower = match caps.name("lower") {
+							Some(lower) => Some(statement.parse_rational(lower.as_str())?),
+							None => None
+						};
+						let upper = match caps.name("upper") {
+							Some(upper) => Some(statement.parse_rational(upper.as_str())?),
+							None => None
+						};
+						(lower, upper)
+					};
+					model.bounds.insert(v.name.clone(), bounds);
+				},
+				Component::Constraint(_) => {
+					let caps = self.constraint_regex.captures(statement.text).expect("Constraint checked in get_statements");
+					model.rows.push(ExactRow {
+						variables: self.parse_exact_vars(&statement, caps.name("terms").unwrap().as_str())?,
+						constant: statement.parse_rational(caps.name("constant").unwrap().as_str())?,
+						range: None
+					});
+				},
+				Component::Objective(_) => {
+					let caps = self.objective_regex.captures(statement.text).expect("Objective checked in get_statements");
+					model.objective = self.parse_exact_vars(&statement, caps.name("equation").unwrap().as_str())?;
+				},
+				_ => {}
+			}
 		}
+
+		Ok(model)
 	}
 
-	fn parse_objective_vars(&self, data: &str) -> Vec<Variable> {
-		data.split('+').map(|s| s.trim()).map(|var| self.parse_variable(var)).collect()
+	// The terms of parse_objective_vars with exact coefficients
+	fn parse_exact_vars(&self, statement: &Statement, data: &str) -> Result<Vec<(String, BigRational)>, ParseError> {
+		if data.trim() == "0" {
+			return Ok(vec![]);
+		}
+		data.split('+').map(|s| s.trim()).map(|var| {
+			let caps = match self.variable_regex.captures(var) {
+				Some(caps) => caps,
+				None => return Err(statement.malformed_term(var))
+			};
+			let coefficient = match caps.name("coeff") {
+				Some(coeff) => statement.parse_rational(coeff.as_str())?,
+				None => BigRational::one()
+			};
+			let coefficient = if caps.name("sign").is_some() { -coefficient } else { coefficient };
+			Ok((caps["name"].to_string(), coefficient))
+		}).collect()
 	}
 
-	fn parse_variable(&self, data: &str) -> Variable {
-		let caps = self.variable_regex.captures(data).unwrap();
+	// Reads `3*x + -y`, or `0` for a sum with no terms
+	fn parse_objective_vars(&self, statement: &Statement, data: &str) -> Result<Vec<Variable>, ParseError> {
+		if data.trim() == "0" {
+			return Ok(vec![]);
+		}
+		data.split('+').map(|s| s.trim()).map(|var| self.parse_variable(statement, var)).collect()
+	}
+
+	fn parse_variable(&self, statement: &Statement, data: &str) -> Result<Variable, ParseError> {
+		let caps = match self.variable_regex.captures(data) {
+			Some(caps) => caps,
+			None => return Err(statement.malformed_term(data))
+		};
+
 		let name = caps["name"].to_string();
 		let sign = match caps.name("sign") {
 			None => {
@@ -242,14 +477,257 @@ impl Parser {
 				1.
 			}, 
 			Some(coeff) => {
-				coeff.as_str().parse::<f64>().unwrap()
+				statement.parse_number(coeff.as_str())?
 			}
 		};
 
-		Variable {
+		Ok(Variable {
 				name: name,
 				coefficient: coefficient * sign,
+		})
+	}
+}
+
+/// The numbers of an LP read exactly, for `exact_lp`.
+pub struct ExactModel {
+	/// The constraints in the order they are added to the Builder.
+	pub rows: Vec<ExactRow>,
+	pub objective: Vec<(String, BigRational)>,
+	/// Lower and upper bounds set on variables, with None for an infinite
+	/// bound. Other variables keep the bounds of 0 and infinity.
+	pub bounds: HashMap<String, (Option<BigRational>, Option<BigRational>)>
+}
+
+pub struct ExactRow {
+	/// Coefficient of each variable, with the last given for a variable
+	/// taking precedence as in the Builder.
+	pub variables: Vec<(String, BigRational)>,
+	pub constant: BigRational,
+	/// Upper bound of the slack or excess of the row, as an MPS range sets.
+	pub range: Option<BigRational>
+}
+
+/// Builds the ExactLp of `lp` from the exact numbers of the text it was
+/// parsed from.
+///
+/// The columns, rows and substitutions are those the Builder laid out in
+/// `lp`, and every entry is worked out again from `model` as the Builder
+/// works it out, so no number goes through floating point. The slack and
+/// excess columns the Builder adds hold 1 or -1.
+pub fn exact_lp(lp: &Lp, model: &ExactModel) -> ExactLp {
+	let columns: HashMap<&str, usize> = lp.vars.iter().enumerate().map(|(col, name)| (name.as_str(), col)).collect();
+	let coefficients = |variables: &[(String, BigRational)]| {
+		let mut coefficients: BTreeMap<usize, BigRational> = variables.iter()
+			.map(|&(ref name, ref value)| (columns[name.as_str()], value.clone()))
+			.collect();
+		for substitution in &lp.substitutions {
+			match *substitution {
+				Substitution::Negated { col } => {
+					if let Some(coefficient) = coefficients.get_mut(&col) {
+						*coefficient = -coefficient.clone();
+					}
+				},
+				Substitution::Split { positive, negative } => {
+					if let Some(coefficient) = coefficients.get(&positive).cloned() {
+						coefficients.insert(negative, -coefficient);
+					}
+				}
+			}
 		}
+		coefficients
+	};
+
+	let cols = lp.A.cols();
+	let artificial = cols - lp.num_artificial_vars .. cols;
+	let mut triplets = vec![];
+	for (row, exact_row) in model.rows.iter().enumerate() {
+		for (col, value) in coefficients(&exact_row.variables) {
+			triplets.push((row, col, value));
+		}
+	}
+
+	let (mut lower, mut upper): (Vec<Option<BigRational>>, Vec<Option<BigRational>>) = lp.vars.iter()
+		.map(|name| model.bounds.get(name).cloned().unwrap_or((Some(BigRational::zero()), None)))
+		.unzip();
+	for col in artificial {
+		for (row, value) in lp.A.column(col) {
+			triplets.push((row, col, if value > 0. { BigRational::one() } else { -BigRational::one() }));
+			upper[col] = model.rows[row].range.clone();
+		}
+	}
+	for substitution in &lp.substitutions {
+		match *substitution {
+			Substitution::Negated { col } => {
+				lower[col] = upper[col].take().map(|upper| -upper);
+			},
+			Substitution::Split { positive, negative } => {
+				lower[positive] = Some(BigRational::zero());
+				lower[negative] = Some(BigRational::zero());
+			}
+		}
+	}
+
+	let mut c = vec![BigRational::zero(); cols];
+	for (col, value) in coefficients(&model.objective) {
+		c[col] = value;
+	}
+
+	ExactLp {
+		A: SparseMatrix::from_triplets(lp.A.rows(), cols, triplets),
+		b: model.rows.iter().map(|row| row.constant.clone()).collect(),
+		c: c,
+		optimization: lp.optimization.clone(),
+		vars: lp.vars.clone(),
+		constraints: lp.constraints.clone(),
+		lower: lower.into_iter().map(|lower| lower.expect("Builder substitutes every var with no lower bound")).collect(),
+		upper: upper,
+		substitutions: lp.substitutions.clone(),
+		num_artificial_vars: lp.num_artificial_vars
+	}
+}
+
+/// A single `;`-terminated statement of the text being parsed, or a line
+/// of CPLEX LP text.
+///
+/// `text` is always a slice of `source`, as are the slices handed to the
+/// error constructors, which lets errors be located in the original text.
+#[derive(Clone, Copy)]
+pub struct Statement<'a> {
+	pub index: usize,
+	pub source: &'a str,
+	pub text: &'a str
+}
+
+impl<'a> Statement<'a> {
+	/// The empty statement just past the end of `source`.
+	pub fn end_of(source: &'a str, index: usize) -> Self {
+		Statement {
+			index: index,
+			source: source,
+			text: &source[source.len() ..]
+		}
+	}
+
+	pub fn parse_number(&self, data: &str) -> Result<f64, ParseError> {
+		match data.trim().parse::<f64>() {
+			Ok(num) if num.is_finite() => Ok(num),
+			_ => Err(ParseError::BadNumber {
+				statement: self.index,
+				span: self.span_of(data),
+				text: data.to_string()
+			})
+		}
+	}
+
+	/// Reads a decimal number such as `-1.25e3` exactly.
+	pub fn parse_rational(&self, data: &str) -> Result<BigRational, ParseError> {
+		decimal_to_rational(data.trim()).ok_or_else(|| ParseError::BadNumber {
+			statement: self.index,
+			span: self.span_of(data),
+			text: data.to_string()
+		})
+	}
+
+	pub fn unknown_statement(&self) -> ParseError {
+		ParseError::UnknownStatement {
+			statement: self.index,
+			span: self.span_of(self.text),
+			text: self.text.to_string()
+		}
+	}
+
+	pub fn malformed_term(&self, data: &str) -> ParseError {
+		ParseError::MalformedTerm {
+			statement: self.index,
+			span: self.span_of(data),
+			text: data.to_string()
+		}
+	}
+
+	pub fn build_error(&self, error: BuildError) -> ParseError {
+		ParseError::Build {
+			statement: self.index,
+			span: self.span_of(self.text),
+			text: self.text.to_string(),
+			error: error
+		}
+	}
+
+	pub fn span_of(&self, data: &str) -> Span {
+		let start = data.as_ptr() as usize - self.source.as_ptr() as usize;
+		let end = match data.char_indices().last() {
+			Some((i, _)) => start + i,
+			None => start
+		};
+		let (line, column) = position(self.source, start);
+		let (end_line, end_column) = position(self.source, end);
+
+		Span {
+			line: line,
+			column: column,
+			end_line: end_line,
+			end_column: end_column
+		}
+	}
+}
+
+/// Returns the exact value of a decimal with an optional sign, fraction
+/// and exponent, or None if the text is not one.
+fn decimal_to_rational(text: &str) -> Option<BigRational> {
+	let (mantissa, exponent) = match text.find(|c| c == 'e' || c == 'E') {
+		Some(i) => (&text[.. i], text[i + 1 ..].parse::<i64>().ok()?),
+		None => (text, 0)
+	};
+	let (negative, mantissa) = if mantissa.starts_with('-') {
+		(true, &mantissa[1 ..])
+	} else if mantissa.starts_with('+') {
+		(false, &mantissa[1 ..])
+	} else {
+		(false, mantissa)
+	};
+	let (whole, fraction) = match mantissa.find('.') {
+		Some(i) => (&mantissa[.. i], &mantissa[i + 1 ..]),
+		None => (mantissa, "")
+	};
+	if whole.len() + fraction.len() == 0 || !whole.chars().chain(fraction.chars()).all(|c| c.is_digit(10)) {
+		return None;
+	}
+
+	// Any finite f64 is within a few hundred powers of ten, which keeps
+	// the power below from growing without bound
+	let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+	let scale = exponent - fraction.len() as i64;
+	if digits.is_zero() {
+		return Some(BigRational::zero());
+	}
+	if scale.abs() > 2000 {
+		return None;
+	}
+	let power = pow(BigInt::from(10), scale.abs() as usize);
+	let value = if scale >= 0 {
+		BigRational::from_integer(digits * power)
+	} else {
+		BigRational::new(digits, power)
+	};
+	Some(if negative { -value } else { value })
+}
+
+/// Returns the 1-based line and column of the byte `offset` in `text`.
+fn position(text: &str, offset: usize) -> (usize, usize) {
+	let before = &text[.. offset];
+	let line = before.matches('\n').count() + 1;
+	let line_start = match before.rfind('\n') {
+		Some(i) => i + 1,
+		None => 0
+	};
+
+	(line, before[line_start ..].chars().count() + 1)
+}
+
+fn starts_with_keyword(line: &str, keyword: &str) -> bool {
+	line.starts_with(keyword) && match line[keyword.len() ..].chars().next() {
+		Some(c) => !(c.is_alphanumeric() || c == '_'),
+		None => true
 	}
 }
 
@@ -257,6 +735,7 @@ impl Parser {
 #[cfg(test)]
 mod LPParser_tests {
 	use super::*;
+	use builder::Builder;
 
 	#[test]
 	fn line_type_test() {
@@ -268,11 +747,12 @@ mod LPParser_tests {
 		let max_objective= "maximize obj: 3*a;";
 		let constraint = "subject to foo_constraint: a == 10;";
 
-		assert_eq!(p.get_line_type(comment), LineType::Comment);
-		assert_eq!(p.get_line_type(variable), LineType::Variable);
-		assert_eq!(p.get_line_type(min_objective), LineType::Objective);
-		assert_eq!(p.get_line_type(max_objective), LineType::Objective);
-		assert_eq!(p.get_line_type(constraint), LineType::Constraint);
+		assert_eq!(p.get_line_type(comment), Some(LineType::Comment));
+		assert_eq!(p.get_line_type(variable), Some(LineType::Variable));
+		assert_eq!(p.get_line_type(min_objective), Some(LineType::Objective));
+		assert_eq!(p.get_line_type(max_objective), Some(LineType::Objective));
+		assert_eq!(p.get_line_type(constraint), Some(LineType::Constraint));
+		assert_eq!(p.get_line_type("variable x"), None);
 	}
 
 
@@ -280,27 +760,207 @@ mod LPParser_tests {
 	fn parse_variable_declaration_test() {
 		let p = Parser::new();
 
-		let variable = "var a;";
+		let variable = statement("var a");
 		let expected = Variable {
 			name: "a".to_string(),
 			coefficient: 0.,
 		};
 
-		assert_eq!(p.parse_variable_declaration(variable), expected);
+		assert_eq!(p.parse_variable_declaration(&variable).unwrap(), expected);
+		assert_eq!(p.parse_variable_declaration(&statement("var a free")).unwrap(), expected);
+		assert_eq!(p.parse_variable_declaration(&statement("var a <= 0")).unwrap(), expected);
+	}
+
+	#[test]
+	fn parse_variable_bounds_test() {
+		let p = Parser::new();
+
+		assert_eq!(p.parse_variable_bounds(&statement("var a")).unwrap(), None);
+		assert_eq!(p.parse_variable_bounds(&statement("var a free")).unwrap(), Some((NEG_INFINITY, INFINITY)));
+		assert_eq!(p.parse_variable_bounds(&statement("var a<=0")).unwrap(), Some((NEG_INFINITY, 0.)));
+		assert_eq!(p.parse_variable_bounds(&statement("var a binary")).unwrap(), Some((0., 1.)));
+		assert_eq!(p.parse_variable_bounds(&statement("var a integer")).unwrap(), None);
+		assert_eq!(p.parse_variable_bounds(&statement("var a integer free")).unwrap(), Some((NEG_INFINITY, INFINITY)));
+		assert!(p.parse_variable_bounds(&statement("var a binary free")).is_err());
+		assert!(p.parse_variable_bounds(&statement("var a binary <= 1")).is_err());
+		assert!(p.parse_variable_bounds(&statement("var a free >= 0")).is_err());
+		assert_eq!(p.parse_variable_bounds(&statement("var a <= 3")).unwrap(), Some((NEG_INFINITY, 3.)));
+		assert_eq!(p.parse_variable_bounds(&statement("var a >= -1.5")).unwrap(), Some((-1.5, INFINITY)));
+		assert_eq!(p.parse_variable_bounds(&statement("var a integer >=2<= 5")).unwrap(), Some((2., 5.)));
+
+		match p.parse_variable_bounds(&statement("var a <= 3 >= 2")) {
+			Err(ParseError::MalformedTerm { .. }) => (),
+			other => panic!("Unexpected result {:?}", other)
+		}
+		match p.parse_variable_bounds(&statement("var a <= zero")) {
+			Err(ParseError::BadNumber { text, .. }) => assert_eq!(text, "zero"),
+			other => panic!("Unexpected result {:?}", other)
+		}
+	}
+
+	#[test]
+	fn components_bounds_test() {
+		let text = "var a free;\nvar b <= 0;\nvar c;\nvar d integer;\nvar e binary;\nvar f integer >= 1 <= 4;\nmaximize obj: a + b + c;";
+		let components = Parser::parse_components_from_text(text).unwrap();
+
+		assert_eq!(components.variables.len(), 6);
+		assert_eq!(components.bounds.len(), 4);
+		assert_eq!(components.bounds["a"], (NEG_INFINITY, INFINITY));
+		assert_eq!(components.bounds["b"], (NEG_INFINITY, 0.));
+		assert_eq!(components.bounds["e"], (0., 1.));
+		assert_eq!(components.bounds["f"], (1., 4.));
+		assert_eq!(components.integers, vec!["d", "e", "f"]);
 	}
 
 	#[test]
 	fn parse_vars_test() {
 		let p = Parser::new();
 
-		let data = "3.5*a + 1.5*b + -0.5*c";
+		let data = statement("3.5*a + 1.5*b + -0.5*c");
 		let expected = vec![
 			generate_var("a".to_string(), 3.5),
 			generate_var("b".to_string(), 1.5),
 			generate_var("c".to_string(), -0.5),
 		];
 
-		assert_eq!(p.parse_objective_vars(data), expected);
+		assert_eq!(p.parse_objective_vars(&data, data.text).unwrap(), expected);
+	}
+
+	#[test]
+	fn unknown_statement_test() {
+		let text = "var a;\nmaximize obj: a;\n  foo bar;";
+		let err = Parser::parse_components_from_text(text).unwrap_err();
+
+		assert_eq!(err, ParseError::UnknownStatement {
+			statement: 2,
+			span: Span { line: 3, column: 3, end_line: 3, end_column: 9 },
+			text: "foo bar".to_string()
+		});
+	}
+
+	#[test]
+	fn malformed_term_test() {
+		let text = "var a;\nvar b;\nmaximize obj: a + 2*b c;";
+		let err = Parser::parse_components_from_text(text).unwrap_err();
+
+		assert_eq!(err, ParseError::MalformedTerm {
+			statement: 2,
+			span: Span { line: 3, column: 19, end_line: 3, end_column: 23 },
+			text: "2*b c".to_string()
+		});
+	}
+
+	#[test]
+	fn bad_number_test() {
+		let text = "var a;\nmaximize obj: a;\nsubject to c1: 1.2.3*a <= 4;";
+		let err = Parser::parse_components_from_text(text).unwrap_err();
+
+		assert_eq!(err, ParseError::BadNumber {
+			statement: 2,
+			span: Span { line: 3, column: 16, end_line: 3, end_column: 20 },
+			text: "1.2.3".to_string()
+		});
+
+		let text = "var a;\nmaximize obj: a;\nsubject to c1: a <= four;";
+		match Parser::parse_components_from_text(text).unwrap_err() {
+			ParseError::BadNumber { text, .. } => assert_eq!(text, "four"),
+			err => panic!("Unexpected error {:?}", err)
+		}
+	}
+
+	#[test]
+	fn decimal_to_rational_test() {
+		let ratio = |numer: i64, denom: i64| BigRational::new(BigInt::from(numer), BigInt::from(denom));
+		assert_eq!(decimal_to_rational("0.1"), Some(ratio(1, 10)));
+		assert_eq!(decimal_to_rational("-2.50"), Some(ratio(-5, 2)));
+		assert_eq!(decimal_to_rational("+.5"), Some(ratio(1, 2)));
+		assert_eq!(decimal_to_rational("3."), Some(ratio(3, 1)));
+		assert_eq!(decimal_to_rational("1.5e2"), Some(ratio(150, 1)));
+		assert_eq!(decimal_to_rational("12E-3"), Some(ratio(3, 250)));
+		assert_eq!(decimal_to_rational("0e99999999999"), Some(ratio(0, 1)));
+		for text in &["", ".", "-", "1.2.3", "1e", "inf", "NaN", "0x10", "1e99999999999"] {
+			assert_eq!(decimal_to_rational(text), None);
+		}
+	}
+
+	#[test]
+	fn exact_lp_test() {
+		let ratio = |numer: i64, denom: i64| BigRational::new(BigInt::from(numer), BigInt::from(denom));
+		let text = "var a;\nvar b <= 0;\nminimize obj: 0.1*a + -0.7*b;\nsubject to c1: -0.1*a + b >= -1.1;";
+		let lp = Parser::exact_lp_from_text(text, Builder::new()).unwrap();
+		assert_eq!(lp.c[.. 2].to_vec(), vec![ratio(1, 10), ratio(7, 10)]);
+		assert_eq!(lp.A.column(0).collect::<Vec<_>>(), vec![(0, ratio(-1, 10))]);
+		assert_eq!(lp.A.column(1).collect::<Vec<_>>(), vec![(0, ratio(-1, 1))]);
+		assert_eq!(lp.b, vec![ratio(-11, 10)]);
+		assert_eq!(lp.upper[1], None);
+
+		// 0.1 and 0.10000000000000001 are the same f64 but not the same number
+		let text = "var a;\nvar b free;\nminimize obj: 0.1*a + 0.10000000000000001*b;\nsubject to c1: a + b >= 0.10000000000000001;";
+		let lp = Parser::exact_lp_from_text(text, Builder::new()).unwrap();
+		let slightly_more = BigRational::new(BigInt::from(10000000000000001i64), pow(BigInt::from(10), 17));
+		assert_eq!(lp.c[.. 2].to_vec(), vec![ratio(1, 10), slightly_more.clone()]);
+		assert_eq!(lp.b, vec![slightly_more.clone()]);
+
+		// The negative part of the free b gets the negated coefficients
+		assert_eq!(lp.vars[2], "negative_0");
+		assert_eq!(lp.c[2], -slightly_more);
+		assert_eq!(lp.A.column(2).collect::<Vec<_>>(), vec![(0, ratio(-1, 1))]);
+		assert_eq!(lp.A.column(3).collect::<Vec<_>>(), vec![(0, ratio(-1, 1))]);
+		assert_eq!(lp.lower, vec![ratio(0, 1); 4]);
+
+		// Declared bounds are read exactly too
+		let text = "var a >= 0.1 <= 0.3;\nvar b <= -0.2;\nminimize obj: a + -1*b;\nsubject to c1: a + b >= -1;";
+		let lp = Parser::exact_lp_from_text(text, Builder::new()).unwrap();
+		assert_eq!((lp.lower[0].clone(), lp.upper[0].clone()), (ratio(1, 10), Some(ratio(3, 10))));
+		assert_eq!((lp.lower[1].clone(), lp.upper[1].clone()), (ratio(1, 5), None));
+	}
+
+	#[test]
+	fn missing_objective_test() {
+		let text = "var a;\nsubject to c1: a <= 4;\n";
+		let err = Parser::parse_components_from_text(text).unwrap_err();
+
+		assert_eq!(err, ParseError::MissingObjective {
+			statement: 2,
+			span: Span { line: 3, column: 1, end_line: 3, end_column: 1 },
+			text: String::new()
+		});
+	}
+
+	#[test]
+	fn build_error_test() {
+		let text = "var a;\nmaximize obj: a;\nsubject to cap: a + b <= 4;";
+		let err = Parser::lp_from_text(text, Builder::new()).unwrap_err();
+
+		assert_eq!(err, ParseError::Build {
+			statement: 2,
+			span: Span { line: 3, column: 1, end_line: 3, end_column: 26 },
+			text: "subject to cap: a + b <= 4".to_string(),
+			error: BuildError::UnknownVariable {
+				variable: "b".to_string(),
+				constraint: "cap".to_string()
+			}
+		});
+
+		let text = "var a;\nmaximize first: a;\nminimize second: a;";
+		match Parser::parse_components_from_text(text).unwrap_err() {
+			ParseError::Build { statement, error, .. } => {
+				assert_eq!(statement, 2);
+				assert_eq!(error, BuildError::DuplicateObjective {
+					existing: "first".to_string(),
+					duplicate: "second".to_string()
+				});
+			},
+			err => panic!("Unexpected error {:?}", err)
+		}
+	}
+
+	fn statement<'a>(text: &'a str) -> Statement<'a> {
+		Statement {
+			index: 0,
+			source: text,
+			text: text
+		}
 	}
 
 	fn generate_var(name: String, coeff: f64) -> Variable {
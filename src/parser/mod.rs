@@ -6,26 +6,75 @@
 extern crate regex;
 use self::regex::Regex;
 
-use lp::Lp;
+use lp::{ExactLp, Lp};
+use num_rational::BigRational;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use builder::{Variable, Constraint, Objective, BuilderBase};
+use builder::{Variable, Constraint, Objective, BuilderBase, BuildError};
 
 
+mod impl_cplex;
+mod impl_format;
+mod impl_mps;
 mod impl_parser;
 
+pub use self::impl_cplex::{components_to_cplex, lp_to_cplex};
+pub use self::impl_format::format_components;
+pub use self::impl_mps::{components_to_mps, lp_to_mps};
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Components {
 	pub variables: Vec<Variable>,
 	pub constraints: Vec<Constraint>,
-	pub objective: Objective
+	pub objective: Objective,
+	/// Lower and upper bounds of the variables declared `free`, `binary`
+	/// or with `>=` and `<=`.
+	pub bounds: HashMap<String, (f64, f64)>,
+	/// Variables declared `integer` or `binary`.
+	pub integers: Vec<String>
+}
+
+/// A region of the parsed text. Lines and columns are 1-based and the
+/// end position is inclusive of the last character of the region.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+	pub line: usize,
+	pub column: usize,
+	pub end_line: usize,
+	pub end_column: usize
+}
+
+/// Describes why a piece of LP text could not be parsed.
+///
+/// Every variant carries the index of the `;`-terminated statement it
+/// occurred in (counting from 0 and including comments), or of the line
+/// for CPLEX LP and MPS text, the span of the offending text and the text itself.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+	/// The statement is not a comment, variable, objective or constraint.
+	UnknownStatement { statement: usize, span: Span, text: String },
+	/// A declaration, or a term of an objective or constraint, is malformed.
+	MalformedTerm { statement: usize, span: Span, text: String },
+	/// No objective was declared. Points at the end of the text.
+	MissingObjective { statement: usize, span: Span, text: String },
+	/// A coefficient or constant is not a valid number.
+	BadNumber { statement: usize, span: Span, text: String },
+	/// The statement was parsed but rejected by the Builder, e.g. because
+	/// it uses an undeclared variable or declares a second objective.
+	Build { statement: usize, span: Span, text: String, error: BuildError },
 }
 
 pub trait ParserBase {
-	fn parse_components_from_text(text: &str) -> Components;
-	fn parse_components_from_file(file: &mut File) -> Components;
+	fn parse_components_from_text(text: &str) -> Result<Components, ParseError>;
+	fn parse_components_from_file(file: &mut File) -> Result<Components, ParseError>;
 
-	fn lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Lp;
-	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Lp;
+	fn lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<Lp, ParseError>;
+	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<Lp, ParseError>;
+
+	fn exact_lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Result<ExactLp, ParseError>;
+	fn exact_lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Result<ExactLp, ParseError>;
 }
 
 #[derive(Debug)]
@@ -33,6 +82,89 @@ pub struct Parser {
 	variable_declaration_regex: Regex,
 	variable_regex: Regex,
 	constraint_regex: Regex,
-	equation_component_regex: Regex,
 	objective_regex: Regex,
 }
+
+/// Reads LPs in the CPLEX LP file format, with `Maximize` or `Minimize`,
+/// `Subject To`, `Bounds`, `General`, `Binary` and `End` sections and `\`
+/// comments. `components_to_cplex` and `lp_to_cplex` write the format.
+#[derive(Debug)]
+pub struct CplexParser;
+
+/// Reads LPs in the free MPS format, with `NAME`, `OBJSENSE`, `ROWS`,
+/// `COLUMNS`, `RHS`, `RANGES`, `BOUNDS` and `ENDATA` sections, integer
+/// markers and `*` comments. Fields are separated by whitespace, so names
+/// may not contain spaces. `components_to_mps` and `lp_to_mps` write the
+/// format.
+///
+/// The first `N` row is the objective and other `N` rows are dropped, as
+/// is any objective constant given in `RHS`. Errors give the 0-based line
+/// as their statement.
+#[derive(Debug)]
+pub struct MpsParser;
+
+/// Reads LPs in the fixed MPS format, whose fields lie in columns 2-3,
+/// 5-12, 15-22, 25-36, 40-47 and 50-61 and may hold spaces. Otherwise
+/// reads as `MpsParser` does.
+#[derive(Debug)]
+pub struct FixedMpsParser;
+
+impl ParseError {
+	/// Index of the statement the error occurred in.
+	pub fn statement(&self) -> usize {
+		match *self {
+			ParseError::UnknownStatement { statement, .. } |
+			ParseError::MalformedTerm { statement, .. } |
+			ParseError::MissingObjective { statement, .. } |
+			ParseError::BadNumber { statement, .. } |
+			ParseError::Build { statement, .. } => statement
+		}
+	}
+
+	/// Location of the offending text.
+	pub fn span(&self) -> Span {
+		match *self {
+			ParseError::UnknownStatement { span, .. } |
+			ParseError::MalformedTerm { span, .. } |
+			ParseError::MissingObjective { span, .. } |
+			ParseError::BadNumber { span, .. } |
+			ParseError::Build { span, .. } => span
+		}
+	}
+
+	/// The offending text.
+	pub fn text(&self) -> &str {
+		match *self {
+			ParseError::UnknownStatement { ref text, .. } |
+			ParseError::MalformedTerm { ref text, .. } |
+			ParseError::MissingObjective { ref text, .. } |
+			ParseError::BadNumber { ref text, .. } |
+			ParseError::Build { ref text, .. } => text
+		}
+	}
+}
+
+impl fmt::Display for Span {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.line == self.end_line {
+			write!(f, "{}:{}-{}", self.line, self.column, self.end_column)
+		} else {
+			write!(f, "{}:{}-{}:{}", self.line, self.column, self.end_line, self.end_column)
+		}
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let kind = match *self {
+			ParseError::UnknownStatement { .. } => "unknown statement".to_string(),
+			ParseError::MalformedTerm { .. } => "malformed term".to_string(),
+			ParseError::MissingObjective { .. } => "no objective function provided".to_string(),
+			ParseError::BadNumber { .. } => "invalid number".to_string(),
+			ParseError::Build { ref error, .. } => error.to_string(),
+		};
+		write!(f, "{} at {} (statement {}): {:?}", kind, self.span(), self.statement(), self.text())
+	}
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,581 @@
+use super::*;
+use lp::{ExactLp, Optimization, Substitution};
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::f64::NAN;
+
+// A dense tableau in rationals. Columns are the Lp's columns shifted to a
+// lower bound of zero, then a slack for each finite upper bound, then an
+// artificial var for each row; the last column is the RHS. Rows are the
+// Lp's rows followed by one `x + slack = upper - lower` row per upper bound.
+struct Tableau {
+	rows: Vec<Vec<BigRational>>,
+	// Reduced cost of each column of the current phase, with minus the
+	// objective value in the RHS column
+	objective: Vec<BigRational>,
+	basis: Vec<usize>,
+	// Whether each row was negated so that its RHS starts non-negative
+	negated: Vec<bool>,
+	// Column of the upper bound slack of each Lp column
+	upper_slacks: Vec<Option<usize>>,
+	num_cols: usize,
+	num_slacks: usize,
+	iterations: usize
+}
+
+impl ExactSimplexSolver {
+	/// Constructor for ExactSimplexSolver struct.
+	pub fn new(lp: ExactLp) -> Self {
+		ExactSimplexSolver {
+			lp: lp,
+			max_iterations: None
+		}
+	}
+
+	/// Solves the ExactLp by the two-phase simplex method in exact arithmetic.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate num_rational;
+	/// # extern crate rulp;
+	/// use num_rational::BigRational;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{ExactSimplexSolver, Status};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	var y;
+	/// 	maximize obj: 0.3*x + 0.1*y;
+	/// 	subject to c1: 0.1*x + 0.2*y <= 0.3;
+	/// 	subject to c2: x <= 2;
+	/// ";
+	/// let lp = Parser::exact_lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// let solution = ExactSimplexSolver::new(lp).solve().unwrap();
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// // 0.6 + 0.05 exactly, where floating point gives 0.6499999999999999
+	/// assert_eq!(solution.objective.unwrap(), "13/20".parse::<BigRational>().unwrap());
+	/// # }
+	/// ```
+	pub fn solve(&self) -> Result<ExactSolution, SolveError> {
+		validate_exact_lp(&self.lp)?;
+
+		let mut tableau = Tableau::new(&self.lp);
+		let phase_one_cols = tableau.num_cols + tableau.num_slacks;
+		if let Err(status) = tableau.optimize(phase_one_cols, self.max_iterations) {
+			return Ok(self.unsolved(status, tableau.iterations));
+		}
+		if tableau.objective[tableau.rhs()].is_negative() {
+			return Ok(self.unsolved(Status::Infeasible, tableau.iterations));
+		}
+		tableau.drive_out_artificials();
+
+		tableau.set_objective(&self.lp);
+		if let Err(status) = tableau.optimize(phase_one_cols, self.max_iterations) {
+			return Ok(self.unsolved(status, tableau.iterations));
+		}
+
+		let (values, duals) = tableau.solution(&self.lp);
+		let reduced_costs = get_reduced_costs(&self.lp, &duals);
+		if !is_optimal(&self.lp, &values, &reduced_costs) {
+			return Ok(self.unsolved(Status::NumericalFailure, tableau.iterations));
+		}
+
+		let objective = values.iter().zip(self.lp.c.iter()).map(|(x, c)| x * c).fold(BigRational::zero(), |sum, term| sum + term);
+		let basis = tableau.get_basis();
+		let (values, reduced_costs) = undo_exact_substitutions(&self.lp, values, reduced_costs);
+
+		Ok(ExactSolution {
+			lp: self.lp.clone(),
+			values: Some(values),
+			objective: Some(objective),
+			status: Status::Optimal,
+			basis: Some(basis),
+			duals: Some(duals),
+			reduced_costs: Some(reduced_costs),
+			iterations: tableau.iterations
+		})
+	}
+
+	fn unsolved(&self, status: Status, iterations: usize) -> ExactSolution {
+		ExactSolution {
+			lp: self.lp.clone(),
+			values: None,
+			objective: None,
+			status: status,
+			basis: None,
+			duals: None,
+			reduced_costs: None,
+			iterations: iterations
+		}
+	}
+}
+
+impl Tableau {
+	// Starts Phase I from the basis of artificial vars
+	fn new(lp: &ExactLp) -> Self {
+		let num_rows = lp.A.rows();
+		let num_cols = lp.A.cols();
+		let upper_slacks: Vec<Option<usize>> = {
+			let mut next = num_cols;
+			lp.upper.iter().map(|upper| upper.as_ref().map(|_| { next += 1; next - 1 })).collect()
+		};
+		let num_slacks = upper_slacks.iter().filter(|slack| slack.is_some()).count();
+		let total_rows = num_rows + num_slacks;
+		let width = num_cols + num_slacks + total_rows + 1;
+
+		let mut rows = vec![vec![BigRational::zero(); width]; total_rows];
+		for (row, col, value) in lp.A.triplets() {
+			rows[row][width - 1] -= &value * &lp.lower[col];
+			rows[row][col] = value;
+		}
+		for row in 0 .. num_rows {
+			rows[row][width - 1] += &lp.b[row];
+		}
+		let mut row = num_rows;
+		for col in 0 .. num_cols {
+			if let (Some(slack), &Some(ref upper)) = (upper_slacks[col], &lp.upper[col]) {
+				rows[row][col] = BigRational::one();
+				rows[row][slack] = BigRational::one();
+				rows[row][width - 1] = upper - &lp.lower[col];
+				row += 1;
+			}
+		}
+
+		let mut negated = vec![false; total_rows];
+		let mut objective = vec![BigRational::zero(); width];
+		for row in 0 .. total_rows {
+			if rows[row][width - 1].is_negative() {
+				negated[row] = true;
+				for value in rows[row].iter_mut() {
+					*value = -value.clone();
+				}
+			}
+			rows[row][num_cols + num_slacks + row] = BigRational::one();
+			for col in 0 .. num_cols + num_slacks {
+				objective[col] -= &rows[row][col];
+			}
+			objective[width - 1] -= &rows[row][width - 1];
+		}
+
+		Tableau {
+			rows: rows,
+			objective: objective,
+			basis: (0 .. total_rows).map(|row| num_cols + num_slacks + row).collect(),
+			negated: negated,
+			upper_slacks: upper_slacks,
+			num_cols: num_cols,
+			num_slacks: num_slacks,
+			iterations: 0
+		}
+	}
+
+	fn rhs(&self) -> usize {
+		self.objective.len() - 1
+	}
+
+	// Pivots by Bland's rule among the first `allowed` columns until no
+	// reduced cost is negative
+	fn optimize(&mut self, allowed: usize, max_iterations: Option<usize>) -> Result<(), Status> {
+		let rhs = self.rhs();
+		loop {
+			let col = match (0 .. allowed).find(|&col| self.objective[col].is_negative()) {
+				Some(col) => col,
+				None => return Ok(())
+			};
+			if max_iterations.map_or(false, |limit| self.iterations >= limit) {
+				return Err(Status::IterationLimit);
+			}
+
+			let mut pivot: Option<(usize, BigRational)> = None;
+			for row in 0 .. self.rows.len() {
+				if !self.rows[row][col].is_positive() {
+					continue;
+				}
+				let ratio = &self.rows[row][rhs] / &self.rows[row][col];
+				let better = match pivot {
+					None => true,
+					Some((other, ref best)) => ratio < *best || (ratio == *best && self.basis[row] < self.basis[other])
+				};
+				if better {
+					pivot = Some((row, ratio));
+				}
+			}
+
+			match pivot {
+				Some((row, _)) => self.pivot(row, col),
+				None => return Err(Status::Unbounded)
+			}
+			self.iterations += 1;
+		}
+	}
+
+	fn pivot(&mut self, row: usize, col: usize) {
+		let element = self.rows[row][col].clone();
+		for value in self.rows[row].iter_mut() {
+			*value = &*value / &element;
+		}
+
+		let pivot_row = self.rows[row].clone();
+		for other in 0 .. self.rows.len() {
+			if other != row && !self.rows[other][col].is_zero() {
+				let factor = self.rows[other][col].clone();
+				for (value, pivot_value) in self.rows[other].iter_mut().zip(pivot_row.iter()) {
+					*value -= &factor * pivot_value;
+				}
+			}
+		}
+		if !self.objective[col].is_zero() {
+			let factor = self.objective[col].clone();
+			for (value, pivot_value) in self.objective.iter_mut().zip(pivot_row.iter()) {
+				*value -= &factor * pivot_value;
+			}
+		}
+
+		self.basis[row] = col;
+	}
+
+	// Swaps each artificial var left in the basis, which is at zero, for
+	// any column with a nonzero entry in its row. A row with none is a
+	// combination of the others and keeps its artificial var at zero.
+	fn drive_out_artificials(&mut self) {
+		let first_artificial = self.num_cols + self.num_slacks;
+		for row in 0 .. self.rows.len() {
+			if self.basis[row] < first_artificial {
+				continue;
+			}
+			if let Some(col) = (0 .. first_artificial).find(|&col| !self.rows[row][col].is_zero()) {
+				self.pivot(row, col);
+			}
+		}
+	}
+
+	// Writes the Lp's objective in Min form over the nonbasic columns
+	fn set_objective(&mut self, lp: &ExactLp) {
+		let cost = |col: usize| -> BigRational {
+			if col >= self.num_cols {
+				BigRational::zero()
+			} else if lp.optimization == Optimization::Max {
+				-lp.c[col].clone()
+			} else {
+				lp.c[col].clone()
+			}
+		};
+
+		let mut objective: Vec<BigRational> = (0 .. self.objective.len() - 1).map(|col| cost(col)).collect();
+		objective.push(BigRational::zero());
+		for (row, &basic) in self.basis.iter().enumerate() {
+			let basic_cost = cost(basic);
+			if !basic_cost.is_zero() {
+				for (value, row_value) in objective.iter_mut().zip(self.rows[row].iter()) {
+					*value -= &basic_cost * row_value;
+				}
+			}
+		}
+		self.objective = objective;
+	}
+
+	// Values of the Lp's columns, and the duals of its rows in the Lp's
+	// own signs. An artificial column is a unit column of its row with no
+	// cost, so its reduced cost is minus the row's dual.
+	fn solution(&self, lp: &ExactLp) -> (Vec<BigRational>, Vec<BigRational>) {
+		let rhs = self.rhs();
+		let mut values = lp.lower.clone();
+		for (row, &basic) in self.basis.iter().enumerate() {
+			if basic < self.num_cols {
+				values[basic] += &self.rows[row][rhs];
+			}
+		}
+
+		let first_artificial = self.num_cols + self.num_slacks;
+		let duals = (0 .. lp.A.rows()).map(|row| {
+			let dual = -self.objective[first_artificial + row].clone();
+			let dual = if self.negated[row] { -dual } else { dual };
+			if lp.optimization == Optimization::Max { -dual } else { dual }
+		}).collect();
+
+		(values, duals)
+	}
+
+	// A column with an upper bound whose slack is nonbasic sits at that bound
+	fn get_basis(&self) -> Basis {
+		let is_basic = |col: usize| self.basis.contains(&col);
+		Basis {
+			statuses: (0 .. self.num_cols).map(|col| {
+				let at_upper = self.upper_slacks[col].map_or(false, |slack| !is_basic(slack));
+				if at_upper {
+					VarStatus::AtUpper
+				} else if is_basic(col) {
+					VarStatus::Basic
+				} else {
+					VarStatus::AtLower
+				}
+			}).collect()
+		}
+	}
+}
+
+impl fmt::Display for ExactSolution {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "")?;
+		match self.status {
+			Status::Optimal => {
+				if let Some(ref objective) = self.objective {
+					writeln!(f, "Optimal objective: {}", objective)?;
+				}
+				if let Some(ref values) = self.values {
+					for col in 0 .. values.len() - self.lp.num_artificial_vars {
+						let negative_part = self.lp.substitutions.iter().any(|substitution| match *substitution {
+							Substitution::Split { negative, .. } => negative == col,
+							_ => false
+						});
+						if !negative_part {
+							writeln!(f, "{}: {}", self.lp.vars[col], values[col])?;
+						}
+					}
+				}
+			},
+			Status::IterationLimit => writeln!(f, "Iteration limit reached")?,
+			Status::Infeasible => writeln!(f, "Infeasible")?,
+			Status::Unbounded => writeln!(f, "Unbounded")?,
+			_ => writeln!(f, "Numerical failure")?
+		}
+
+		write!(f, "")
+	}
+}
+
+// d_j = c_j - y^T A_j
+fn get_reduced_costs(lp: &ExactLp, duals: &Vec<BigRational>) -> Vec<BigRational> {
+	let mut reduced_costs = lp.c.clone();
+	for (row, col, value) in lp.A.triplets() {
+		reduced_costs[col] -= &duals[row] * &value;
+	}
+	reduced_costs
+}
+
+// Checks that the values are feasible and that every nonzero reduced cost
+// pushes its column against the bound it sits at, which proves optimality
+// for an Lp with equality rows and bounded columns
+fn is_optimal(lp: &ExactLp, values: &Vec<BigRational>, reduced_costs: &Vec<BigRational>) -> bool {
+	let mut A_x = vec![BigRational::zero(); lp.A.rows()];
+	for (row, col, value) in lp.A.triplets() {
+		A_x[row] += &value * &values[col];
+	}
+	if A_x != lp.b {
+		return false;
+	}
+
+	(0 .. lp.A.cols()).all(|col| {
+		let at_lower = values[col] == lp.lower[col];
+		let at_upper = lp.upper[col].as_ref().map_or(false, |upper| values[col] == *upper);
+		let within = values[col] >= lp.lower[col] && lp.upper[col].as_ref().map_or(true, |upper| values[col] <= *upper);
+		let (raises, lowers) = match lp.optimization {
+			Optimization::Max => (reduced_costs[col].is_positive(), reduced_costs[col].is_negative()),
+			Optimization::Min => (reduced_costs[col].is_negative(), reduced_costs[col].is_positive())
+		};
+		within && (!raises || at_upper) && (!lowers || at_lower)
+	})
+}
+
+fn undo_exact_substitutions(lp: &ExactLp, mut values: Vec<BigRational>, mut reduced_costs: Vec<BigRational>)
+		-> (Vec<BigRational>, Vec<BigRational>) {
+	for substitution in &lp.substitutions {
+		match *substitution {
+			Substitution::Negated { col } => {
+				values[col] = -values[col].clone();
+				reduced_costs[col] = -reduced_costs[col].clone();
+			},
+			Substitution::Split { positive, negative } => {
+				values[positive] = &values[positive] - &values[negative];
+			}
+		}
+	}
+	(values, reduced_costs)
+}
+
+// Checks the dimensions and bounds of an ExactLp, as validate_lp does for an Lp
+fn validate_exact_lp(lp: &ExactLp) -> Result<(), SolveError> {
+	let rows = lp.A.rows();
+	let cols = lp.A.cols();
+
+	if lp.b.len() != rows {
+		return Err(SolveError::RhsLength { rows: rows, len: lp.b.len() });
+	}
+	if lp.c.len() != cols {
+		return Err(SolveError::ObjectiveLength { cols: cols, len: lp.c.len() });
+	}
+	if lp.vars.len() != cols {
+		return Err(SolveError::VariablesLength { cols: cols, len: lp.vars.len() });
+	}
+	if lp.constraints.len() != rows {
+		return Err(SolveError::ConstraintsLength { rows: rows, len: lp.constraints.len() });
+	}
+	if lp.lower.len() != cols || lp.upper.len() != cols {
+		return Err(SolveError::BoundsLength { cols: cols, len: lp.lower.len().min(lp.upper.len()) });
+	}
+	for substitution in &lp.substitutions {
+		let col = match *substitution {
+			Substitution::Negated { col } if col >= cols => col,
+			Substitution::Split { positive, negative } if positive >= cols || positive == negative => positive,
+			Substitution::Split { negative, .. } if negative >= cols => negative,
+			_ => continue
+		};
+		return Err(SolveError::InvalidSubstitution { col: col });
+	}
+	if lp.num_artificial_vars > rows || lp.num_artificial_vars > cols {
+		return Err(SolveError::InvalidArtificialVars {
+			num_artificial_vars: lp.num_artificial_vars,
+			rows: rows,
+			cols: cols
+		});
+	}
+	for col in 0 .. cols {
+		if let Some(ref upper) = lp.upper[col] {
+			if *upper < lp.lower[col] {
+				return Err(SolveError::InvalidBounds {
+					var: lp.vars[col].clone(),
+					lower: to_f64(&lp.lower[col]),
+					upper: to_f64(upper)
+				});
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn to_f64(value: &BigRational) -> f64 {
+	match (value.numer().to_f64(), value.denom().to_f64()) {
+		(Some(numer), Some(denom)) => numer / denom,
+		_ => NAN
+	}
+}
+
+#[cfg(test)]
+mod exact_tests {
+	use super::*;
+	use builder::{Builder, BuilderBase};
+	use num_bigint::BigInt;
+	use parser::{Parser, ParserBase};
+
+	fn ratio(numer: i64, denom: i64) -> BigRational {
+		BigRational::new(BigInt::from(numer), BigInt::from(denom))
+	}
+
+	fn solve(text: &str) -> ExactSolution {
+		let lp = Parser::exact_lp_from_text(text, Builder::new()).unwrap();
+		ExactSimplexSolver::new(lp).solve().unwrap()
+	}
+
+	#[test]
+	fn solve_test() {
+		let text = "
+			var x;
+			var y;
+			maximize obj: 3*x + 2*y;
+			subject to c1: x + y <= 4;
+			subject to c2: x + 3*y <= 6;
+			subject to c3: x <= 3;
+		";
+		let expected = SimplexSolver::new(Parser::lp_from_text(text, Builder::new()).unwrap()).solve().unwrap();
+		let solution = solve(text);
+		assert_eq!(solution.status, Status::Optimal);
+		assert_eq!(solution.objective, Some(ratio(11, 1)));
+		assert_eq!(solution.values.as_ref().unwrap()[.. 2].to_vec(), vec![ratio(3, 1), ratio(1, 1)]);
+		assert_eq!(solution.duals, Some(expected.duals.unwrap().iter().map(|&dual| BigRational::from_float(dual).unwrap()).collect()));
+		assert_eq!(solution.basis, expected.basis);
+	}
+
+	#[test]
+	fn bounds_test() {
+		// The optimum x = 3/7, y = 1 sits at y's upper bound, and z is
+		// split into two parts
+		let text = "
+			var x;
+			var y binary;
+			var z free;
+			maximize obj: 0.7*x + 0.3*y + -0.2*z;
+			subject to c1: 0.7*x + 0.2*y <= 0.5;
+			subject to c2: z + -1*x >= -0.3;
+		";
+		let solution = solve(text);
+		assert_eq!(solution.status, Status::Optimal);
+		let values = solution.values.clone().unwrap();
+		assert_eq!(values[.. 3].to_vec(), vec![ratio(3, 7), ratio(1, 1), ratio(9, 70)]);
+		assert_eq!(solution.objective, Some(ratio(3, 5) - ratio(9, 350)));
+		assert_eq!(solution.basis.as_ref().unwrap().statuses[1], VarStatus::AtUpper);
+		assert_eq!(solution.reduced_costs.as_ref().unwrap()[1], ratio(11, 70));
+		assert!(solution.to_string().contains("x: 3/7"));
+	}
+
+	#[test]
+	fn cycling_test() {
+		// Beale's example, on which the textbook pivoting rules cycle
+		let text = "
+			var x4;
+			var x5;
+			var x6;
+			var x7;
+			maximize obj: 0.75*x4 + -20*x5 + 0.5*x6 + -6*x7;
+			subject to c1: 0.25*x4 + -8*x5 + -1*x6 + 9*x7 <= 0;
+			subject to c2: 0.5*x4 + -12*x5 + -0.5*x6 + 3*x7 <= 0;
+			subject to c3: x6 <= 1;
+		";
+		let solution = solve(text);
+		assert_eq!(solution.status, Status::Optimal);
+		assert_eq!(solution.objective, Some(ratio(5, 4)));
+	}
+
+	#[test]
+	fn redundant_row_test() {
+		// The third row is the sum of the first two
+		let text = "
+			var x;
+			var y;
+			minimize obj: 0.3*x + 0.2*y;
+			subject to c1: x + y == 1.1;
+			subject to c2: x + -1*y == 0.1;
+			subject to c3: 2*x == 1.2;
+		";
+		let solution = solve(text);
+		assert_eq!(solution.status, Status::Optimal);
+		assert_eq!(solution.values.unwrap(), vec![ratio(3, 5), ratio(1, 2)]);
+		assert_eq!(solution.objective, Some(ratio(7, 25)));
+	}
+
+	#[test]
+	fn status_test() {
+		let infeasible = "var x;\nmaximize obj: x;\nsubject to c1: x <= 0.1;\nsubject to c2: x >= 0.2;";
+		assert_eq!(solve(infeasible).status, Status::Infeasible);
+
+		let unbounded = "var x;\nvar y;\nmaximize obj: x;\nsubject to c1: x + -1*y <= 0.1;";
+		let solution = solve(unbounded);
+		assert_eq!(solution.status, Status::Unbounded);
+		assert_eq!(solution.values, None);
+
+		let text = "var x;\nmaximize obj: x;\nsubject to c1: x <= 0.1;";
+		let mut solver = ExactSimplexSolver::new(Parser::exact_lp_from_text(text, Builder::new()).unwrap());
+		solver.max_iterations = Some(0);
+		assert_eq!(solver.solve().unwrap().status, Status::IterationLimit);
+
+		let mut lp = Parser::exact_lp_from_text(text, Builder::new()).unwrap();
+		lp.b.push(ratio(1, 1));
+		assert_eq!(ExactSimplexSolver::new(lp).solve(), Err(SolveError::RhsLength { rows: 1, len: 2 }));
+	}
+
+	#[test]
+	fn is_optimal_test() {
+		// max x with x + s = 1: x = 1 is optimal with y = 1, but not x = 0
+		let text = "var x;\nmaximize obj: x;\nsubject to c1: x <= 1;";
+		let lp = Parser::exact_lp_from_text(text, Builder::new()).unwrap();
+		let duals = vec![ratio(1, 1)];
+		let reduced_costs = get_reduced_costs(&lp, &duals);
+		assert_eq!(reduced_costs, vec![ratio(0, 1), ratio(-1, 1)]);
+		assert!(is_optimal(&lp, &vec![ratio(1, 1), ratio(0, 1)], &reduced_costs));
+		assert!(!is_optimal(&lp, &vec![ratio(0, 1), ratio(1, 1)], &reduced_costs));
+		assert!(!is_optimal(&lp, &vec![ratio(1, 2), ratio(0, 1)], &reduced_costs));
+	}
+}
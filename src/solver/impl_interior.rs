@@ -0,0 +1,783 @@
+use super::*;
+use lp::{Lp, Optimization};
+use std::f64::INFINITY;
+use std::time::Instant;
+
+// Iterations after which the iterates are taken not to converge
+const MAX_ITERATIONS: usize = 200;
+// Fraction of the distance to the boundary of the positive orthant taken by a step
+const STEP_FRACTION: f64 = 0.995;
+// Size at which the iterates are taken to diverge
+const DIVERGENCE: f64 = 1e12;
+// Step lengths below which the iterates are taken to have stalled
+const STALL: f64 = 1e-10;
+
+// The state of one solve, on the Lp in Min form with every column shifted
+// by its lower bound: minimize cost^T x subject to A x = b and
+// x + w = upper with x, w >= 0, whose dual is A^T y + z - v = cost with
+// z, v >= 0. Entries of w and v are kept for columns with an upper bound
+// only. Columns whose bounds meet stay at zero and take no part.
+struct Interior<'a> {
+	lp: &'a Lp,
+	options: &'a InteriorPointOptions,
+	columns: Vec<Vec<(usize, f64)>>,
+	cost: Vec<f64>,
+	b: Vec<f64>,
+	upper: Vec<f64>,
+	fixed: Vec<bool>,
+	x: Vec<f64>,
+	w: Vec<f64>,
+	y: Vec<f64>,
+	z: Vec<f64>,
+	v: Vec<f64>,
+	iterations: usize,
+	deadline: Option<Instant>
+}
+
+// A Newton step for each of the iterates
+struct Direction {
+	x: Vec<f64>,
+	w: Vec<f64>,
+	y: Vec<f64>,
+	z: Vec<f64>,
+	v: Vec<f64>
+}
+
+// Dense Cholesky factor of the normal matrix A Theta A^T. A row whose pivot
+// vanishes, e.g. one that depends on the rows before it, is dropped and
+// takes zero in every solve.
+struct Normal {
+	factor: Vec<Vec<f64>>,
+	dropped: Vec<bool>
+}
+
+enum Outcome {
+	Converged,
+	Limit(Status),
+	Diverged
+}
+
+impl SolverBase for InteriorPointSolver {
+	/// Constructor for InteriorPointSolver struct with default options.
+	fn new(lp: Lp) -> Self {
+		InteriorPointSolver::with_options(lp, InteriorPointOptions::default())
+	}
+
+	/// Solves the Lp by Mehrotra's predictor-corrector method.
+	///
+	/// Each iteration factorizes `A Theta A^T` once and solves with it for
+	/// both an affine scaling step and a centering and correcting step.
+	/// With `InteriorPointOptions::crossover` the SimplexSolver then starts
+	/// from a basis of the columns furthest from their bounds, and gives
+	/// the final Solution.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate rulp;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{InteriorPointSolver, SolverBase, Status};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	var y;
+	/// 	maximize obj: 3*x + 2*y;
+	/// 	subject to c1: x + y <= 4;
+	/// 	subject to c2: x + 3*y <= 6;
+	/// 	subject to c3: x <= 3;
+	/// ";
+	/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// let solution = InteriorPointSolver::new(lp).solve().unwrap();
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// assert!((solution.objective.unwrap() - 11.).abs() < 1e-9);
+	/// assert!(solution.basis.is_some());
+	/// # }
+	/// ```
+	fn solve(&self) -> Result<Solution, SolveError> {
+		validate_lp(&self.lp)?;
+
+		let mut interior = Interior::new(&self.lp, &self.options);
+		match interior.iterate() {
+			Outcome::Converged => {},
+			Outcome::Limit(status) => return Ok(interior.unsolved(status)),
+			Outcome::Diverged => return interior.simplex(None)
+		}
+
+		if self.options.crossover {
+			let basis = interior.crossover_basis();
+			interior.simplex(Some(basis))
+		} else {
+			Ok(interior.solution())
+		}
+	}
+}
+
+impl InteriorPointSolver {
+	/// Constructor for InteriorPointSolver struct with non-default options.
+	pub fn with_options(lp: Lp, options: InteriorPointOptions) -> Self {
+		InteriorPointSolver {
+			lp: lp,
+			options: options
+		}
+	}
+}
+
+impl<'a> Interior<'a> {
+	// Starts from the least squares solutions of the primal and dual
+	// equations, pushed well inside the bounds
+	fn new(lp: &'a Lp, options: &'a InteriorPointOptions) -> Self {
+		let rows = lp.A.rows();
+		let cols = lp.A.cols();
+		let columns: Vec<Vec<(usize, f64)>> = (0 .. cols).map(|col| lp.A.column(col).collect()).collect();
+		let sign = match lp.optimization {
+			Optimization::Max => -1.,
+			Optimization::Min => 1.
+		};
+		let cost: Vec<f64> = lp.c.iter().map(|c| sign * c).collect();
+		let upper: Vec<f64> = (0 .. cols).map(|col| lp.upper[col] - lp.lower[col]).collect();
+		let fixed: Vec<bool> = upper.iter().map(|&upper| upper <= 0.).collect();
+
+		let mut b = lp.b.clone();
+		for col in 0 .. cols {
+			for &(row, value) in &columns[col] {
+				b[row] -= value * lp.lower[col];
+			}
+		}
+
+		let mut interior = Interior {
+			lp: lp,
+			options: options,
+			columns: columns,
+			cost: cost,
+			b: b,
+			upper: upper,
+			fixed: fixed,
+			x: vec![0.; cols],
+			w: vec![0.; cols],
+			y: vec![0.; rows],
+			z: vec![0.; cols],
+			v: vec![0.; cols],
+			iterations: 0,
+			deadline: options.time_limit.map(|limit| Instant::now() + limit)
+		};
+
+		let theta: Vec<f64> = (0 .. cols).map(|col| if interior.fixed[col] { 0. } else { 1. }).collect();
+		let normal = Normal::new(&interior.columns, &theta, rows);
+		let x = interior.transpose_multiply(&normal.solve(&interior.b));
+		let weighted_cost: Vec<f64> = (0 .. cols).map(|col| theta[col] * interior.cost[col]).collect();
+		interior.y = normal.solve(&interior.multiply(&weighted_cost));
+		let y_A = interior.transpose_multiply(&interior.y);
+
+		for col in 0 .. cols {
+			if interior.fixed[col] {
+				continue;
+			}
+			let z = interior.cost[col] - y_A[col];
+			if interior.bounded(col) {
+				let floor = interior.upper[col].min(2.) / 2.;
+				interior.x[col] = x[col].max(floor).min(interior.upper[col] - floor);
+				interior.w[col] = interior.upper[col] - interior.x[col];
+				interior.z[col] = z.max(0.) + 1.;
+				interior.v[col] = (-z).max(0.) + 1.;
+			} else {
+				interior.x[col] = x[col].max(1.);
+				interior.z[col] = z.max(1.);
+			}
+		}
+
+		interior
+	}
+
+	fn bounded(&self, col: usize) -> bool {
+		!self.fixed[col] && self.upper[col] < INFINITY
+	}
+
+	// Takes predictor-corrector steps until the residuals and the duality
+	// gap are within tolerance
+	fn iterate(&mut self) -> Outcome {
+		let cols = self.columns.len();
+		loop {
+			let (rb, ru, rc) = self.residuals();
+			if self.converged(&rb, &ru, &rc) {
+				return Outcome::Converged;
+			}
+			if let Some(status) = self.check_limits() {
+				return Outcome::Limit(status);
+			}
+			if self.iterations >= MAX_ITERATIONS || self.diverged() {
+				return Outcome::Diverged;
+			}
+
+			let pairs = (0 .. cols).filter(|&col| !self.fixed[col]).count() +
+				(0 .. cols).filter(|&col| self.bounded(col)).count();
+			if pairs == 0 {
+				return Outcome::Diverged;
+			}
+			let mu = self.complementarity(&self.x, &self.w, &self.z, &self.v) / pairs as f64;
+			let theta: Vec<f64> = (0 .. cols).map(|col| {
+				if self.fixed[col] {
+					0.
+				} else if self.bounded(col) {
+					1. / (self.z[col] / self.x[col] + self.v[col] / self.w[col])
+				} else {
+					self.x[col] / self.z[col]
+				}
+			}).collect();
+			let normal = Normal::new(&self.columns, &theta, self.y.len());
+
+			// The affine scaling step aims straight for complementarity,
+			// and how far it gets sets how much the corrector centers
+			let rxz: Vec<f64> = (0 .. cols).map(|col| -self.x[col] * self.z[col]).collect();
+			let rwv: Vec<f64> = (0 .. cols).map(|col| -self.w[col] * self.v[col]).collect();
+			let affine = self.direction(&normal, &theta, &rb, &ru, &rc, &rxz, &rwv);
+			let (primal, dual) = self.max_steps(&affine);
+			let (primal, dual) = (primal.min(1.), dual.min(1.));
+			let moved = |values: &Vec<f64>, steps: &Vec<f64>, length: f64| -> Vec<f64> {
+				values.iter().zip(steps.iter()).map(|(value, step)| value + length * step).collect()
+			};
+			let mu_affine = self.complementarity(&moved(&self.x, &affine.x, primal), &moved(&self.w, &affine.w, primal),
+				&moved(&self.z, &affine.z, dual), &moved(&self.v, &affine.v, dual)) / pairs as f64;
+			let sigma = (mu_affine / mu).powi(3).min(1.);
+
+			let rxz: Vec<f64> = (0 .. cols)
+				.map(|col| sigma * mu + rxz[col] - affine.x[col] * affine.z[col])
+				.collect();
+			let rwv: Vec<f64> = (0 .. cols)
+				.map(|col| if self.bounded(col) { sigma * mu + rwv[col] - affine.w[col] * affine.v[col] } else { 0. })
+				.collect();
+			let rxz: Vec<f64> = (0 .. cols).map(|col| if self.fixed[col] { 0. } else { rxz[col] }).collect();
+			let step = self.direction(&normal, &theta, &rb, &ru, &rc, &rxz, &rwv);
+			let (primal, dual) = self.max_steps(&step);
+			let (primal, dual) = ((STEP_FRACTION * primal).min(1.), (STEP_FRACTION * dual).min(1.));
+			if primal < STALL && dual < STALL {
+				return Outcome::Diverged;
+			}
+
+			self.x = moved(&self.x, &step.x, primal);
+			self.w = moved(&self.w, &step.w, primal);
+			self.y = moved(&self.y, &step.y, dual);
+			self.z = moved(&self.z, &step.z, dual);
+			self.v = moved(&self.v, &step.v, dual);
+			self.iterations += 1;
+		}
+	}
+
+	// Residuals of A x = b, x + w = upper and A^T y + z - v = cost
+	fn residuals(&self) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+		let cols = self.columns.len();
+		let A_x = self.multiply(&self.x);
+		let rb = (0 .. self.b.len()).map(|row| self.b[row] - A_x[row]).collect();
+		let ru = (0 .. cols)
+			.map(|col| if self.bounded(col) { self.upper[col] - self.x[col] - self.w[col] } else { 0. })
+			.collect();
+		let y_A = self.transpose_multiply(&self.y);
+		let rc = (0 .. cols)
+			.map(|col| if self.fixed[col] { 0. } else { self.cost[col] - y_A[col] - self.z[col] + self.v[col] })
+			.collect();
+		(rb, ru, rc)
+	}
+
+	fn converged(&self, rb: &Vec<f64>, ru: &Vec<f64>, rc: &Vec<f64>) -> bool {
+		let cols = self.columns.len();
+		let finite_upper: Vec<f64> = (0 .. cols).map(|col| if self.bounded(col) { self.upper[col] } else { 0. }).collect();
+		let primal = (norm(rb) / (1. + norm(&self.b))).max(norm(ru) / (1. + norm(&finite_upper)));
+		let dual = norm(rc) / (1. + norm(&self.cost));
+
+		let primal_objective: f64 = (0 .. cols).map(|col| self.cost[col] * self.x[col]).sum();
+		let dual_objective = (0 .. self.b.len()).map(|row| self.b[row] * self.y[row]).sum::<f64>() -
+			(0 .. cols).map(|col| finite_upper[col] * self.v[col]).sum::<f64>();
+		let gap = (primal_objective - dual_objective).abs() / (1. + primal_objective.abs());
+
+		let tolerance = self.options.tolerance;
+		primal <= tolerance && dual <= tolerance && gap <= tolerance
+	}
+
+	fn diverged(&self) -> bool {
+		[&self.x, &self.w, &self.y, &self.z, &self.v].iter()
+			.any(|values| values.iter().any(|value| !value.is_finite() || value.abs() > DIVERGENCE))
+	}
+
+	fn complementarity(&self, x: &Vec<f64>, w: &Vec<f64>, z: &Vec<f64>, v: &Vec<f64>) -> f64 {
+		(0 .. self.columns.len()).map(|col| {
+			if self.fixed[col] {
+				0.
+			} else if self.bounded(col) {
+				x[col] * z[col] + w[col] * v[col]
+			} else {
+				x[col] * z[col]
+			}
+		}).sum()
+	}
+
+	// Solves the Newton equations with `rxz` and `rwv` as the targets for
+	// the changes in the products x z and w v. Eliminating dz, dw and dv
+	// leaves A Theta A^T dy = rb + A Theta rho, where dx = Theta (A^T dy - rho).
+	fn direction(&self, normal: &Normal, theta: &Vec<f64>, rb: &Vec<f64>, ru: &Vec<f64>, rc: &Vec<f64>,
+			rxz: &Vec<f64>, rwv: &Vec<f64>) -> Direction {
+		let cols = self.columns.len();
+		let rho: Vec<f64> = (0 .. cols).map(|col| {
+			if self.fixed[col] {
+				0.
+			} else if self.bounded(col) {
+				rc[col] - rxz[col] / self.x[col] + (rwv[col] - self.v[col] * ru[col]) / self.w[col]
+			} else {
+				rc[col] - rxz[col] / self.x[col]
+			}
+		}).collect();
+
+		let weighted: Vec<f64> = (0 .. cols).map(|col| theta[col] * rho[col]).collect();
+		let A_weighted = self.multiply(&weighted);
+		let rhs: Vec<f64> = (0 .. rb.len()).map(|row| rb[row] + A_weighted[row]).collect();
+		let dy = normal.solve(&rhs);
+		let dy_A = self.transpose_multiply(&dy);
+
+		let mut direction = Direction {
+			x: vec![0.; cols],
+			w: vec![0.; cols],
+			y: dy,
+			z: vec![0.; cols],
+			v: vec![0.; cols]
+		};
+		for col in 0 .. cols {
+			if self.fixed[col] {
+				continue;
+			}
+			direction.x[col] = theta[col] * (dy_A[col] - rho[col]);
+			direction.z[col] = (rxz[col] - self.z[col] * direction.x[col]) / self.x[col];
+			if self.bounded(col) {
+				direction.w[col] = ru[col] - direction.x[col];
+				direction.v[col] = (rwv[col] - self.v[col] * direction.w[col]) / self.w[col];
+			}
+		}
+
+		direction
+	}
+
+	// Longest primal and dual steps along a direction that keep x, w, z
+	// and v non-negative
+	fn max_steps(&self, direction: &Direction) -> (f64, f64) {
+		let max_step = |values: &Vec<f64>, steps: &Vec<f64>| -> f64 {
+			values.iter().zip(steps.iter())
+				.filter(|&(_, &step)| step < 0.)
+				.fold(INFINITY, |length, (value, step)| length.min(-value / step))
+		};
+		(max_step(&self.x, &direction.x).min(max_step(&self.w, &direction.w)),
+		 max_step(&self.z, &direction.z).min(max_step(&self.v, &direction.v)))
+	}
+
+	fn multiply(&self, x: &Vec<f64>) -> Vec<f64> {
+		let mut product = vec![0.; self.b.len()];
+		for (col, column) in self.columns.iter().enumerate() {
+			if x[col] != 0. {
+				for &(row, value) in column {
+					product[row] += value * x[col];
+				}
+			}
+		}
+		product
+	}
+
+	fn transpose_multiply(&self, y: &Vec<f64>) -> Vec<f64> {
+		self.columns.iter().map(|column| column.iter().map(|&(row, value)| value * y[row]).sum()).collect()
+	}
+
+	// Ranks the columns by how far they are from their bounds relative to
+	// their dual slack, and takes as basic the first that are linearly
+	// independent. The rest sit at the bound they are nearest.
+	fn crossover_basis(&self) -> Basis {
+		let rows = self.b.len();
+		let cols = self.columns.len();
+		let scores: Vec<f64> = (0 .. cols).map(|col| {
+			if self.fixed[col] {
+				0.
+			} else if self.bounded(col) {
+				self.x[col].min(self.w[col]) / (self.z[col] + self.v[col])
+			} else {
+				self.x[col] / self.z[col]
+			}
+		}).collect();
+		let mut order: Vec<usize> = (0 .. cols).collect();
+		order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap_or(::std::cmp::Ordering::Equal));
+
+		let mut statuses: Vec<VarStatus> = (0 .. cols).map(|col| {
+			if self.bounded(col) && self.w[col] < self.x[col] { VarStatus::AtUpper } else { VarStatus::AtLower }
+		}).collect();
+
+		// Each chosen column is reduced against those before it and kept
+		// with the row of its largest remaining entry
+		let mut pivots: Vec<(usize, Vec<f64>)> = vec![];
+		for &col in &order {
+			if pivots.len() == rows {
+				break;
+			}
+			let mut reduced = vec![0.; rows];
+			for &(row, value) in &self.columns[col] {
+				reduced[row] = value;
+			}
+			let scale = reduced.iter().fold(1., |scale: f64, value| scale.max(value.abs()));
+			for &(row, ref pivot) in &pivots {
+				let factor = reduced[row];
+				if factor != 0. {
+					for i in 0 .. rows {
+						reduced[i] -= factor * pivot[i];
+					}
+				}
+			}
+
+			let row = (0 .. rows).fold(0, |best, row| if reduced[row].abs() > reduced[best].abs() { row } else { best });
+			if rows > 0 && reduced[row].abs() > self.options.simplex.tolerances.pivot * scale {
+				let pivot_value = reduced[row];
+				pivots.push((row, reduced.iter().map(|value| value / pivot_value).collect()));
+				statuses[col] = VarStatus::Basic;
+			}
+		}
+
+		Basis {
+			statuses: statuses
+		}
+	}
+
+	// Hands the Lp to the SimplexSolver, from a crossover basis or cold
+	fn simplex(&self, warm_start: Option<Basis>) -> Result<Solution, SolveError> {
+		let mut simplex = SimplexSolver::with_options(self.lp.clone(), self.options.simplex.clone());
+		simplex.warm_start = warm_start;
+		let mut solution = simplex.solve()?;
+		solution.iterations += self.iterations;
+		Ok(solution)
+	}
+
+	fn check_limits(&self) -> Option<Status> {
+		if let Some(ref cancel) = self.options.cancel {
+			if cancel.is_cancelled() {
+				return Some(Status::Cancelled);
+			}
+		}
+
+		if let Some(max_iterations) = self.options.max_iterations {
+			if self.iterations >= max_iterations {
+				return Some(Status::IterationLimit);
+			}
+		}
+
+		match self.deadline {
+			Some(deadline) if Instant::now() >= deadline => Some(Status::TimeLimit),
+			_ => None
+		}
+	}
+
+	// The interior solution, with duals and reduced costs in the Lp's own
+	// signs as with the SimplexSolver
+	fn solution(&self) -> Solution {
+		let cols = self.columns.len();
+		let sign = match self.lp.optimization {
+			Optimization::Max => -1.,
+			Optimization::Min => 1.
+		};
+		let mut values: Vec<f64> = (0 .. cols)
+			.map(|col| (self.lp.lower[col] + self.x[col]).min(self.lp.upper[col]))
+			.collect();
+		let objective = values.iter().zip(self.lp.c.iter()).map(|(x, c)| x * c).sum();
+
+		let duals: Vec<f64> = self.y.iter().map(|y| sign * y).collect();
+		let y_A = self.transpose_multiply(&duals);
+		let mut reduced_costs = Some((0 .. cols).map(|col| {
+			let cost = self.lp.c[col] - y_A[col];
+			if cost.abs() < self.options.tolerance { 0. } else { cost }
+		}).collect());
+		undo_substitutions(self.lp, |_| false, &mut values, &mut reduced_costs, &mut None);
+
+		Solution {
+			lp: self.lp.clone(),
+			values: Some(values),
+			objective: Some(objective),
+			status: Status::Optimal,
+			basis: None,
+			duals: Some(duals),
+			reduced_costs: reduced_costs,
+			sensitivity: None,
+			certificate: None,
+			iterations: self.iterations
+		}
+	}
+
+	fn unsolved(&self, status: Status) -> Solution {
+		Solution {
+			lp: self.lp.clone(),
+			values: None,
+			objective: None,
+			status: status,
+			basis: None,
+			duals: None,
+			reduced_costs: None,
+			sensitivity: None,
+			certificate: None,
+			iterations: self.iterations
+		}
+	}
+}
+
+impl Normal {
+	fn new(columns: &Vec<Vec<(usize, f64)>>, theta: &Vec<f64>, rows: usize) -> Self {
+		let mut factor = vec![vec![0.; rows]; rows];
+		for (col, column) in columns.iter().enumerate() {
+			if theta[col] == 0. {
+				continue;
+			}
+			for &(i, a) in column {
+				for &(k, b) in column {
+					factor[i][k] += theta[col] * a * b;
+				}
+			}
+		}
+
+		let mut dropped = vec![false; rows];
+		for j in 0 .. rows {
+			let diagonal = factor[j][j];
+			let pivot = diagonal - (0 .. j).map(|k| factor[j][k] * factor[j][k]).sum::<f64>();
+			if pivot <= 1e-12 * diagonal || pivot <= 0. {
+				dropped[j] = true;
+				factor[j][j] = 1.;
+				for i in j + 1 .. rows {
+					factor[i][j] = 0.;
+				}
+				continue;
+			}
+
+			let root = pivot.sqrt();
+			factor[j][j] = root;
+			for i in j + 1 .. rows {
+				let value = factor[i][j] - (0 .. j).map(|k| factor[i][k] * factor[j][k]).sum::<f64>();
+				factor[i][j] = value / root;
+			}
+		}
+
+		Normal {
+			factor: factor,
+			dropped: dropped
+		}
+	}
+
+	fn solve(&self, rhs: &Vec<f64>) -> Vec<f64> {
+		let rows = rhs.len();
+		let mut work = vec![0.; rows];
+		for j in 0 .. rows {
+			if !self.dropped[j] {
+				let value = (0 .. j).fold(rhs[j], |value, k| value - self.factor[j][k] * work[k]);
+				work[j] = value / self.factor[j][j];
+			}
+		}
+		for j in (0 .. rows).rev() {
+			if self.dropped[j] {
+				work[j] = 0.;
+			} else {
+				let value = (j + 1 .. rows).fold(work[j], |value, i| value - self.factor[i][j] * work[i]);
+				work[j] = value / self.factor[j][j];
+			}
+		}
+		work
+	}
+}
+
+fn norm(values: &Vec<f64>) -> f64 {
+	values.iter().fold(0., |norm: f64, value| norm.max(value.abs()))
+}
+
+#[cfg(test)]
+mod interior_tests {
+	use super::*;
+	use assert_approx_eq::*;
+	use rulinalg::matrix::Matrix;
+
+	fn lp_from(A: Matrix<f64>, b: Vec<f64>, c: Vec<f64>, optimization: Optimization) -> Lp {
+		let rows = A.rows();
+		let cols = A.cols();
+		Lp {
+			A: A.into(),
+			b: b,
+			c: c,
+			optimization: optimization,
+			vars: (0 .. cols).map(|col| format!("x{}", col)).collect(),
+			constraints: (0 .. rows).map(|row| format!("c{}", row)).collect(),
+			lower: vec![0.; cols],
+			upper: vec![INFINITY; cols],
+			substitutions: vec![],
+			integers: vec![],
+			num_artificial_vars: 0
+		}
+	}
+
+	// The interior solution is only as accurate as the tolerance
+	fn assert_close(value: f64, expected: f64) {
+		assert!((value - expected).abs() < 1e-6, "{} is not close to {}", value, expected);
+	}
+
+	fn without_crossover() -> InteriorPointOptions {
+		let mut options = InteriorPointOptions::default();
+		options.crossover = false;
+		options
+	}
+
+	#[test]
+	fn solve_test() {
+		// x1 + x2 - e1 = 2 and x1 + 3x2 + s2 = 9, with 1 <= x1 <= 3
+		let mut lp = lp_from(matrix![1., 1., -1., 0.;
+									 1., 3., 0., 1.],
+							 vec![2., 9.], vec![2., 3., 0., 0.], Optimization::Max);
+		lp.lower[0] = 1.;
+		lp.upper[0] = 3.;
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+
+		let solution = InteriorPointSolver::with_options(lp.clone(), without_crossover()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_eq!(solution.basis, None);
+		let interior_iterations = solution.iterations;
+		assert_close(solution.objective.unwrap(), expected.objective.unwrap());
+		for (value, expected) in solution.values.unwrap().iter().zip(expected.values.as_ref().unwrap().iter()) {
+			assert_close(*value, *expected);
+		}
+		for (dual, expected) in solution.duals.unwrap().iter().zip(expected.duals.as_ref().unwrap().iter()) {
+			assert_close(*dual, *expected);
+		}
+
+		// Crossover lands on the SimplexSolver's own basis
+		let solution = InteriorPointSolver::new(lp).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_eq!(solution.basis, expected.basis);
+		assert_eq!(solution.basis.unwrap().statuses[0], VarStatus::AtUpper);
+		assert_approx_eq!(solution.objective.unwrap(), expected.objective.unwrap());
+		for (dual, expected) in solution.duals.unwrap().iter().zip(expected.duals.unwrap().iter()) {
+			assert_approx_eq!(dual, expected);
+		}
+		assert!(solution.sensitivity.is_some());
+		assert_eq!(solution.iterations, interior_iterations);
+	}
+
+	#[test]
+	fn degenerate_test() {
+		// Many rows are tight at the optimum of x1 + ... + x6 over rows
+		// that each cap a pair of vars at 1, along with dependent rows
+		let A = matrix![1., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0.;
+						0., 1., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0.;
+						0., 0., 1., 1., 0., 0., 0., 0., 1., 0., 0., 0.;
+						0., 0., 0., 1., 1., 0., 0., 0., 0., 1., 0., 0.;
+						0., 0., 0., 0., 1., 1., 0., 0., 0., 0., 1., 0.;
+						1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0., 1.;
+						1., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0.];
+		let lp = lp_from(A, vec![1., 1., 1., 1., 1., 1., 1.],
+						 vec![1., 1., 1., 1., 1., 1., 0., 0., 0., 0., 0., 0.], Optimization::Max);
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+
+		let solution = InteriorPointSolver::with_options(lp.clone(), without_crossover()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_close(solution.objective.unwrap(), 3.);
+
+		let solution = InteriorPointSolver::new(lp.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), expected.objective.unwrap());
+		let values = solution.values.unwrap();
+		for row in 0 .. 7 {
+			let lhs: f64 = (0 .. 12).map(|col| lp.A[[row, col]] * values[col]).sum();
+			assert_approx_eq!(lhs, 1.);
+		}
+	}
+
+	#[test]
+	fn generated_test() {
+		// Even rows are inequalities with a slack and odd rows are equations,
+		// with b chosen so a point inside the bounds is feasible
+		let (rows, structural) = (30, 60);
+		let cols = structural + rows / 2;
+		let mut seed: u64 = 11;
+		let mut next = || {
+			seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			((seed >> 33) % 19) as f64 - 6.
+		};
+		let point: Vec<f64> = (0 .. cols).map(|col| (col % 5) as f64 * 0.5 + 0.5).collect();
+		let mut data = vec![];
+		for row in 0 .. rows {
+			for _ in 0 .. structural {
+				data.push(next());
+			}
+			for slack in 0 .. rows / 2 {
+				data.push(if row == 2 * slack { 1. } else { 0. });
+			}
+		}
+		let A = Matrix::new(rows, cols, data);
+		let b = (0 .. rows).map(|row| (0 .. cols).map(|col| A[[row, col]] * point[col]).sum()).collect();
+		let c = (0 .. cols).map(|_| next()).collect();
+		let mut lp = lp_from(A, b, c, Optimization::Max);
+		lp.upper = vec![5.; cols];
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+
+		let solution = InteriorPointSolver::with_options(lp.clone(), without_crossover()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_close(solution.objective.unwrap(), expected.objective.unwrap());
+		let interior_iterations = solution.iterations;
+
+		let solution = InteriorPointSolver::new(lp).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), expected.objective.unwrap());
+		// Crossover takes far fewer pivots than a cold start
+		assert!(solution.iterations - interior_iterations < expected.iterations / 2);
+	}
+
+	#[test]
+	fn diverge_test() {
+		// x1 + x2 + s1 = 1 with x1 - e2 = 3
+		let infeasible = lp_from(matrix![1., 1., 1., 0.;
+										 1., 0., 0., -1.],
+								 vec![1., 3.], vec![1., 1., 0., 0.], Optimization::Max);
+		let solution = InteriorPointSolver::new(infeasible.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&infeasible, 1e-9));
+
+		// x1 - x2 + s1 = 1 lets both grow together
+		let unbounded = lp_from(matrix![1., -1., 1.], vec![1.], vec![1., 1., 0.], Optimization::Max);
+		let solution = InteriorPointSolver::new(unbounded.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&unbounded, 1e-9));
+	}
+
+	#[test]
+	fn limits_test() {
+		let lp = lp_from(matrix![1., 1., -1., 0.;
+								 1., 3., 0., 1.],
+						 vec![2., 9.], vec![2., 3., 0., 0.], Optimization::Min);
+		let mut options = InteriorPointOptions::default();
+		options.max_iterations = Some(1);
+		let solution = InteriorPointSolver::with_options(lp.clone(), options).solve().unwrap();
+		assert_eq!(solution.status, Status::IterationLimit);
+		assert_eq!(solution.values, None);
+		assert_eq!(solution.iterations, 1);
+
+		let mut options = InteriorPointOptions::default();
+		let cancel = CancelToken::new();
+		cancel.cancel();
+		options.cancel = Some(cancel);
+		let solution = InteriorPointSolver::with_options(lp, options).solve().unwrap();
+		assert_eq!(solution.status, Status::Cancelled);
+	}
+
+	#[test]
+	fn normal_test() {
+		// The third row is the sum of the first two, so it is dropped
+		let columns = vec![vec![(0, 1.), (2, 1.)], vec![(1, 1.), (2, 1.)], vec![(0, 2.), (1, 1.), (2, 3.)]];
+		let normal = Normal::new(&columns, &vec![1., 2., 1.], 3);
+		assert_eq!(normal.dropped, vec![false, false, true]);
+
+		// A consistent rhs is still solved on the remaining rows
+		let y = normal.solve(&vec![3., 1., 4.]);
+		assert_eq!(y[2], 0.);
+		let product: Vec<f64> = (0 .. 2).map(|row| {
+			columns.iter().enumerate().map(|(col, column)| {
+				let a_row = column.iter().find(|&&(i, _)| i == row).map_or(0., |&(_, a)| a);
+				let a_y: f64 = column.iter().map(|&(i, a)| a * y[i]).sum();
+				a_row * [1., 2., 1.][col] * a_y
+			}).sum()
+		}).collect();
+		assert_approx_eq!(product[0], 3.);
+		assert_approx_eq!(product[1], 1.);
+	}
+}
@@ -0,0 +1,811 @@
+use super::*;
+use lp::{Lp, Optimization, SparseMatrix, Substitution};
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::INFINITY;
+
+// Relative distance within which two values are taken as equal
+const TOLERANCE: f64 = 1e-9;
+
+// The Lp as reductions are made: the entries left in each row, the rows
+// each column is left in, and the rhs and bounds as they stand
+struct Reducer<'a> {
+	lp: &'a Lp,
+	rows: Vec<BTreeMap<usize, f64>>,
+	cols: Vec<BTreeSet<usize>>,
+	b: Vec<f64>,
+	lower: Vec<f64>,
+	upper: Vec<f64>,
+	removed_rows: Vec<bool>,
+	removed_cols: Vec<bool>,
+	reductions: Vec<Reduction>,
+	unbounded: Option<usize>
+}
+
+impl Presolve {
+	/// Reduces `lp`, which is kept to carry solutions back to.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate rulp;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{Presolve, SimplexSolver, Status};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	var y;
+	/// 	maximize obj: 3*x + 2*y;
+	/// 	subject to c1: x + y <= 4;
+	/// 	subject to c2: x + 3*y <= 6;
+	/// 	subject to c3: x <= 3;
+	/// ";
+	/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// // c3 becomes an upper bound on x
+	/// let presolve = Presolve::new(&lp).unwrap();
+	/// assert_eq!(presolve.lp.A.rows(), 2);
+	///
+	/// let solution = presolve.solve::<SimplexSolver>().unwrap();
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// assert_eq!(solution.objective, Some(11.));
+	/// assert_eq!(solution.dual("c3"), Some(1.));
+	/// # }
+	/// ```
+	pub fn new(lp: &Lp) -> Result<Presolve, SolveError> {
+		validate_lp(lp)?;
+
+		let mut reducer = Reducer::new(lp);
+		let result = reducer.reduce();
+		let rows: Vec<usize> = (0 .. lp.A.rows()).filter(|&row| !reducer.removed_rows[row]).collect();
+		let cols: Vec<usize> = (0 .. lp.A.cols()).filter(|&col| !reducer.removed_cols[col]).collect();
+
+		// Every column left is in a row and every row left has a column,
+		// so either both are left or neither
+		let status = match result {
+			Err(status) => Some(status),
+			Ok(()) if cols.is_empty() && reducer.unbounded.is_some() => Some(Status::Unbounded),
+			Ok(()) if cols.is_empty() => Some(Status::Optimal),
+			Ok(()) => None
+		};
+
+		Ok(Presolve {
+			original: lp.clone(),
+			lp: reducer.reduced_lp(&rows, &cols),
+			status: status,
+			rows: rows,
+			cols: cols,
+			reductions: reducer.reductions,
+			unbounded: reducer.unbounded
+		})
+	}
+
+	/// Solves the reduced Lp with a solver of type `S` and default options,
+	/// unless presolve already decided the status, and carries the result
+	/// back to the original Lp.
+	///
+	/// A Farkas ray of the reduced Lp says nothing about the rows presolve
+	/// removed, so an infeasible Lp is solved again without presolve for
+	/// its certificate.
+	pub fn solve<S: SolverBase>(&self) -> Result<Solution, SolveError> {
+		let solution = match self.status {
+			None => S::new(self.lp.clone()).solve()?,
+			Some(_) => self.empty_solution()
+		};
+		let mut result = self.postsolve(&solution);
+		if result.status == Status::Infeasible {
+			let original = S::new(self.original.clone()).solve()?;
+			if original.status == Status::Infeasible {
+				result.certificate = original.certificate;
+			}
+		}
+		Ok(result)
+	}
+
+	/// Carries a Solution of the reduced `lp` back to the original Lp.
+	///
+	/// Values are given for every original column, and for an optimal
+	/// Solution so are duals for every original constraint, reduced costs
+	/// and a basis. No sensitivity report is carried back, and only an
+	/// unboundedness certificate is; `solve` finds the Farkas ray of an
+	/// infeasible Lp.
+	pub fn postsolve(&self, solution: &Solution) -> Solution {
+		let mut result = Solution {
+			lp: self.original.clone(),
+			values: None,
+			objective: None,
+			status: solution.status.clone(),
+			basis: None,
+			duals: None,
+			reduced_costs: None,
+			sensitivity: None,
+			certificate: None,
+			iterations: solution.iterations
+		};
+		if self.status == Some(Status::Infeasible) {
+			result.status = Status::Infeasible;
+			return result;
+		}
+
+		if let Some(Certificate::Unbounded { ref point, ref ray, entering }) = solution.certificate {
+			result.certificate = Some(Certificate::Unbounded {
+				point: self.undo_primal(point, false),
+				ray: self.undo_primal(ray, true),
+				entering: self.cols[entering]
+			});
+		}
+		let mut values = match solution.values {
+			Some(ref values) => self.undo_primal(&self.redo_substitutions(values), false),
+			None => return result
+		};
+
+		if let Some(col) = self.unbounded {
+			if solution.status == Status::Optimal {
+				// The Lp is feasible, and nothing stops the column in no row
+				let ray = self.undo_primal(&vec![0.; self.cols.len()], true);
+				result.status = Status::Unbounded;
+				result.certificate = Some(Certificate::Unbounded { point: values, ray: ray, entering: col });
+				return result;
+			}
+		}
+
+		let mut statuses = None;
+		let mut reduced_costs = None;
+		if let (&Some(ref duals), &Some(ref basis)) = (&solution.duals, &solution.basis) {
+			let mut y = vec![0.; self.original.A.rows()];
+			for (i, &row) in self.rows.iter().enumerate() {
+				y[row] = duals[i];
+			}
+			let mut basis_statuses = vec![VarStatus::AtLower; self.original.A.cols()];
+			for (i, &col) in self.cols.iter().enumerate() {
+				basis_statuses[col] = basis.statuses[i];
+			}
+			self.undo_duals(&values, &mut y, &mut basis_statuses);
+
+			reduced_costs = Some((0 .. self.original.A.cols()).map(|col| {
+				let cost = self.reduced_cost(col, &y);
+				if cost.abs() < TOLERANCE { 0. } else { cost }
+			}).collect());
+			statuses = Some(basis_statuses);
+			result.duals = Some(y);
+		}
+
+		result.objective = Some(values.iter().zip(self.original.c.iter()).map(|(x, c)| x * c).sum());
+		{
+			let is_basic = |col: usize| statuses.as_ref().map_or(false, |statuses: &Vec<VarStatus>| statuses[col] == VarStatus::Basic);
+			undo_substitutions(&self.original, is_basic, &mut values, &mut reduced_costs, &mut None);
+		}
+		result.values = Some(values);
+		result.reduced_costs = reduced_costs;
+		result.basis = statuses.map(|statuses| Basis { statuses: statuses });
+		result
+	}
+
+	// The optimal Solution of a reduced Lp with no rows or columns
+	fn empty_solution(&self) -> Solution {
+		Solution {
+			lp: self.lp.clone(),
+			values: Some(vec![]),
+			objective: Some(0.),
+			status: Status::Optimal,
+			basis: Some(Basis { statuses: vec![] }),
+			duals: Some(vec![]),
+			reduced_costs: Some(vec![]),
+			sensitivity: None,
+			certificate: None,
+			iterations: 0
+		}
+	}
+
+	// Values of the columns of the reduced Lp, from the values the solver
+	// reports for the vars it substituted
+	fn redo_substitutions(&self, values: &Vec<f64>) -> Vec<f64> {
+		let mut values = values.clone();
+		for substitution in &self.lp.substitutions {
+			match *substitution {
+				Substitution::Negated { col } => values[col] = -values[col],
+				Substitution::Split { positive, negative } => values[positive] += values[negative]
+			}
+		}
+		values
+	}
+
+	// Values of the original columns from those of the reduced columns. A
+	// homogeneous undo, for a ray, takes every rhs and fixed value as zero,
+	// apart from a step of one along the unbounded column in no row.
+	fn undo_primal(&self, values: &Vec<f64>, homogeneous: bool) -> Vec<f64> {
+		let constant = |value: f64| if homogeneous { 0. } else { value };
+		let mut x = vec![0.; self.original.A.cols()];
+		for (i, &col) in self.cols.iter().enumerate() {
+			x[col] = values[i];
+		}
+
+		for reduction in self.reductions.iter().rev() {
+			match *reduction {
+				Reduction::EmptyRow { .. } => {},
+				Reduction::FixedColumn { col, .. } if homogeneous && self.unbounded == Some(col) => {
+					x[col] = 1.;
+				},
+				Reduction::FixedColumn { col, value } | Reduction::SingletonRow { col, value, .. } => {
+					x[col] = constant(value);
+				},
+				Reduction::BoundRow { col, coefficient, slack, slack_coefficient, rhs, .. } => {
+					x[slack] = (constant(rhs) - coefficient * x[col]) / slack_coefficient;
+				},
+				Reduction::DuplicateRow { ref entries, rhs, slack: Some((slack, slack_coefficient)), .. } => {
+					let body: f64 = entries.iter().map(|&(col, value)| value * x[col]).sum();
+					x[slack] = (constant(rhs) - body) / slack_coefficient;
+				},
+				Reduction::DuplicateRow { slack: None, .. } => {}
+			}
+		}
+		x
+	}
+
+	// Duals of the removed rows and statuses of the removed columns. A row
+	// that fixed or bounded a column at the solution takes over the
+	// column's reduced cost, and the column joins the basis.
+	fn undo_duals(&self, x: &Vec<f64>, y: &mut Vec<f64>, statuses: &mut Vec<VarStatus>) {
+		for reduction in self.reductions.iter().rev() {
+			match *reduction {
+				Reduction::EmptyRow { .. } => {},
+				Reduction::FixedColumn { col, .. } => {
+					statuses[col] = self.bound_status(col, x[col]);
+				},
+				Reduction::SingletonRow { row, col, coefficient, .. } => {
+					y[row] = self.reduced_cost(col, y) / coefficient;
+					statuses[col] = VarStatus::Basic;
+				},
+				Reduction::BoundRow { row, col, coefficient, slack, before, after, .. } => {
+					let cost = self.reduced_cost(col, y);
+					if cost.abs() > TOLERANCE && at_narrowed_bound(x[col], before, after) {
+						y[row] = cost / coefficient;
+						statuses[col] = VarStatus::Basic;
+						statuses[slack] = self.bound_status(slack, x[slack]);
+					} else {
+						statuses[slack] = VarStatus::Basic;
+					}
+				},
+				Reduction::DuplicateRow { row, kept, ratio, slack, kept_slack, before, after, .. } => {
+					match kept_slack {
+						Some((kept_slack, _)) if y[kept].abs() > TOLERANCE && at_narrowed_bound(x[kept_slack], before, after) => {
+							y[row] = y[kept] / ratio;
+							y[kept] = 0.;
+							statuses[kept_slack] = VarStatus::Basic;
+							if let Some((slack, _)) = slack {
+								statuses[slack] = self.bound_status(slack, x[slack]);
+							}
+						},
+						_ => if let Some((slack, _)) = slack {
+							statuses[slack] = VarStatus::Basic;
+						}
+					}
+				}
+			}
+		}
+	}
+
+	fn reduced_cost(&self, col: usize, y: &Vec<f64>) -> f64 {
+		let mut cost = self.original.c[col];
+		for (row, value) in self.original.A.column(col) {
+			cost -= y[row] * value;
+		}
+		cost
+	}
+
+	fn bound_status(&self, col: usize, value: f64) -> VarStatus {
+		if !close(value, self.original.lower[col]) && close(value, self.original.upper[col]) {
+			VarStatus::AtUpper
+		} else {
+			VarStatus::AtLower
+		}
+	}
+}
+
+impl<'a> Reducer<'a> {
+	fn new(lp: &'a Lp) -> Self {
+		let mut rows = vec![BTreeMap::new(); lp.A.rows()];
+		let mut cols = vec![BTreeSet::new(); lp.A.cols()];
+		for (row, col, value) in lp.A.triplets() {
+			rows[row].insert(col, value);
+			cols[col].insert(row);
+		}
+
+		Reducer {
+			lp: lp,
+			rows: rows,
+			cols: cols,
+			b: lp.b.clone(),
+			lower: lp.lower.clone(),
+			upper: lp.upper.clone(),
+			removed_rows: vec![false; lp.A.rows()],
+			removed_cols: vec![false; lp.A.cols()],
+			reductions: vec![],
+			unbounded: None
+		}
+	}
+
+	// Makes reductions until none is left, or the Lp is found infeasible
+	fn reduce(&mut self) -> Result<(), Status> {
+		loop {
+			let mut changed = false;
+			for row in 0 .. self.rows.len() {
+				if self.removed_rows[row] {
+					continue;
+				}
+				match self.rows[row].len() {
+					0 => self.empty_row(row)?,
+					1 => self.singleton_row(row)?,
+					_ => continue
+				}
+				changed = true;
+			}
+
+			for col in 0 .. self.cols.len() {
+				if self.removed_cols[col] {
+					continue;
+				}
+				if self.cols[col].is_empty() {
+					self.empty_column(col);
+				} else if close(self.lower[col], self.upper[col]) {
+					let value = self.lower[col];
+					self.remove_column(col, value);
+					self.reductions.push(Reduction::FixedColumn { col: col, value: value });
+				} else {
+					continue;
+				}
+				changed = true;
+			}
+
+			for row in 0 .. self.rows.len() {
+				if self.removed_rows[row] || self.rows[row].len() != 2 {
+					continue;
+				}
+				if let Some(slack) = self.slack(row) {
+					self.bound_row(row, slack)?;
+					changed = true;
+				}
+			}
+
+			if !(self.duplicate_rows()? || changed) {
+				return Ok(());
+			}
+		}
+	}
+
+	fn empty_row(&mut self, row: usize) -> Result<(), Status> {
+		if !close(self.b[row], 0.) {
+			return Err(Status::Infeasible);
+		}
+		self.remove_row(row);
+		self.reductions.push(Reduction::EmptyRow { row: row });
+		Ok(())
+	}
+
+	fn singleton_row(&mut self, row: usize) -> Result<(), Status> {
+		let (col, coefficient) = self.rows[row].iter().map(|(&col, &value)| (col, value)).next().unwrap();
+		let value = self.b[row] / coefficient;
+		if !self.within_bounds(col, value) {
+			return Err(Status::Infeasible);
+		}
+		let value = value.max(self.lower[col]).min(self.upper[col]);
+
+		self.remove_row(row);
+		self.remove_column(col, value);
+		self.reductions.push(Reduction::SingletonRow { row: row, col: col, coefficient: coefficient, value: value });
+		Ok(())
+	}
+
+	// Sets a column in no row to the bound its cost favours, noting the
+	// Lp unbounded if it is feasible and that bound is infinite
+	fn empty_column(&mut self, col: usize) {
+		let cost = self.lp.c[col];
+		let increasing = match self.lp.optimization {
+			Optimization::Max => cost > 0.,
+			Optimization::Min => cost < 0.
+		};
+		let value = if increasing && self.upper[col] < INFINITY {
+			self.upper[col]
+		} else {
+			if increasing && self.unbounded.is_none() {
+				self.unbounded = Some(col);
+			}
+			self.lower[col]
+		};
+
+		self.removed_cols[col] = true;
+		self.reductions.push(Reduction::FixedColumn { col: col, value: value });
+	}
+
+	// Replaces `coefficient * col + slack_coefficient * slack = rhs` by the
+	// bounds on col that the slack's bounds give
+	fn bound_row(&mut self, row: usize, slack: usize) -> Result<(), Status> {
+		let (col, coefficient) = self.rows[row].iter()
+			.map(|(&col, &value)| (col, value))
+			.find(|&(col, _)| col != slack)
+			.unwrap();
+		let slack_coefficient = self.rows[row][&slack];
+		let rhs = self.b[row];
+
+		let ends = [
+			(rhs - slack_coefficient * self.lower[slack]) / coefficient,
+			(rhs - slack_coefficient * self.upper[slack]) / coefficient
+		];
+		let before = (self.lower[col], self.upper[col]);
+		let after = self.narrow(col, ends)?;
+
+		self.remove_row(row);
+		self.removed_cols[slack] = true;
+		self.reductions.push(Reduction::BoundRow {
+			row: row,
+			col: col,
+			coefficient: coefficient,
+			slack: slack,
+			slack_coefficient: slack_coefficient,
+			rhs: rhs,
+			before: before,
+			after: after
+		});
+		Ok(())
+	}
+
+	// Removes every row whose entries other than its slack are a multiple
+	// of those of another row, keeping a row without a slack where there
+	// is one
+	fn duplicate_rows(&mut self) -> Result<bool, Status> {
+		let mut groups: BTreeMap<Vec<usize>, Vec<usize>> = BTreeMap::new();
+		for row in 0 .. self.rows.len() {
+			if self.removed_rows[row] || self.rows[row].len() < 2 {
+				continue;
+			}
+			let pattern = self.body(row).iter().map(|&(col, _)| col).collect();
+			groups.entry(pattern).or_insert_with(Vec::new).push(row);
+		}
+
+		let mut changed = false;
+		for group in groups.values() {
+			let kept = *group.iter().find(|&&row| self.slack(row).is_none()).unwrap_or(&group[0]);
+			for &row in group {
+				if row == kept {
+					continue;
+				}
+				if let Some(ratio) = self.ratio(row, kept) {
+					self.duplicate_row(row, kept, ratio)?;
+					changed = true;
+				}
+			}
+		}
+		Ok(changed)
+	}
+
+	fn duplicate_row(&mut self, row: usize, kept: usize, ratio: f64) -> Result<(), Status> {
+		let slack = self.slack(row).map(|col| (col, self.rows[row][&col]));
+		let kept_slack = self.slack(kept).map(|col| (col, self.rows[kept][&col]));
+		let rhs = self.b[row];
+		let mut before = (0., 0.);
+		let mut after = (0., 0.);
+
+		match (slack, kept_slack) {
+			(None, None) => if !close(rhs, ratio * self.b[kept]) {
+				return Err(Status::Infeasible);
+			},
+			(Some((slack, slack_coefficient)), None) => {
+				let value = (rhs - ratio * self.b[kept]) / slack_coefficient;
+				if !self.within_bounds(slack, value) {
+					return Err(Status::Infeasible);
+				}
+			},
+			(Some((slack, slack_coefficient)), Some((kept_slack, kept_coefficient))) => {
+				// The kept row's entries sum to b - kept_coefficient * kept_slack,
+				// which must be a value the removed row allows them
+				let ends = [
+					(rhs - slack_coefficient * self.lower[slack]) / ratio,
+					(rhs - slack_coefficient * self.upper[slack]) / ratio
+				];
+				let ends = [
+					(self.b[kept] - ends[0]) / kept_coefficient,
+					(self.b[kept] - ends[1]) / kept_coefficient
+				];
+				before = (self.lower[kept_slack], self.upper[kept_slack]);
+				after = self.narrow(kept_slack, ends)?;
+			},
+			(None, Some(_)) => unreachable!("a row with a slack is kept over one without")
+		}
+
+		let entries = self.body(row);
+		self.remove_row(row);
+		if let Some((slack, _)) = slack {
+			self.removed_cols[slack] = true;
+		}
+		self.reductions.push(Reduction::DuplicateRow {
+			row: row,
+			kept: kept,
+			ratio: ratio,
+			entries: entries,
+			rhs: rhs,
+			slack: slack,
+			kept_slack: kept_slack,
+			before: before,
+			after: after
+		});
+		Ok(())
+	}
+
+	// The column of a row that is in no other row and has no cost, which
+	// can take up any difference between the rest of the row and its rhs
+	fn slack(&self, row: usize) -> Option<usize> {
+		self.rows[row].keys()
+			.rev()
+			.find(|&&col| self.cols[col].len() == 1 && self.lp.c[col] == 0. && !self.lp.integers.contains(&col))
+			.cloned()
+	}
+
+	// The entries of a row other than its slack
+	fn body(&self, row: usize) -> Vec<(usize, f64)> {
+		let slack = self.slack(row);
+		self.rows[row].iter()
+			.filter(|&(&col, _)| Some(col) != slack)
+			.map(|(&col, &value)| (col, value))
+			.collect()
+	}
+
+	// The multiple of the body of `kept` that the body of `row` is, if any
+	fn ratio(&self, row: usize, kept: usize) -> Option<f64> {
+		let (body, kept_body) = (self.body(row), self.body(kept));
+		let ratio = body[0].1 / kept_body[0].1;
+		if body.iter().zip(kept_body.iter()).all(|(&(_, value), &(_, kept_value))| close(value, ratio * kept_value)) {
+			Some(ratio)
+		} else {
+			None
+		}
+	}
+
+	// Narrows the bounds of a column to the interval between two ends
+	fn narrow(&mut self, col: usize, ends: [f64; 2]) -> Result<(f64, f64), Status> {
+		let lower = self.lower[col].max(ends[0].min(ends[1]));
+		let upper = self.upper[col].min(ends[0].max(ends[1]));
+		if lower > upper && !close(lower, upper) {
+			return Err(Status::Infeasible);
+		}
+		let bounds = (lower.min(upper), upper);
+		self.lower[col] = bounds.0;
+		self.upper[col] = bounds.1;
+		Ok(bounds)
+	}
+
+	fn within_bounds(&self, col: usize, value: f64) -> bool {
+		(value >= self.lower[col] || close(value, self.lower[col])) &&
+			(value <= self.upper[col] || close(value, self.upper[col]))
+	}
+
+	fn remove_row(&mut self, row: usize) {
+		for &col in self.rows[row].keys() {
+			self.cols[col].remove(&row);
+		}
+		self.rows[row].clear();
+		self.removed_rows[row] = true;
+	}
+
+	// Removes a column fixed at `value`, moving its entries into the rhs
+	fn remove_column(&mut self, col: usize, value: f64) {
+		for &row in &self.cols[col] {
+			let coefficient = self.rows[row].remove(&col).unwrap();
+			self.b[row] -= coefficient * value;
+		}
+		self.cols[col].clear();
+		self.removed_cols[col] = true;
+	}
+
+	fn reduced_lp(&self, rows: &Vec<usize>, cols: &Vec<usize>) -> Lp {
+		let lp = self.lp;
+		let mut index = vec![None; lp.A.cols()];
+		for (i, &col) in cols.iter().enumerate() {
+			index[col] = Some(i);
+		}
+
+		let mut triplets = vec![];
+		for (i, &row) in rows.iter().enumerate() {
+			for (&col, &value) in &self.rows[row] {
+				triplets.push((i, index[col].unwrap(), value));
+			}
+		}
+		// A substitution is only kept if all of its columns are
+		let substitutions = lp.substitutions.iter().filter_map(|substitution| match *substitution {
+			Substitution::Negated { col } => index[col].map(|col| Substitution::Negated { col: col }),
+			Substitution::Split { positive, negative } => match (index[positive], index[negative]) {
+				(Some(positive), Some(negative)) => Some(Substitution::Split { positive: positive, negative: negative }),
+				_ => None
+			}
+		}).collect();
+		let first_artificial = lp.A.cols() - lp.num_artificial_vars;
+
+		Lp {
+			A: SparseMatrix::from_triplets(rows.len(), cols.len(), triplets),
+			b: rows.iter().map(|&row| self.b[row]).collect(),
+			c: cols.iter().map(|&col| lp.c[col]).collect(),
+			optimization: lp.optimization.clone(),
+			vars: cols.iter().map(|&col| lp.vars[col].clone()).collect(),
+			constraints: rows.iter().map(|&row| lp.constraints[row].clone()).collect(),
+			lower: cols.iter().map(|&col| self.lower[col]).collect(),
+			upper: cols.iter().map(|&col| self.upper[col]).collect(),
+			substitutions: substitutions,
+			integers: lp.integers.iter().filter_map(|&col| index[col]).collect(),
+			num_artificial_vars: cols.iter().filter(|&&col| col >= first_artificial).count()
+		}
+	}
+}
+
+fn close(a: f64, b: f64) -> bool {
+	a == b || (a - b).abs() <= TOLERANCE * (1. + a.abs().max(b.abs())) && a.is_finite() && b.is_finite()
+}
+
+// Whether a value sits at a bound that a reduction narrowed
+fn at_narrowed_bound(value: f64, before: (f64, f64), after: (f64, f64)) -> bool {
+	(after.0 > before.0 && close(value, after.0)) || (after.1 < before.1 && close(value, after.1))
+}
+
+#[cfg(test)]
+mod presolve_tests {
+	use super::*;
+	use builder::{Builder, BuilderBase, Constraint, Objective, Relation, Variable};
+	use parser::{Parser, ParserBase};
+
+	fn assert_close(actual: &Vec<f64>, expected: &Vec<f64>) {
+		assert_eq!(actual.len(), expected.len());
+		for (a, e) in actual.iter().zip(expected.iter()) {
+			assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+		}
+	}
+
+	fn terms(terms: &[(&str, f64)]) -> Vec<Variable> {
+		terms.iter().map(|&(name, coefficient)| Variable { name: name.to_string(), coefficient: coefficient }).collect()
+	}
+
+	fn constraint(name: &str, variables: &[(&str, f64)], relation: Relation, constant: f64) -> Constraint {
+		Constraint { name: name.to_string(), variables: terms(variables), constant: constant, relation: relation }
+	}
+
+	// max 3x + 2y + z + w + v with z fixed by a row, w by its bounds, v in
+	// no row, c2 twice the rest of c1, c3 and c5 bounds and c6 empty
+	fn reducible_lp() -> Lp {
+		let mut builder = Builder::new();
+		for name in &["x", "y", "z", "w", "v"] {
+			builder.add_variable(Variable { name: name.to_string(), coefficient: 0. }).unwrap();
+		}
+		builder.set_bounds("w", 2., 2.).unwrap();
+		builder.set_bounds("v", 0., 3.).unwrap();
+		builder.add_constraint(constraint("c1", &[("x", 1.), ("y", 1.), ("z", 1.)], Relation::LessThanOrEqual, 9.)).unwrap();
+		builder.add_constraint(constraint("c2", &[("x", 2.), ("y", 2.)], Relation::LessThanOrEqual, 8.)).unwrap();
+		builder.add_constraint(constraint("c3", &[("x", 1.)], Relation::LessThanOrEqual, 3.)).unwrap();
+		builder.add_constraint(constraint("c4", &[("z", 1.)], Relation::Equal, 4.)).unwrap();
+		builder.add_constraint(constraint("c5", &[("y", 1.), ("w", 1.)], Relation::GreaterThanOrEqual, 1.)).unwrap();
+		builder.add_constraint(constraint("c6", &[("x", 0.)], Relation::Equal, 0.)).unwrap();
+		builder.add_objective(Objective {
+			name: "obj".to_string(),
+			variables: terms(&[("x", 3.), ("y", 2.), ("z", 1.), ("w", 1.), ("v", 1.)]),
+			maximize: true
+		}).unwrap();
+		builder.build_lp().unwrap()
+	}
+
+	#[test]
+	fn reduce_test() {
+		let lp = reducible_lp();
+		let presolve = Presolve::new(&lp).unwrap();
+		assert_eq!(presolve.status, None);
+
+		// Only c1 is left, on x, y and its slack, which c2 bounds below
+		assert_eq!(presolve.lp.constraints, vec!["c1".to_string()]);
+		assert_eq!(presolve.lp.vars, vec!["x".to_string(), "y".to_string(), "slack_0".to_string()]);
+		assert_eq!(presolve.lp.b, vec![5.]);
+		assert_eq!(presolve.lp.lower, vec![0., 0., 1.]);
+		assert_eq!(presolve.lp.upper, vec![3., INFINITY, INFINITY]);
+		assert_eq!(presolve.lp.num_artificial_vars, 1);
+	}
+
+	#[test]
+	fn postsolve_test() {
+		let lp = reducible_lp();
+		let solution = Presolve::new(&lp).unwrap().solve::<SimplexSolver>().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_eq!(solution.objective, Some(20.));
+
+		// Columns x, y, z, w, v, then the slacks of c1 - c3 and the excess of c5
+		let values = solution.values.clone().unwrap();
+		assert_close(&values, &vec![3., 1., 4., 2., 3., 1., 0., 0., 2.]);
+		assert_close(solution.duals.as_ref().unwrap(), &vec![0., 1., 1., 1., 0., 0.]);
+		assert_close(solution.reduced_costs.as_ref().unwrap(), &vec![0., 0., 0., 1., 1., 0., -1., -1., 0.]);
+		assert_eq!(solution.dual("c3"), Some(1.));
+
+		// One basic column for each row but the empty one
+		let basis = solution.basis.clone().unwrap();
+		assert_eq!(basis.basic_vars(), vec![0, 1, 2, 5, 8]);
+		assert_eq!(basis.statuses[4], VarStatus::AtUpper);
+	}
+
+	#[test]
+	fn substitution_test() {
+		let text = "
+			var x free;
+			var y <= 0;
+			var z;
+			minimize cost: x + 2*y + z;
+			subject to floor: x + y >= -5;
+			subject to ceiling: x + -1*y <= 3;
+			subject to fix: z == 2;
+		";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+		let presolve = Presolve::new(&lp).unwrap();
+		assert_eq!(presolve.lp.substitutions.len(), 2);
+
+		let solution = presolve.solve::<SimplexSolver>().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert!((solution.objective.unwrap() - expected.objective.unwrap()).abs() < 1e-9);
+		assert_close(&solution.values.unwrap(), &expected.values.unwrap());
+		assert_close(&solution.duals.unwrap(), &expected.duals.unwrap());
+		assert_close(&solution.reduced_costs.unwrap(), &expected.reduced_costs.unwrap());
+	}
+
+	#[test]
+	fn infeasible_test() {
+		let texts = [
+			// A singleton row outside the bounds
+			"var x;\nmaximize obj: x;\nsubject to c1: x == -1;",
+			// A bound row that empties the bounds
+			"var x <= 0;\nmaximize obj: x;\nsubject to c1: x >= 1;",
+			// Duplicate rows with different constants
+			"var x;\nvar y;\nmaximize obj: x;\nsubject to c1: x + y == 1;\nsubject to c2: 2*x + 2*y == 3;",
+			// Duplicate rows whose ranges do not meet
+			"var x;\nvar y;\nmaximize obj: x;\nsubject to c1: x + y <= 1;\nsubject to c2: -2*x + -2*y <= -3;"
+		];
+		for text in texts.iter() {
+			let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+			let presolve = Presolve::new(&lp).unwrap();
+			assert_eq!(presolve.status, Some(Status::Infeasible), "{}", text);
+			let solution = presolve.solve::<SimplexSolver>().unwrap();
+			assert_eq!(solution.status, Status::Infeasible);
+			assert_eq!(solution.values, None);
+			assert!(solution.certificate.unwrap().verify(&lp, 1e-9), "{}", text);
+		}
+
+		// Presolve leaves the Lp, and the solver finds it infeasible
+		let text = "var x;\nvar y;\nmaximize obj: x;\nsubject to c1: x + y <= 1;\nsubject to c2: x + 2*y >= 3;\nsubject to c3: x >= 1;";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let presolve = Presolve::new(&lp).unwrap();
+		assert_eq!(presolve.status, None);
+		let solution = presolve.solve::<SimplexSolver>().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+	}
+
+	#[test]
+	fn unbounded_test() {
+		// Nothing is left, so presolve decides
+		let text = "var x;\nvar y;\nmaximize obj: x + y;\nsubject to c1: y <= 1;";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let presolve = Presolve::new(&lp).unwrap();
+		assert_eq!(presolve.status, Some(Status::Unbounded));
+		let solution = presolve.solve::<SimplexSolver>().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+
+		// x is unbounded once the rest is found feasible
+		let text = "var x;\nvar y;\nvar z;\nmaximize obj: x + y;\nsubject to c1: y + z <= 1;\nsubject to c2: y + -1*z <= 0;";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let presolve = Presolve::new(&lp).unwrap();
+		assert_eq!(presolve.status, None);
+		let solution = presolve.solve::<SimplexSolver>().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+
+		// The reduced Lp is unbounded itself, and its certificate is carried back
+		let text = "var x;\nvar y;\nmaximize obj: x + y;\nsubject to c1: -1*x + y <= 1;\nsubject to c2: y <= 2;";
+		let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+		let presolve = Presolve::new(&lp).unwrap();
+		assert_eq!(presolve.lp.A.rows(), 1);
+		let solution = presolve.solve::<SimplexSolver>().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+	}
+}
@@ -0,0 +1,600 @@
+use super::*;
+use super::lu::BasisFactor;
+use super::scaling::Scale;
+use lp::{Lp, Optimization};
+use rulinalg::matrix::BaseMatrix;
+use std::f64::INFINITY;
+use std::time::Instant;
+
+// Column replacements applied to a factorization before it is rebuilt
+const REFACTOR_INTERVAL: usize = 50;
+
+// The state of one solve. Columns past those of the Lp are artificial
+// vars, one per row, which are fixed at zero once Phase I is over.
+struct Revised<'a> {
+	lp: &'a Lp,
+	options: &'a SimplexOptions,
+	columns: Vec<Vec<(usize, f64)>>,
+	// Costs of the current phase in Min form
+	cost: Vec<f64>,
+	lower: Vec<f64>,
+	upper: Vec<f64>,
+	x: Vec<f64>,
+	status: Vec<VarStatus>,
+	// Basic column at each position of the basis
+	head: Vec<usize>,
+	factor: BasisFactor,
+	pricing: PricingRule,
+	stalled: usize,
+	iterations: usize,
+	deadline: Option<Instant>,
+	certificate: Option<Certificate>
+}
+
+impl SolverBase for RevisedSimplexSolver {
+	/// Constructor for RevisedSimplexSolver struct with default options.
+	fn new(lp: Lp) -> Self {
+		RevisedSimplexSolver::with_options(lp, SimplexOptions::default())
+	}
+
+	/// Solves the Lp by the revised simplex method.
+	///
+	/// Only the basis is factorized; each pivot solves with the factors
+	/// for the duals and the entering column, and reads the columns of
+	/// `Lp::A` it prices from a sparse copy.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate rulp;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{RevisedSimplexSolver, SolverBase, Status};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	var y;
+	/// 	maximize obj: 3*x + 2*y;
+	/// 	subject to c1: x + y <= 4;
+	/// 	subject to c2: x + 3*y <= 6;
+	/// 	subject to c3: x <= 3;
+	/// ";
+	/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// let solution = RevisedSimplexSolver::new(lp).solve().unwrap();
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// assert_eq!(solution.objective, Some(11.));
+	/// # }
+	/// ```
+	fn solve(&self) -> Result<Solution, SolveError> {
+		validate_lp(&self.lp)?;
+		if let Some(method) = self.options.scaling {
+			let scale = Scale::new(&self.lp, method);
+			let scaled = RevisedSimplexSolver::with_options(scale.scale_lp(&self.lp), SimplexOptions { scaling: None, .. self.options.clone() });
+			return scaled.solve().map(|solution| scale.unscale(solution, &self.lp));
+		}
+
+		let mut revised = Revised::new(&self.lp, &self.options);
+		if let Err(status) = revised.find_bfs() {
+			return Ok(revised.unsolved(status));
+		}
+
+		let status = revised.optimize();
+		match status {
+			Status::Optimal | Status::IterationLimit | Status::TimeLimit | Status::Cancelled => {},
+			_ => return Ok(revised.unsolved(status))
+		}
+
+		Ok(revised.solution(status))
+	}
+}
+
+impl RevisedSimplexSolver {
+	/// Constructor for RevisedSimplexSolver struct with non-default options.
+	pub fn with_options(lp: Lp, options: SimplexOptions) -> Self {
+		RevisedSimplexSolver {
+			lp: lp,
+			options: options
+		}
+	}
+}
+
+impl<'a> Revised<'a> {
+	// Starts every column at its lower bound. Each row is covered by a
+	// column that appears in no other row when that column can absorb the
+	// row's residual, and by an artificial var otherwise.
+	fn new(lp: &'a Lp, options: &'a SimplexOptions) -> Self {
+		let rows = lp.A.rows();
+		let cols = lp.A.cols();
+		let mut columns: Vec<Vec<(usize, f64)>> = (0 .. cols).map(|col| lp.A.column(col).collect()).collect();
+		let mut lower = lp.lower.clone();
+		let mut upper = lp.upper.clone();
+		let mut x = lower.clone();
+		let mut status = vec![VarStatus::AtLower; cols];
+
+		let mut residual = lp.b.clone();
+		for col in 0 .. cols {
+			for &(row, value) in &columns[col] {
+				residual[row] -= value * x[col];
+			}
+		}
+
+		let mut covered: Vec<Option<usize>> = vec![None; rows];
+		for col in 0 .. cols {
+			if columns[col].len() != 1 {
+				continue;
+			}
+			let (row, coeff) = columns[col][0];
+			let shift = residual[row] / coeff;
+			if covered[row].is_none() && shift >= 0. && lower[col] + shift <= upper[col] {
+				covered[row] = Some(col);
+				x[col] += shift;
+				residual[row] = 0.;
+				status[col] = VarStatus::Basic;
+			}
+		}
+
+		let mut head = Vec::with_capacity(rows);
+		for row in 0 .. rows {
+			let artificial = cols + row;
+			let sign = if residual[row] >= 0. { 1. } else { -1. };
+			columns.push(vec![(row, sign)]);
+			lower.push(0.);
+			match covered[row] {
+				Some(col) => {
+					head.push(col);
+					upper.push(0.);
+					x.push(0.);
+					status.push(VarStatus::AtLower);
+				},
+				None => {
+					head.push(artificial);
+					upper.push(INFINITY);
+					x.push(residual[row].abs());
+					status.push(VarStatus::Basic);
+				}
+			}
+		}
+
+		let mut cost = vec![0.; cols];
+		cost.extend(vec![1.; rows]);
+		let basis: Vec<Vec<(usize, f64)>> = head.iter().map(|&col| columns[col].clone()).collect();
+		let factor = BasisFactor::new(&basis, options.tolerances.pivot, options.tolerances.zero)
+			.expect("A basis of columns in distinct rows is nonsingular");
+
+		Revised {
+			lp: lp,
+			options: options,
+			columns: columns,
+			cost: cost,
+			lower: lower,
+			upper: upper,
+			x: x,
+			status: status,
+			head: head,
+			factor: factor,
+			pricing: options.pricing,
+			stalled: 0,
+			iterations: 0,
+			deadline: options.time_limit.map(|limit| Instant::now() + limit),
+			certificate: None
+		}
+	}
+
+	// Phase I drives the artificial vars to zero, then they are fixed
+	// there and the Lp's own objective takes over
+	fn find_bfs(&mut self) -> Result<(), Status> {
+		let cols = self.lp.A.cols();
+		if self.upper[cols ..].iter().any(|&upper| upper > 0.) {
+			let status = self.optimize();
+			if status != Status::Optimal {
+				return Err(status);
+			}
+
+			let infeasibility: f64 = self.x[cols ..].iter().sum();
+			if infeasibility > self.options.tolerances.primal_feasibility {
+				// Phase I duals weigh the rows so no point within the bounds
+				// meets them, which is exactly a Farkas ray up to sign
+				let duals = self.duals();
+				self.certificate = Some(Certificate::Infeasible { farkas: duals.iter().map(|y| -y).collect() });
+				return Err(Status::Infeasible);
+			}
+		}
+
+		for artificial in cols .. self.columns.len() {
+			self.upper[artificial] = 0.;
+			self.cost[artificial] = 0.;
+		}
+		for col in 0 .. cols {
+			self.cost[col] = match self.lp.optimization {
+				Optimization::Max => -self.lp.c[col],
+				Optimization::Min => self.lp.c[col]
+			};
+		}
+		self.pricing = self.options.pricing;
+		self.stalled = 0;
+		Ok(())
+	}
+
+	// Pivots until no nonbasic column improves the current phase's cost
+	fn optimize(&mut self) -> Status {
+		loop {
+			if let Some(status) = self.check_limits() {
+				return status;
+			}
+			if self.factor.num_updates() >= REFACTOR_INTERVAL && !self.refactor() {
+				return Status::NumericalFailure;
+			}
+
+			let duals = self.duals();
+			let (entering, reduced_cost) = match self.price(&duals) {
+				Some(entering) => entering,
+				None => return Status::Optimal
+			};
+			let direction = if reduced_cost < 0. { 1. } else { -1. };
+			let alpha = self.factor.ftran(&self.dense_column(entering));
+			let (step, leaving) = self.ratio_test(entering, direction, &alpha);
+			if step == INFINITY {
+				self.certificate = Some(self.unbounded_certificate(entering, direction, &alpha));
+				return Status::Unbounded;
+			}
+
+			self.x[entering] += direction * step;
+			for position in 0 .. self.head.len() {
+				self.x[self.head[position]] -= direction * step * alpha[position];
+			}
+			match leaving {
+				Some((position, to_upper)) => {
+					let col = self.head[position];
+					self.set_at_bound(col, to_upper);
+					self.head[position] = entering;
+					self.status[entering] = VarStatus::Basic;
+					self.factor.update(position, &alpha);
+				},
+				None => self.set_at_bound(entering, direction > 0.)
+			}
+			self.iterations += 1;
+
+			if step * reduced_cost.abs() <= self.options.tolerances.zero {
+				self.stalled += 1;
+				if self.options.stall_limit.map_or(false, |limit| self.stalled >= limit) {
+					self.pricing = PricingRule::Bland;
+				}
+			} else {
+				self.stalled = 0;
+			}
+			if self.head.iter().any(|&col| !self.x[col].is_finite()) {
+				return Status::NumericalFailure;
+			}
+		}
+	}
+
+	// y = B^-T c_B for the costs of the current phase
+	fn duals(&self) -> Vec<f64> {
+		let basic_costs: Vec<f64> = self.head.iter().map(|&col| self.cost[col]).collect();
+		self.factor.btran(&basic_costs)
+	}
+
+	// Picks a nonbasic column whose reduced cost shows the objective
+	// improves as it moves off its bound, with that reduced cost
+	fn price(&self, duals: &Vec<f64>) -> Option<(usize, f64)> {
+		let tol = self.options.tolerances.optimality;
+		let mut best = None;
+		let mut best_score = 0.;
+		for col in 0 .. self.columns.len() {
+			if self.status[col] == VarStatus::Basic || self.lower[col] == self.upper[col] {
+				continue;
+			}
+
+			let reduced_cost = self.columns[col].iter()
+				.fold(self.cost[col], |cost, &(row, value)| cost - duals[row] * value);
+			let improving = match self.status[col] {
+				VarStatus::AtUpper => reduced_cost > tol,
+				_ => reduced_cost < -tol
+			};
+			if !improving {
+				continue;
+			}
+			if self.pricing == PricingRule::Bland {
+				return Some((col, reduced_cost));
+			}
+			if reduced_cost.abs() > best_score {
+				best = Some((col, reduced_cost));
+				best_score = reduced_cost.abs();
+			}
+		}
+
+		best
+	}
+
+	// Finds how far the entering column can move before it or a basic
+	// column reaches a bound. Returns that step with the position of the
+	// basic column that leaves and whether it leaves at its upper bound,
+	// or no position when the entering column moves to its other bound.
+	fn ratio_test(&self, entering: usize, direction: f64, alpha: &Vec<f64>) -> (f64, Option<(usize, bool)>) {
+		let tolerances = &self.options.tolerances;
+		let mut step = self.upper[entering] - self.lower[entering];
+		let mut leaving: Option<(usize, bool)> = None;
+		let mut leaving_alpha = 0.;
+
+		for position in 0 .. self.head.len() {
+			let col = self.head[position];
+			let delta = direction * alpha[position];
+			let (limit, to_upper) = if delta > tolerances.pivot {
+				((self.x[col] - self.lower[col]).max(0.) / delta, false)
+			} else if delta < -tolerances.pivot && self.upper[col] < INFINITY {
+				((self.upper[col] - self.x[col]).max(0.) / -delta, true)
+			} else {
+				continue;
+			};
+
+			let tied = limit <= step + tolerances.primal_feasibility;
+			let better = limit < step - tolerances.primal_feasibility || (tied && match self.pricing {
+				PricingRule::Bland => leaving.map_or(true, |(other, _)| col < self.head[other]),
+				PricingRule::Dantzig => delta.abs() > leaving_alpha
+			});
+			if better {
+				step = limit;
+				leaving = Some((position, to_upper));
+				leaving_alpha = delta.abs();
+			}
+		}
+
+		(step, leaving)
+	}
+
+	fn set_at_bound(&mut self, col: usize, at_upper: bool) {
+		if at_upper {
+			self.x[col] = self.upper[col];
+			self.status[col] = VarStatus::AtUpper;
+		} else {
+			self.x[col] = self.lower[col];
+			self.status[col] = VarStatus::AtLower;
+		}
+	}
+
+	fn dense_column(&self, col: usize) -> Vec<f64> {
+		let mut dense = vec![0.; self.head.len()];
+		for &(row, value) in &self.columns[col] {
+			dense[row] = value;
+		}
+		dense
+	}
+
+	// Factorizes the basis afresh and recomputes the basic values from the
+	// nonbasic ones, which sheds the error the updates have built up
+	fn refactor(&mut self) -> bool {
+		let basis: Vec<Vec<(usize, f64)>> = self.head.iter().map(|&col| self.columns[col].clone()).collect();
+		self.factor = match BasisFactor::new(&basis, self.options.tolerances.pivot, self.options.tolerances.zero) {
+			Some(factor) => factor,
+			None => return false
+		};
+
+		let mut rhs = self.lp.b.clone();
+		for col in 0 .. self.columns.len() {
+			if self.status[col] != VarStatus::Basic {
+				for &(row, value) in &self.columns[col] {
+					rhs[row] -= value * self.x[col];
+				}
+			}
+		}
+		let values = self.factor.ftran(&rhs);
+		for (position, &col) in self.head.iter().enumerate() {
+			self.x[col] = values[position];
+		}
+		true
+	}
+
+	// The entering column moves without bound while the basic columns
+	// follow along -alpha
+	fn unbounded_certificate(&self, entering: usize, direction: f64, alpha: &Vec<f64>) -> Certificate {
+		let cols = self.lp.A.cols();
+		let mut ray = vec![0.; cols];
+		ray[entering] = direction;
+		for (position, &col) in self.head.iter().enumerate() {
+			if col < cols {
+				ray[col] = -direction * alpha[position];
+			}
+		}
+
+		Certificate::Unbounded {
+			point: self.x[.. cols].to_vec(),
+			ray: ray,
+			entering: entering
+		}
+	}
+
+	fn check_limits(&self) -> Option<Status> {
+		if let Some(ref cancel) = self.options.cancel {
+			if cancel.is_cancelled() {
+				return Some(Status::Cancelled);
+			}
+		}
+
+		if let Some(max_iterations) = self.options.max_iterations {
+			if self.iterations >= max_iterations {
+				return Some(Status::IterationLimit);
+			}
+		}
+
+		match self.deadline {
+			Some(deadline) if Instant::now() >= deadline => Some(Status::TimeLimit),
+			_ => None
+		}
+	}
+
+	fn solution(&self, status: Status) -> Solution {
+		let cols = self.lp.A.cols();
+		let zero = self.options.tolerances.zero;
+		let mut values = self.x[.. cols].to_vec();
+		let objective = values.iter().zip(self.lp.c.iter()).map(|(x, c)| x * c).sum();
+
+		// Duals carry the Lp's own signs, as with the SimplexSolver
+		let duals = if status == Status::Optimal {
+			let basic_costs: Vec<f64> = self.head.iter().map(|&col| if col < cols { self.lp.c[col] } else { 0. }).collect();
+			Some(self.factor.btran(&basic_costs))
+		} else {
+			None
+		};
+		let mut reduced_costs = duals.as_ref().map(|duals| (0 .. cols).map(|col| {
+			let cost = self.columns[col].iter().fold(self.lp.c[col], |cost, &(row, value)| cost - duals[row] * value);
+			if cost.abs() < zero { 0. } else { cost }
+		}).collect());
+		undo_substitutions(self.lp, |col| self.status[col] == VarStatus::Basic, &mut values, &mut reduced_costs, &mut None);
+
+		Solution {
+			lp: self.lp.clone(),
+			values: Some(values),
+			objective: Some(objective),
+			status: status,
+			basis: Some(Basis { statuses: self.status[.. cols].to_vec() }),
+			duals: duals,
+			reduced_costs: reduced_costs,
+			sensitivity: None,
+			certificate: None,
+			iterations: self.iterations
+		}
+	}
+
+	fn unsolved(&self, status: Status) -> Solution {
+		Solution {
+			lp: self.lp.clone(),
+			values: None,
+			objective: None,
+			status: status,
+			basis: None,
+			duals: None,
+			reduced_costs: None,
+			sensitivity: None,
+			certificate: self.certificate.clone(),
+			iterations: self.iterations
+		}
+	}
+}
+
+#[cfg(test)]
+mod revised_tests {
+	use super::*;
+	use assert_approx_eq::*;
+	use rulinalg::matrix::Matrix;
+
+	fn lp_from(A: Matrix<f64>, b: Vec<f64>, c: Vec<f64>, optimization: Optimization) -> Lp {
+		let rows = A.rows();
+		let cols = A.cols();
+		Lp {
+			A: A.into(),
+			b: b,
+			c: c,
+			optimization: optimization,
+			vars: (0 .. cols).map(|col| format!("x{}", col)).collect(),
+			constraints: (0 .. rows).map(|row| format!("c{}", row)).collect(),
+			lower: vec![0.; cols],
+			upper: vec![INFINITY; cols],
+			substitutions: vec![],
+			integers: vec![],
+			num_artificial_vars: 0
+		}
+	}
+
+	fn assert_same_solution(lp: Lp) {
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+		let solution = RevisedSimplexSolver::new(lp).solve().unwrap();
+		assert_eq!(solution.status, expected.status);
+		assert_approx_eq!(solution.objective.unwrap(), expected.objective.unwrap());
+		for (value, expected) in solution.values.unwrap().iter().zip(expected.values.unwrap().iter()) {
+			assert_approx_eq!(value, expected);
+		}
+		for (dual, expected) in solution.duals.unwrap().iter().zip(expected.duals.unwrap().iter()) {
+			assert_approx_eq!(dual, expected);
+		}
+	}
+
+	#[test]
+	fn solve_test() {
+		// x1 + x2 - e1 = 2 and x1 + 3x2 + s2 = 9 need Phase I for the first row
+		let lp = lp_from(matrix![1., 1., -1., 0.;
+								 1., 3., 0., 1.],
+						 vec![2., 9.], vec![2., 3., 0., 0.], Optimization::Min);
+		assert_same_solution(lp.clone());
+
+		// Bounds 1 <= x1 <= 3 and x2 <= 2 end with x1 at its upper bound
+		let mut bounded = lp;
+		bounded.optimization = Optimization::Max;
+		bounded.lower[0] = 1.;
+		bounded.upper[0] = 3.;
+		bounded.upper[1] = 2.;
+		assert_same_solution(bounded.clone());
+		let solution = RevisedSimplexSolver::new(bounded).solve().unwrap();
+		assert_eq!(solution.basis.unwrap().statuses[0], VarStatus::AtUpper);
+	}
+
+	#[test]
+	fn refactor_test() {
+		// Enough pivots to rebuild the factorization several times. Even
+		// rows are inequalities with a slack, odd rows are equations, and b
+		// is chosen so a point inside the bounds is feasible.
+		let (rows, structural) = (30, 60);
+		let cols = structural + rows / 2;
+		let mut seed: u64 = 7;
+		let mut next = || {
+			seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			((seed >> 33) % 19) as f64 - 6.
+		};
+		let point: Vec<f64> = (0 .. cols).map(|col| (col % 5) as f64 * 0.5 + 0.5).collect();
+		let mut data = vec![];
+		for row in 0 .. rows {
+			for _ in 0 .. structural {
+				data.push(next());
+			}
+			for slack in 0 .. rows / 2 {
+				data.push(if row == 2 * slack { 1. } else { 0. });
+			}
+		}
+		let A = Matrix::new(rows, cols, data);
+		let b = (0 .. rows).map(|row| (0 .. cols).map(|col| A[[row, col]] * point[col]).sum()).collect();
+		let c = (0 .. cols).map(|_| next()).collect();
+		let mut lp = lp_from(A, b, c, Optimization::Max);
+		lp.upper = vec![5.; cols];
+
+		let solution = RevisedSimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert!(solution.iterations > REFACTOR_INTERVAL);
+		assert_same_solution(lp);
+	}
+
+	#[test]
+	fn certificates_test() {
+		// x1 + x2 + s1 = 1 with x1 - e2 = 3
+		let infeasible = lp_from(matrix![1., 1., 1., 0.;
+										 1., 0., 0., -1.],
+								 vec![1., 3.], vec![1., 1., 0., 0.], Optimization::Max);
+		let solution = RevisedSimplexSolver::new(infeasible.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&infeasible, 1e-9));
+
+		// x1 - x2 + s1 = 1 lets both grow together
+		let unbounded = lp_from(matrix![1., -1., 1.], vec![1.], vec![1., 1., 0.], Optimization::Max);
+		let solution = RevisedSimplexSolver::new(unbounded.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&unbounded, 1e-9));
+	}
+
+	#[test]
+	fn limits_test() {
+		let lp = lp_from(matrix![1., 1., -1., 0.;
+								 1., 3., 0., 1.],
+						 vec![2., 9.], vec![2., 3., 0., 0.], Optimization::Max);
+		let mut options = SimplexOptions::default();
+		options.max_iterations = Some(0);
+		let solution = RevisedSimplexSolver::with_options(lp.clone(), options).solve().unwrap();
+		assert_eq!(solution.status, Status::IterationLimit);
+		assert_eq!(solution.values, None);
+
+		// Bland's rule reaches the same optimum
+		let mut options = SimplexOptions::default();
+		options.pricing = PricingRule::Bland;
+		let solution = RevisedSimplexSolver::with_options(lp, options).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 18.);
+	}
+}
@@ -1,23 +1,23 @@
 use super::*;
-use lp::{Lp, Optimization};
+use super::scaling::Scale;
+use lp::{Lp, Optimization, Substitution};
 use rulinalg::matrix::{BaseMatrixMut, BaseMatrix};
-use std::f64::INFINITY;
-use utils::print_matrix;
+use rulinalg::vector::Vector;
+use std::f64::{INFINITY, NEG_INFINITY};
+use std::time::Instant;
 
 impl SolverBase for SimplexSolver {
 	/// Constructor for SolverBase struct.
 	/// 
 	/// Requires an input Lp struct.
 	fn new(lp: Lp) -> Self {
-		SimplexSolver {
-			tableau: SimplexSolver::convert_lp_to_tableau(&lp),
-			lp: lp
-		}	
+		SimplexSolver::with_options(lp, SimplexOptions::default())
 	}
 
 	/// Solves the SimplexSolver.
 	///
-	/// Returns a Solution struct.
+	/// Returns a Solution struct, or a SolveError if the Lp has
+	/// inconsistent dimensions or non-finite entries.
 	///
 	/// # Examples
 	/// ```
@@ -40,69 +40,788 @@ impl SolverBase for SimplexSolver {
 	/// vars.push("x3".to_string());
 	/// vars.push("x4".to_string());
 	/// let lp = Lp {
-	/// 		A: A,
+	/// 		A: A.into(),
 	/// 		b: b,
 	/// 		c: c,
 	/// 		optimization: Optimization::Max,
 	/// 		vars: vars,
+	/// 		constraints: vec!["c1".to_string(), "c2".to_string()],
+	/// 		lower: vec![0.; 4],
+	/// 		upper: vec![INFINITY; 4],
+	/// 		substitutions: vec![],
+	/// 		integers: vec![],
 	///			num_artificial_vars: 0,
 	/// };
 	///
 	/// let simplex = SimplexSolver::new(lp);
 	/// let expected = vec![0., 0., 4., 3.];
-	/// let solution = simplex.solve();
+	/// let solution = simplex.solve().unwrap();
 	/// assert_eq!(solution.status, Status::Optimal);
 	/// assert_eq!(solution.values.unwrap(), expected);
 	/// assert_eq!(solution.objective.unwrap(), 0.);
 	/// # }
 	/// ```
-	fn solve(&self) -> Solution {
-		// println!("Solver called");
-		let mut local = SimplexSolver::new(self.lp.clone());
-		let has_bfs = local.find_bfs();
+	fn solve(&self) -> Result<Solution, SolveError> {
+		self.solve_tableau().map(|(solution, _)| solution)
+	}
+}
 
+impl SimplexSolver {
+	/// Constructor for SimplexSolver struct with non-default pivoting options.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate rulp;
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{SimplexSolver, SimplexOptions, SolverBase, PricingRule, RatioTest, Status};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	var y;
+	/// 	maximize obj: x + y;
+	/// 	subject to c1: x + 2*y <= 4;
+	/// 	subject to c2: 2*x + y <= 4;
+	/// ";
+	/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// let options = SimplexOptions {
+	/// 	pricing: PricingRule::Bland,
+	/// 	ratio_test: RatioTest::Lexicographic,
+	/// 	.. SimplexOptions::default()
+	/// };
+	/// let solution = SimplexSolver::with_options(lp, options).solve().unwrap();
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// # }
+	/// ```
+	pub fn with_options(lp: Lp, options: SimplexOptions) -> Self {
+		let tableau = SimplexSolver::convert_lp_to_tableau(&lp);
+		let upper = SimplexSolver::find_upper_bounds(&lp, tableau.cols());
+		let mut basis = SimplexSolver::find_initial_basis(&tableau, options.tolerances.zero);
+
+		let rhs_index = tableau.cols() - 1;
+		for row in 1 .. tableau.rows() {											// A unit column starting above its upper bound
+			if let Some(col) = basis[row] {										// is left to Phase I
+				if tableau[[row, rhs_index]] > upper[col] + options.tolerances.primal_feasibility {
+					basis[row] = None;
+				}
+			}
+		}
 
-		// print_matrix(&local.tableau);
+		let complemented = vec![false; tableau.cols()];
+		SimplexSolver {
+			tableau: tableau,
+			lp: lp,
+			pricing: options.pricing,
+			options: options,
+			warm_start: None,
+			lex_cols: vec![],
+			basis: basis,
+			iterations: 0,
+			deadline: None,
+			certificate: None,
+			upper: upper,
+			complemented: complemented,
+			solved: false
+		}
+	}
+
+	/// Solves the Lp like `solve`, then keeps the optimal tableau and its
+	/// basis as `warm_start`.
+	///
+	/// Changes made afterwards with `add_constraint` or `set_rhs` are
+	/// written into the kept tableau: a new row is reduced by the basis, and
+	/// a new entry of `b` is carried through B^-1 to the basic vars. Both
+	/// leave the tableau dual feasible, so the next call finishes by the
+	/// dual simplex method and `iterations` counts only its pivots.
+	///
+	/// No tableau is kept for a scaled Lp or after a call that ends without
+	/// an optimum. The next call then rebuilds the tableau and pivots the
+	/// `warm_start` basis into it before the dual simplex method, and those
+	/// pivots are not counted.
+	///
+	/// # Examples
+	/// ```
+	/// # extern crate rulp;
+	/// use rulp::builder::{Builder, BuilderBase, Relation};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{SimplexSolver, SolverBase};
+	///
+	/// # fn main() {
+	/// let text_problem = "
+	/// 	var x;
+	/// 	var y;
+	/// 	maximize obj: x + y;
+	/// 	subject to c1: x + 2*y <= 4;
+	/// 	subject to c2: 3*x + y <= 6;
+	/// ";
+	/// let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	///
+	/// let mut solver = SimplexSolver::new(lp);
+	/// solver.reoptimize().unwrap();
+	///
+	/// // x + y <= 2 cuts off the optimum, which one dual pivot restores
+	/// solver.add_constraint("c3", vec![1., 1., 0., 0.], Relation::LessThanOrEqual, 2.).unwrap();
+	/// let solution = solver.reoptimize().unwrap();
+	/// assert!((solution.objective.unwrap() - 2.).abs() < 1e-9);
+	/// assert_eq!(solution.iterations, 1);
+	/// # }
+	/// ```
+	pub fn reoptimize(&mut self) -> Result<Solution, SolveError> {
+		if !self.solved {
+			let (solution, local) = self.solve_tableau()?;
+			if solution.status == Status::Optimal {
+				if let Some(local) = local {
+					*self = local;
+					self.solved = true;
+				}
+				self.warm_start = solution.basis.clone();
+			}
+			return Ok(solution);
+		}
 
-		if !has_bfs {
-			return Solution {
-				lp: self.lp.clone(),
-    			values: None,
-    			objective: None,
-    			status: Status::Infeasible
-    		};
+		validate_lp(&self.lp)?;
+		self.iterations = 0;
+		self.certificate = None;
+		self.deadline = self.options.time_limit.map(|limit| Instant::now() + limit);
+		let status = match self.dual_optimize() {
+			Ok(()) => self.optimize(),
+			Err(status) => status
+		};
+		if status != Status::Optimal {
+			self.solved = false;
+			if status == Status::Infeasible {
+				self.certificate = self.cold_certificate();
+			}
+			return Ok(self.unsolved(status));
 		}
 
-		// Local has a basic feasible solution so we can optimize
-		let bounded = local.optimize();
+		let solution = self.solution(status);
+		self.warm_start = solution.basis.clone();
+		Ok(solution)
+	}
 
-		if !bounded {
-			return Solution {
-				lp: self.lp.clone(),
-    			values: None,
-    			objective: None,
-    			status: Status::Unbounded
-    		};
+	/// Appends the constraint `coefficients^T x <relation> rhs` to the Lp.
+	///
+	/// The coefficients are over the columns of `A`, and the new row gets
+	/// a `slack_#` or `excess_#` column numbered by the row; an equality
+	/// gets a slack fixed at zero. Any `warm_start` basis is extended with
+	/// that column basic, and so is a tableau kept by `reoptimize`.
+	pub fn add_constraint(&mut self, constraint: &str, coefficients: Vec<f64>, relation: Relation, rhs: f64)
+			-> Result<(), SolveError> {
+		let rows = self.lp.A.rows();
+		let cols = self.lp.A.cols();
+		if coefficients.len() != cols {
+			return Err(SolveError::CoefficientsLength { cols: cols, len: coefficients.len() });
 		}
 
+		let mut A = self.lp.A.clone();
+		let entries: Vec<(usize, f64)> = coefficients.iter().cloned().enumerate().collect();
+		A.push_row(&entries);
+
+		let (coeff, name, upper) = match relation {
+			Relation::LessThanOrEqual => (1., format!("slack_{}", rows), INFINITY),
+			Relation::GreaterThanOrEqual => (-1., format!("excess_{}", rows), INFINITY),
+			Relation::Equal => (1., format!("slack_{}", rows), 0.)
+		};
+		A.push_column(&[(rows, coeff)]);
+
+		let mut lp = self.lp.clone();
+		lp.A = A;
+		lp.b.push(rhs);
+		lp.c.push(0.);
+		lp.vars.push(name);
+		lp.constraints.push(constraint.to_string());
+		lp.lower.push(0.);
+		lp.upper.push(upper);
+		lp.num_artificial_vars += 1;
+
+		let mut warm_start = self.warm_start.take();
+		if let Some(ref mut basis) = warm_start {
+			basis.statuses.push(VarStatus::Basic);
+		}
+		if self.solved {
+			self.append_row(&coefficients, coeff, rhs, upper);
+			self.lp = lp;
+			self.warm_start = warm_start;
+		} else {
+			self.reset(lp, warm_start);
+		}
+		Ok(())
+	}
+
+	/// Changes entry `row` of `b`, keeping any `warm_start` basis and any
+	/// tableau kept by `reoptimize`.
+	pub fn set_rhs(&mut self, row: usize, value: f64) -> Result<(), SolveError> {
+		if row >= self.lp.b.len() {
+			return Err(SolveError::InvalidRow { row: row, rows: self.lp.b.len() });
+		}
+
+		let delta = value - self.lp.b[row];
+		if self.solved && self.shift_rhs(row, delta) {
+			self.lp.b[row] = value;
+			return Ok(());
+		}
+
+		let mut lp = self.lp.clone();
+		lp.b[row] = value;
+		let warm_start = self.warm_start.take();
+		self.reset(lp, warm_start);
+		Ok(())
+	}
+
+	// Rebuilds the tableau for a changed Lp
+	fn reset(&mut self, lp: Lp, warm_start: Option<Basis>) {
+		*self = SimplexSolver::with_options(lp, self.options.clone());
+		self.warm_start = warm_start;
+	}
+
+	// Appends the row of a new constraint to the solved tableau, with its
+	// slack column basic in it. The row is written in the shifted and
+	// complemented vars, divided by the slack coefficient of +-1 and then
+	// reduced by every basic row, so that the objective row stays optimal
+	// and only the slack may lie outside its bounds.
+	fn append_row(&mut self, coefficients: &[f64], slack: f64, rhs: f64, upper: f64) {
+		let rows = self.tableau.rows();
+		let cols = self.tableau.cols();
+		let slack_col = cols - 1;
+		let rhs_index = cols;
+
+		let mut tableau = Matrix::zeros(rows + 1, cols + 1);
+		for row in 0 .. rows {
+			for col in 0 .. slack_col {
+				tableau[[row, col]] = self.tableau[[row, col]];
+			}
+			tableau[[row, rhs_index]] = self.tableau[[row, cols - 1]];
+		}
+
+		let mut new_row = vec![0.; cols + 1];
+		let mut value = rhs;
+		for (var, &coeff) in coefficients.iter().enumerate() {
+			let col = var + 1;
+			value -= coeff * self.lp.lower[var];
+			if self.complemented[col] {
+				value -= coeff * self.upper[col];
+				new_row[col] = -slack * coeff;
+			} else {
+				new_row[col] = slack * coeff;
+			}
+		}
+		new_row[slack_col] = 1.;
+		new_row[rhs_index] = slack * value;
+
+		let zero = self.options.tolerances.zero;
+		for row in 1 .. rows {
+			let basic = match self.basis[row] {
+				Some(basic) => basic,
+				None => continue
+			};
+			let factor = new_row[basic];
+			if factor == 0. {
+				continue;
+			}
+			for col in 1 .. cols + 1 {
+				new_row[col] -= factor * tableau[[row, col]];
+				if new_row[col].abs() < zero {
+					new_row[col] = 0.;
+				}
+			}
+			new_row[basic] = 0.;
+		}
+		for col in 1 .. cols + 1 {
+			tableau[[rows, col]] = new_row[col];
+		}
+
+		self.tableau = tableau;
+		self.basis.push(Some(slack_col));
+		self.upper.insert(slack_col, upper);
+		self.complemented.insert(slack_col, false);
+	}
+
+	// Raising b_row by delta moves the basic vars by delta times column row
+	// of B^-1, which is taken from the original Lp as in RHS ranging, and
+	// the objective by their costs. Returns false, leaving the tableau as
+	// it was, when a row is unspanned or the basis is singular.
+	fn shift_rhs(&mut self, row: usize, delta: f64) -> bool {
+		let spanned = self.spanned_rows();
+		let size = spanned.len();
+		if size != self.lp.A.rows() {
+			return false;
+		}
+
+		let mut B = Matrix::zeros(size, size);
+		let mut unit = vec![0.; size];
+		for (i, &(basic_row, _)) in spanned.iter().enumerate() {
+			for (j, &(_, col)) in spanned.iter().enumerate() {
+				B[[i, j]] = self.lp.A[[basic_row, col]];
+			}
+			if basic_row == row {
+				unit[i] = 1.;
+			}
+		}
+		let beta = match B.solve(Vector::new(unit)) {
+			Ok(beta) => beta,
+			Err(_) => return false
+		};
+
+		let rhs_index = self.tableau.cols() - 1;
+		let sign = if self.lp.optimization == Optimization::Max { 1. } else { -1. };
+		for (j, &(basic_row, col)) in spanned.iter().enumerate() {
+			let change = delta * beta[j];
+			self.tableau[[basic_row + 1, rhs_index]] += if self.complemented[col + 1] { -change } else { change };
+			self.tableau[[0, rhs_index]] += sign * self.lp.c[col] * change;
+		}
+		true
+	}
+
+	// Solves the Lp, also returning the solver holding the final tableau
+	// unless the Lp was scaled
+	fn solve_tableau(&self) -> Result<(Solution, Option<SimplexSolver>), SolveError> {
+		// println!("Solver called");
+		validate_lp(&self.lp)?;
+		if let Some(ref basis) = self.warm_start {
+			if basis.statuses.len() != self.lp.A.cols() {
+				return Err(SolveError::InvalidBasis { cols: self.lp.A.cols(), len: basis.statuses.len() });
+			}
+		}
+		if let Some(method) = self.options.scaling {
+			let scale = Scale::new(&self.lp, method);
+			let mut scaled = SimplexSolver::with_options(scale.scale_lp(&self.lp), SimplexOptions { scaling: None, .. self.options.clone() });
+			scaled.warm_start = self.warm_start.clone();
+			return scaled.solve().map(|solution| (scale.unscale(solution, &self.lp), None));
+		}
+
+		let mut local = SimplexSolver::with_options(self.lp.clone(), self.options.clone());
+		local.deadline = self.options.time_limit.map(|limit| Instant::now() + limit);
+		let dual_start = match self.warm_start {
+			Some(ref basis) => local.load_basis(basis),
+			None => false
+		};
+
+		let found = if dual_start { local.dual_optimize() } else { local.find_bfs() };
+		if let Err(status) = found {
+			if status == Status::Infeasible && self.warm_start.is_some() {
+				local.certificate = local.cold_certificate();
+			}
+			return Ok((local.unsolved(status), None));
+		}
+
+		// print_matrix(&local.tableau);
+
+		// Local has a basic feasible solution so we can optimize
+		let status = local.optimize();
+		match status {
+			Status::Optimal | Status::IterationLimit | Status::TimeLimit | Status::Cancelled => {},
+			_ => return Ok((local.unsolved(status), None))
+		}
+
+		let solution = local.solution(status);
+		Ok((solution, Some(local)))
+	}
+
+	// Reads the Solution off a tableau that optimize has finished with
+	fn solution(&self, status: Status) -> Solution {
 		let coeff;
 		match &self.lp.optimization {
 			&Optimization::Max => coeff = 1.,
 			&Optimization::Min => coeff = -1.,
 		}
-		return Solution {
-					lp: self.lp.clone(),
-	    			values: Some(local.get_basic_feasible_solution()),
-	    			objective: Some(local.get_objective() * coeff),
-	    			status: Status::Optimal
+
+		let duals = if status == Status::Optimal { self.get_duals() } else { None };
+		let mut reduced_costs = duals.as_ref().map(|duals| self.get_reduced_costs(duals));
+		let mut sensitivity = match reduced_costs {
+			Some(ref costs) => self.get_sensitivity(costs),
+			None => None
 		};
+		let mut values = self.get_basic_feasible_solution();
+		undo_substitutions(&self.lp, |col| self.is_basic(col + 1), &mut values, &mut reduced_costs, &mut sensitivity);
+
+		Solution {
+			lp: self.lp.clone(),
+			values: Some(values),
+			objective: Some(self.get_objective() * coeff + self.get_lower_objective()),
+			status: status,
+			basis: Some(self.get_basis()),
+			duals: duals,
+			reduced_costs: reduced_costs,
+			sensitivity: sensitivity,
+			certificate: None,
+			iterations: self.iterations
+		}
 	}
-}
 
-impl SimplexSolver {
+	// The Farkas ray is read off rows of the cold tableau, which a warm
+	// start or re-optimization may have combined
+	fn cold_certificate(&self) -> Option<Certificate> {
+		let mut cold = SimplexSolver::with_options(self.lp.clone(), self.options.clone());
+		cold.deadline = self.deadline;
+		match cold.find_bfs() {
+			Err(Status::Infeasible) => cold.certificate,
+			_ => None
+		}
+	}
+
+	fn unsolved(&self, status: Status) -> Solution {
+		Solution {
+			lp: self.lp.clone(),
+			values: None,
+			objective: None,
+			status: status,
+			basis: None,
+			duals: None,
+			reduced_costs: None,
+			sensitivity: None,
+			certificate: self.certificate.clone(),
+			iterations: self.iterations
+		}
+	}
+
+	// Solves B^T y = c_B against the original Lp so the duals carry the
+	// Lp's own signs regardless of how the tableau rows were scaled. Rows
+	// left unspanned by Phase I are redundant and get a dual of zero.
+	fn get_duals(&self) -> Option<Vec<f64>> {
+		let spanned = self.spanned_rows();
+		let size = spanned.len();
+
+		let mut duals = vec![0.; self.lp.A.rows()];
+		if size == 0 {
+			return Some(duals);
+		}
+
+		let mut basis_t = Matrix::zeros(size, size);
+		let mut c_B = Vec::with_capacity(size);
+		for (i, &(_, col)) in spanned.iter().enumerate() {
+			for (j, &(row, _)) in spanned.iter().enumerate() {
+				basis_t[[i, j]] = self.lp.A[[row, col]];
+			}
+			c_B.push(self.lp.c[col]);
+		}
+
+		let y = match basis_t.solve(Vector::new(c_B)) {
+			Ok(y) => y,
+			Err(_) => return None
+		};
+		for (j, &(row, _)) in spanned.iter().enumerate() {
+			duals[row] = y[j];
+		}
+
+		Some(duals)
+	}
+
+	// Pairs each row of the Lp holding a basic variable with that variable's
+	// column in the Lp.
+	fn spanned_rows(&self) -> Vec<(usize, usize)> {
+		(1 .. self.tableau.rows())
+			.filter_map(|row| self.basis[row].map(|col| (row - 1, col - 1)))
+			.collect()
+	}
+
+	// Objective ranging reads B^-1 A straight from the optimal tableau rows;
+	// the row negations made when building the tableau cancel out there.
+	// RHS ranging needs B^-1 itself, which is taken from the original Lp.
+	fn get_sensitivity(&self, reduced_costs: &Vec<f64>) -> Option<SensitivityReport> {
+		let tol = self.options.tolerances.pivot;
+		let is_max = self.lp.optimization == Optimization::Max;
+		let cols = self.lp.A.cols();
+
+		let mut objective_ranges = Vec::with_capacity(cols);
+		for col in 0 .. cols {
+			let c = self.lp.c[col];
+			let d = reduced_costs[col];
+			let row = match (1 .. self.tableau.rows()).find(|&row| self.basis[row] == Some(col + 1)) {
+				Some(row) => row,
+				None => {
+					// A nonbasic variable stays out until its reduced cost changes sign
+					let at_lower = !self.complemented[col + 1];
+					objective_ranges.push(if is_max == at_lower {
+						Range { lower: NEG_INFINITY, upper: c - d }
+					} else {
+						Range { lower: c - d, upper: INFINITY }
+					});
+					continue;
+				}
+			};
+
+			// Raising c_j by delta lowers every other reduced cost d_l by delta * alpha_l.
+			// The tableau holds complemented vars, whose costs and rows are negated.
+			let mut lower = NEG_INFINITY;
+			let mut upper = INFINITY;
+			for other in 0 .. cols {
+				// The negative part of a split var moves with it, so its
+				// reduced cost does not change
+				if self.is_basic(other + 1) || self.split_twin(col) == Some(other) {
+					continue;
+				}
+				let alpha = self.tableau[[row, other + 1]];
+				if alpha.abs() <= tol {
+					continue;
+				}
+				let cost = if self.complemented[other + 1] { -reduced_costs[other] } else { reduced_costs[other] };
+				let delta = cost / alpha;
+				if (alpha > 0.) == is_max {
+					lower = lower.max(delta);
+				} else {
+					upper = upper.min(delta);
+				}
+			}
+			if self.complemented[col + 1] {
+				objective_ranges.push(Range { lower: c - upper, upper: c - lower });
+			} else {
+				objective_ranges.push(Range { lower: c + lower, upper: c + upper });
+			}
+		}
+
+		let spanned = self.spanned_rows();
+		let size = spanned.len();
+		let mut B = Matrix::zeros(size, size);
+		for (i, &(row, _)) in spanned.iter().enumerate() {
+			for (j, &(_, col)) in spanned.iter().enumerate() {
+				B[[i, j]] = self.lp.A[[row, col]];
+			}
+		}
+		let B_inv = if size == 0 {
+			B
+		} else {
+			match B.inverse() {
+				Ok(B_inv) => B_inv,
+				Err(_) => return None
+			}
+		};
+
+		let rhs_col = self.tableau.cols() - 1;
+		let mut rhs_ranges = Vec::with_capacity(self.lp.b.len());
+		for row in 0 .. self.lp.b.len() {
+			let b = self.lp.b[row];
+			// Redundant rows cannot move without making the Lp infeasible
+			let index = match spanned.iter().position(|&(r, _)| r == row) {
+				Some(index) => index,
+				None => {
+					rhs_ranges.push(Range { lower: b, upper: b });
+					continue;
+				}
+			};
+
+			// Raising b_i by delta moves each basic variable by delta * beta,
+			// which must keep it between its bounds
+			let mut lower = NEG_INFINITY;
+			let mut upper = INFINITY;
+			for (j, &(basic_row, col)) in spanned.iter().enumerate() {
+				let beta = B_inv[[j, index]];
+				if beta.abs() <= tol {
+					continue;
+				}
+				let bound = self.upper[col + 1];
+				let mut value = self.tableau[[basic_row + 1, rhs_col]].max(0.);
+				if self.complemented[col + 1] {
+					value = bound - value;
+				}
+				let (to_lower, to_upper) = (-value / beta, (bound - value) / beta);
+				if beta > 0. {
+					lower = lower.max(to_lower);
+					upper = upper.min(to_upper);
+				} else {
+					upper = upper.min(to_lower);
+					lower = lower.max(to_upper);
+				}
+			}
+			rhs_ranges.push(Range { lower: b + lower, upper: b + upper });
+		}
+
+		Some(SensitivityReport {
+			vars: self.lp.vars.clone(),
+			constraints: self.lp.constraints.clone(),
+			objective_ranges: objective_ranges,
+			rhs_ranges: rhs_ranges
+		})
+	}
+
+	// Returns the other column of the split var that col belongs to
+	fn split_twin(&self, col: usize) -> Option<usize> {
+		self.lp.substitutions.iter().filter_map(|substitution| match *substitution {
+			Substitution::Split { positive, negative } if positive == col => Some(negative),
+			Substitution::Split { positive, negative } if negative == col => Some(positive),
+			_ => None
+		}).next()
+	}
+
+	// d_j = c_j - y^T A_j, computed against the original Lp
+	fn get_reduced_costs(&self, duals: &Vec<f64>) -> Vec<f64> {
+		let zero = self.options.tolerances.zero;
+		(0 .. self.lp.A.cols()).map(|col| {
+			let mut cost = self.lp.c[col];
+			for (row, value) in self.lp.A.column(col) {
+				cost -= duals[row] * value;
+			}
+			if cost.abs() < zero { 0. } else { cost }
+		}).collect()
+	}
+
+	/// Derives a Gomory fractional cut from each row of the optimal tableau
+	/// whose basic var must be integral but is further than
+	/// `integrality_tolerance` from an integer.
+	///
+	/// `basis` is the optimal basis of a Solution of this solver's Lp; no
+	/// cuts are returned for any other basis. Each cut is rounded to the
+	/// form `x_B + sum floor(a_j) x_j <= floor(b)` with integer coefficients,
+	/// which keeps the slack of the cut integral. A row gives no cut when a
+	/// rounded coefficient falls on a column that need not be integral.
+	/// Columns listed in `Lp::integers` with integral bounds are integral,
+	/// apart from the positive part of a split var, and so are the slack
+	/// and excess columns of rows with integral data on integral columns.
+	pub fn gomory_cuts(&self, basis: &Basis, integrality_tolerance: f64) -> Result<Vec<Cut>, SolveError> {
+		validate_lp(&self.lp)?;
+		if basis.statuses.len() != self.lp.A.cols() {
+			return Err(SolveError::InvalidBasis { cols: self.lp.A.cols(), len: basis.statuses.len() });
+		}
+
+		let mut local = SimplexSolver::with_options(self.lp.clone(), self.options.clone());
+		if local.load_basis(basis) || local.get_basis() != *basis {
+			return Ok(vec![]);
+		}
+
+		let tol = integrality_tolerance;
+		let integral = integral_columns(&self.lp);
+		let rhs_index = local.tableau.cols() - 1;
+		let mut cuts = vec![];
+		for row in 1 .. local.tableau.rows() {
+			match local.basis[row] {
+				Some(col) if integral[col - 1] => {},
+				_ => continue
+			}
+			let value = local.tableau[[row, rhs_index]];
+			let fraction = value - value.floor();
+			if fraction <= tol || fraction >= 1. - tol {
+				continue;
+			}
+
+			// Tableau vars are x - l, or u - x once complemented
+			let mut coefficients = vec![0.; self.lp.A.cols()];
+			let mut rhs = value.floor();
+			let mut valid = true;
+			for col in 1 .. rhs_index {
+				let rounded = (local.tableau[[row, col]] + tol).floor();
+				if rounded == 0. {
+					continue;
+				}
+				if !integral[col - 1] {
+					valid = false;
+					break;
+				}
+				if local.complemented[col] {
+					coefficients[col - 1] = -rounded;
+					rhs -= rounded * self.lp.upper[col - 1];
+				} else {
+					coefficients[col - 1] = rounded;
+					rhs += rounded * self.lp.lower[col - 1];
+				}
+			}
+
+			if valid {
+				cuts.push(Cut {
+					coefficients: coefficients,
+					rhs: rhs
+				});
+			}
+		}
+
+		Ok(cuts)
+	}
+
+	/// Returns the current basis of the tableau.
+	pub fn get_basis(&self) -> Basis {
+		let mut statuses: Vec<VarStatus> = (1 .. self.tableau.cols() - 1)
+			.map(|col| if self.complemented[col] { VarStatus::AtUpper } else { VarStatus::AtLower })
+			.collect();
+		for row in 1 .. self.tableau.rows() {
+			if let Some(col) = self.basis[row] {
+				statuses[col - 1] = VarStatus::Basic;
+			}
+		}
+
+		Basis {
+			statuses: statuses
+		}
+	}
+
+	// Pivots the basic columns of a warm start basis into the tableau. Rows
+	// the basis does not cover keep their slack or are left for Phase I. A
+	// basis that spans every row and is dual but not primal feasible, e.g.
+	// an optimal basis after a row is added, is kept with its objective row
+	// written in the nonbasic vars and true is returned, so the dual simplex
+	// method can finish the solve. Otherwise if the resulting point is not
+	// primal feasible the cold tableau is restored.
+	fn load_basis(&mut self, basis: &Basis) -> bool {
+		let cold_tableau = self.tableau.clone();
+		let cold_basis = self.basis.clone();
+		let wanted: Vec<usize> = basis.basic_vars().iter().map(|&var| var + 1).collect();
+		let rhs_index = self.tableau.cols() - 1;
+
+		for &col in &wanted {
+			if self.is_basic(col) {
+				continue;
+			}
+
+			let mut pivot_row = None;												// Largest coeff among rows not yet claimed by
+			let mut pivot_coeff = self.options.tolerances.pivot;					// the warm start basis, for stability
+			for row in 1 .. self.tableau.rows() {
+				let claimed = match self.basis[row] {
+					Some(basic) => wanted.contains(&basic),
+					None => false
+				};
+				let coeff = self.tableau[[row, col]].abs();
+				if !claimed && coeff > pivot_coeff {
+					pivot_row = Some(row);
+					pivot_coeff = coeff;
+				}
+			}
+
+			if let Some(row) = pivot_row {
+				self.pivot(row, col);
+			}
+		}
+
+		for (var, status) in basis.statuses.iter().enumerate() {				// Vars at their upper bound start there
+			let col = var + 1;
+			if *status == VarStatus::AtUpper && !self.is_basic(col) && self.upper[col].is_finite() {
+				self.complement(col);
+			}
+		}
+
+		for col in 0 .. self.tableau.cols() {									// Row 0 must hold the raw objective until
+			self.tableau[[0, col]] = cold_tableau[[0, col]];						// write_obj_in_nb_vars is called
+		}
+
+		let tol = self.options.tolerances.primal_feasibility;
+		let feasible = (1 .. self.tableau.rows()).all(|row| {
+			let rhs = self.tableau[[row, rhs_index]];
+			let upper = match self.basis[row] {
+				Some(col) => self.upper[col],
+				None => INFINITY
+			};
+			rhs >= -tol && rhs <= upper + tol
+		});
+		if feasible {
+			return false;
+		}
+
+		if self.find_unspanned_rows().is_none() && self.write_obj_in_nb_vars().is_ok() && self.is_optimal() {
+			return true;
+		}
+		self.tableau = cold_tableau;
+		self.basis = cold_basis;
+		self.complemented = vec![false; self.tableau.cols()];
+		false
+	}
+
+	// Sized from A alone so that an Lp with a mismatched b or c still gets
+	// a tableau; solve reports the mismatch instead of panicking here.
+	// Every var is shifted by its lower bound so that it starts at zero, and
+	// rows with a negative RHS are negated so every RHS starts non-negative.
 	fn convert_lp_to_tableau(lp: &Lp) -> Matrix<f64> {
 		let mut mat_builder: Vec<f64> = vec![1.];
-		for opt_coeff in &lp.c {
+		for col in 0 .. lp.A.cols() {
+			let opt_coeff = lp.c.get(col).unwrap_or(&0.);
 			match lp.optimization {
 				Optimization::Min => {
 					mat_builder.push(-1. * opt_coeff);
@@ -113,27 +832,77 @@ impl SimplexSolver {
 			}
 		}
 		mat_builder.push(0.);
+		let rhs_index = lp.A.cols() + 1;
+		let mut signs = vec![];
+		for row in 0 .. lp.A.rows() {
+			let rhs = SimplexSolver::get_shifted_rhs(lp, row);
+			let sign = if rhs < 0. { -1. } else { 1. };
+			mat_builder.extend(vec![0.; rhs_index]);
+			mat_builder.push(sign * rhs);
+			signs.push(sign);
+		}
+
+		let mut tableau = Matrix::new(&lp.A.rows()+1, &lp.A.cols()+2, mat_builder);
+		for (row, col, value) in lp.A.triplets() {
+			tableau[[row + 1, col + 1]] = signs[row] * value;
+		}
+		tableau
+	}
+
+	// RHS of a row once every var is shifted by its lower bound
+	fn get_shifted_rhs(lp: &Lp, row: usize) -> f64 {
+		let mut rhs = *lp.b.get(row).unwrap_or(&0.);
+		for col in 0 .. lp.A.cols() {
+			rhs -= lp.A[[row, col]] * *lp.lower.get(col).unwrap_or(&0.);
+		}
+		rhs
+	}
+
+	// Indexed by tableau column, like the basis; z and the RHS are unbounded
+	fn find_upper_bounds(lp: &Lp, cols: usize) -> Vec<f64> {
+		let mut upper = vec![INFINITY; cols];
+		for col in 1 .. cols - 1 {
+			let var = col - 1;
+			upper[col] = *lp.upper.get(var).unwrap_or(&INFINITY) - *lp.lower.get(var).unwrap_or(&0.);
+		}
+		upper
+	}
+
+	// The only place the basis is read off the column patterns; from here
+	// on it is tracked through every pivot. Row 0 is spanned by z (col 0).
+	fn find_initial_basis(tableau: &Matrix<f64>, zero: f64) -> Vec<Option<usize>> {
+		let mut basis = vec![None; tableau.rows()];
+		basis[0] = Some(0);
+
 		unsafe {
-			for row in 0 .. lp.A.rows() {
-				mat_builder.push(0.);
-				for col in 0 .. lp.A.cols() {
-					mat_builder.push(*lp.A.get_unchecked([row, col]));
+			for col in 1 .. tableau.cols() - 1 {
+				let mut one_row = None;
+				let mut is_unit = true;
+				for row in 1 .. tableau.rows() {
+					let coeff = *tableau.get_unchecked([row, col]);
+					if (coeff - 1.).abs() <= zero && one_row.is_none() {
+						one_row = Some(row);
+					} else if coeff.abs() > zero {
+						is_unit = false;
+						break;
+					}
+				}
+
+				match one_row {
+					Some(row) if is_unit && basis[row].is_none() => basis[row] = Some(col),
+					_ => {}
 				}
-				mat_builder.push(lp.b[row]);
 			}
 		}
-		
-		// println!("{:}", lp);
-		// println!("{:?}", mat_builder);
-		// println!("{:?}", mat_builder.len());
 
-		Matrix::new(&lp.A.rows()+1, &lp.A.cols()+2, mat_builder)
+		basis
 	}
 
 	fn is_optimal(&self) -> bool {
+		let optimality = self.options.tolerances.optimality;
 		unsafe{
 			for col in 1 .. self.tableau.cols() - 1{
-				if *self.tableau.get_unchecked([0, col]) < 0. {
+				if *self.tableau.get_unchecked([0, col]) < -optimality {
 					return false;
 				}
 			}
@@ -143,67 +912,48 @@ impl SimplexSolver {
 	}
 
 	fn get_basic_feasible_solution(&self) -> Vec<f64> {
-		let mut bfs = vec![];
+		let mut bfs = vec![0.0; self.tableau.cols() - 2];
 		let rhs_index = self.tableau.cols() - 1;
 
 		unsafe {
-			for i in 1 .. self.tableau.cols() - 1 {
-				if self.is_basic(i) {
-					let row = self.get_basic_row(i);
-					let val = *self.tableau.get_unchecked([row, rhs_index]);
-					bfs.push(val);
-				} else {
-					bfs.push(0.0);
+			for row in 1 .. self.tableau.rows() {
+				if let Some(col) = self.basis[row] {
+					bfs[col - 1] = *self.tableau.get_unchecked([row, rhs_index]);
 				}
 			}
 		}
+
+		for col in 1 .. self.tableau.cols() - 1 {								// Undoing the complements and the shift
+			if self.complemented[col] {											// by the lower bounds
+				bfs[col - 1] = self.upper[col] - bfs[col - 1];
+			}
+			bfs[col - 1] += *self.lp.lower.get(col - 1).unwrap_or(&0.);
+		}
 		return bfs;
 	}
 
 	fn get_basic_row(&self, col: usize) -> usize {
-		let mut ret = 1;
-		unsafe {
-			for row in 1 .. self.tableau.rows() {
-				if *self.tableau.get_unchecked([row, col]) == 1. {
-					ret = row;
-				}
-			}
-		}
-		return ret;
+		(1 .. self.tableau.rows()).find(|&row| self.basis[row] == Some(col)).unwrap_or(1)
 	}
 
 	fn is_basic(&self, col: usize) -> bool {
-		if col < 1 || col >= self.tableau.cols() {
-			panic!("Invalid col index {} for basic variable", col);
+		if col < 1 || col >= self.tableau.cols() - 1 {
+			return false;
 		}
-		unsafe {
-			let mut one_ct = 0;
-			let mut zero_ct = 0;
-			for row in 1 .. self.tableau.rows() {
-				let coeff = *self.tableau.get_unchecked([row, col]);
-				if coeff == 1. {
-					one_ct += 1;
-				} else if coeff == 0. {
-					zero_ct += 1;
-				}
-			}
 
-			one_ct == 1 && zero_ct == (self.tableau.rows() - 2)
-		}
+		self.basis[1 ..].contains(&Some(col))
 	}
 
 	fn calc_pivot_ratio(&self, row: usize, col: usize) -> Option<f64> {
-		if self.is_basic(col) {
-			panic!("Attempting to calculate pivot ratio on basic variable");
-		} else if row == 0 || row >= self.tableau.rows() {
-			panic!("Invalid constraint index {}", row);
+		if self.is_basic(col) || row == 0 || row >= self.tableau.rows() {
+			return None;
 		}
 		unsafe {
 			let coeff = *self.tableau.get_unchecked([row, col]);
-			if coeff > 0. {
+			if coeff > self.options.tolerances.pivot {
 				let rhs_index = self.tableau.cols() - 1;
 				let rhs_val = *self.tableau.get_unchecked([row, rhs_index]);
-				Some(rhs_val / coeff)
+				Some(rhs_val.max(0.) / coeff)								// Slightly negative RHS is round-off, treat as 0
 			} else {
 				None
 			}
@@ -211,13 +961,17 @@ impl SimplexSolver {
 	}
 
 	fn choose_pivot_row(&self, col: usize) -> usize {
+		let tie = self.options.tolerances.primal_feasibility;
 		let mut min_ratio = INFINITY;
 		let mut min_row = 0;
 
 		for row in 1 .. self.tableau.rows() {
 			match self.calc_pivot_ratio(row, col) {
 				Some(ratio) => {
-					if ratio < min_ratio {
+					if ratio < min_ratio - tie {
+						min_ratio = ratio;
+						min_row = row;
+					} else if ratio <= min_ratio + tie && self.breaks_tie(row, min_row, col) {
 						min_ratio = ratio;
 						min_row = row;
 					}
@@ -234,16 +988,99 @@ impl SimplexSolver {
 		min_row
 	}
 
-	fn choose_pivot_col(&self) -> usize {
+	// Returns the row whose basic var first reaches its upper bound as col
+	// increases, with the increase, or (0, INFINITY) if there is none
+	fn choose_upper_row(&self, col: usize) -> (usize, f64) {
+		let rhs_index = self.tableau.cols() - 1;
+		let mut min_ratio = INFINITY;
+		let mut min_row = 0;
+
+		for row in 1 .. self.tableau.rows() {
+			let basic = match self.basis[row] {
+				Some(basic) if self.upper[basic].is_finite() => basic,
+				_ => continue
+			};
+			let coeff = self.tableau[[row, col]];
+			if coeff < -self.options.tolerances.pivot {
+				let ratio = (self.upper[basic] - self.tableau[[row, rhs_index]]).max(0.) / -coeff;
+				if ratio < min_ratio {
+					min_ratio = ratio;
+					min_row = row;
+				}
+			}
+		}
+
+		(min_row, min_ratio)
+	}
+
+	// Replaces the var in col by its distance from its upper bound, so that
+	// a var at its upper bound sits at zero like the other nonbasic vars.
+	// Complementing the same column twice restores it.
+	fn complement(&mut self, col: usize) {
+		let upper = self.upper[col];
+		let rhs_index = self.tableau.cols() - 1;
+		for row in 0 .. self.tableau.rows() {
+			let coeff = self.tableau[[row, col]];
+			self.tableau[[row, rhs_index]] -= upper * coeff;
+			self.tableau[[row, col]] = -coeff;
+		}
+		self.complemented[col] = !self.complemented[col];
+	}
+
+	// Returns true if row should replace the tied incumbent as the pivot row
+	fn breaks_tie(&self, row: usize, incumbent: usize, col: usize) -> bool {
+		if self.options.ratio_test == RatioTest::Lexicographic {
+			let zero = self.options.tolerances.zero;
+			unsafe {
+				let row_coeff = *self.tableau.get_unchecked([row, col]);
+				let incumbent_coeff = *self.tableau.get_unchecked([incumbent, col]);
+				for &lex_col in &self.lex_cols {
+					let row_val = *self.tableau.get_unchecked([row, lex_col]) / row_coeff;
+					let incumbent_val = *self.tableau.get_unchecked([incumbent, lex_col]) / incumbent_coeff;
+					if (row_val - incumbent_val).abs() > zero {
+						return row_val < incumbent_val;
+					}
+				}
+			}
+			false
+		} else if self.pricing == PricingRule::Bland {
+			match (self.get_basic_col(row), self.get_basic_col(incumbent)) {
+				(Some(row_var), Some(incumbent_var)) => row_var < incumbent_var,
+				(Some(_), None) => false,
+				(None, _) => true
+			}
+		} else {
+			false
+		}
+	}
+
+	fn choose_pivot_col(&self) -> Option<usize> {
+		let mut best_col = None;
+		let mut best_cost = -self.options.tolerances.optimality;
+
 		unsafe {
 			for i in 1 .. self.tableau.cols() - 1 {
-				if *self.tableau.get_unchecked([0, i]) < 0. {
-					return i;
+				let cost = *self.tableau.get_unchecked([0, i]);
+				if cost < best_cost {
+					if self.pricing == PricingRule::Bland {
+						return Some(i);
+					}
+					best_col = Some(i);
+					best_cost = cost;
 				}
 			}
 		}
 
-		panic!("No pivot var chosen because optimal solution!");
+		best_col
+	}
+
+	// Returns the column of the basic variable spanning row, if any
+	fn get_basic_col(&self, row: usize) -> Option<usize> {
+		if row == 0 || row >= self.basis.len() {
+			return None;
+		}
+
+		self.basis[row]
 	}
 
 	fn normalize_pivot(&mut self, row: usize, col: usize) {
@@ -257,11 +1094,19 @@ impl SimplexSolver {
 	}
 
 	fn eliminate_row(&mut self, pivot_row: usize, pivot_col: usize, row: usize) {
+		let zero = self.options.tolerances.zero;
 		unsafe {
 			let mult_factor = *self.tableau.get_unchecked([row, pivot_col]) / *self.tableau.get_unchecked([pivot_row, pivot_col]) * -1.0;
+			if mult_factor == 0. {
+				return;
+			}
 			for c in 1 .. self.tableau.cols() {
 				let add_factor = *self.tableau.get_unchecked([pivot_row, c]) * mult_factor;
-				*self.tableau.get_unchecked_mut([row, c]) += add_factor;
+				let entry = self.tableau.get_unchecked_mut([row, c]);
+				*entry += add_factor;
+				if entry.abs() < zero {											// Flush round-off so zeros stay zeros
+					*entry = 0.;
+				}
 			}
 			*self.tableau.get_unchecked_mut([row, pivot_col]) = 0.;
 		}
@@ -277,63 +1122,201 @@ impl SimplexSolver {
 
 			self.eliminate_row(row, col, r);
 		}
+
+		self.basis[row] = Some(col);
 	}
 
 	fn find_unspanned_rows(&self) -> Option<Vec<usize>> {
-		unsafe {
-			let mut no_basic = vec![];
-			for row in 1 .. self.tableau.rows() {
-				let mut has_basic = false;
-				for col in 1 .. self.tableau.cols() - 1 {
-					if *self.tableau.get_unchecked([row, col]) == 1. && self.is_basic(col) {
-						has_basic = true;
-					}
-				}
+		let no_basic: Vec<usize> = (1 .. self.tableau.rows()).filter(|&row| self.basis[row].is_none()).collect();
+
+		if no_basic.len() == 0 {
+			return None;
+		} else {
+			return Some(no_basic);
+		}
+	}
 
-				if !has_basic {
-					no_basic.push(row);
+	// Returns true if the objective row and RHS column hold only finite values
+	fn is_finite(&self) -> bool {
+		unsafe {
+			let rhs_index = self.tableau.cols() - 1;
+			for col in 1 .. self.tableau.cols() {
+				if !self.tableau.get_unchecked([0, col]).is_finite() {
+					return false;
 				}
 			}
-			
-			if no_basic.len() == 0 {
-				return None;
-			} else {
-				return Some(no_basic);
+			for row in 1 .. self.tableau.rows() {
+				if !self.tableau.get_unchecked([row, rhs_index]).is_finite() {
+					return false;
+				}
 			}
 		}
+
+		true
 	}
 
 	// Can only be called once a BFS has been established
-	fn optimize(&mut self) -> bool {
+	fn optimize(&mut self) -> Status {
 		// println!("Beginning optimization actually");
 		// let mut iterations = 0;
+		self.pricing = self.options.pricing;
+		self.lex_cols = self.basis[1 ..].iter().filter_map(|&col| col).collect();
+		let mut degenerate_pivots = 0;
+
 		while !(self.is_optimal()) {
+			if let Some(status) = self.check_limits() {
+				return status
+			}
 			// println!(">>> Iteration {}", iterations);
 			// print_matrix(&self.tableau);
-			let pivot_col = self.choose_pivot_col();
+			let pivot_col = match self.choose_pivot_col() {
+				Some(col) => col,
+				None => return Status::NumericalFailure		// Not optimal yet nothing to pivot on
+			};
 			// println!("Pivot column: {:?} ({} var entering)", pivot_col, pivot_col - 1);
 			let pivot_row = self.choose_pivot_row(pivot_col);
-			if pivot_row == 0 {			// Unbounded
-				return false
+			let ratio = self.calc_pivot_ratio(pivot_row, pivot_col).unwrap_or(INFINITY);
+			let (upper_row, upper_ratio) = self.choose_upper_row(pivot_col);
+			let flip_ratio = self.upper[pivot_col];
+			if ratio == INFINITY && upper_ratio == INFINITY && flip_ratio == INFINITY {			// Unbounded
+				self.certificate = Some(self.get_unbounded_ray(pivot_col));
+				return Status::Unbounded
 			}
 			// println!("Pivot row: {:?} ({} var leaving)", pivot_row, pivot_row - 1);
-			self.pivot(pivot_row, pivot_col);
+			let prev_objective = self.get_objective();
+			let tie = self.options.tolerances.primal_feasibility;
+			if flip_ratio < ratio - tie && flip_ratio <= upper_ratio {		// Entering var reaches its own upper bound first
+				self.complement(pivot_col);
+			} else if upper_ratio < ratio - tie {							// A basic var reaches its upper bound first
+				if let Some(leaving) = self.basis[upper_row] {
+					self.complement(leaving);
+				}
+				self.pivot(upper_row, pivot_col);
+			} else {
+				self.pivot(pivot_row, pivot_col);
+			}
+			self.iterations += 1;
+			if !self.is_finite() {
+				return Status::NumericalFailure
+			}
+
+			if (self.get_objective() - prev_objective).abs() <= self.options.tolerances.zero {	// Degenerate pivot, may be stalling or cycling
+				degenerate_pivots += 1;
+				if let Some(limit) = self.options.stall_limit {
+					if degenerate_pivots >= limit {
+						self.pricing = PricingRule::Bland;
+					}
+				}
+			} else {
+				degenerate_pivots = 0;
+			}
 			// print_matrix(&self.tableau);
 			// println!("<<< Iteration {}", iterations);
 			// iterations += 1;
 		}
 
-		true
+		if !self.is_finite() {			// NaNs never compare as negative so check before claiming optimality
+			return Status::NumericalFailure
+		}
+
+		Status::Optimal
 		// iterations
 	}
 
+	// The dual simplex method. Starts from an optimal objective row and keeps
+	// it optimal while pivoting out the basic var furthest outside its
+	// bounds, until every basic var is within them. A basic var above its
+	// upper bound is complemented first, so that it too leaves below zero.
+	fn dual_optimize(&mut self) -> Result<(), Status> {
+		let rhs_index = self.tableau.cols() - 1;
+		loop {
+			let mut leaving_row = 0;
+			let mut worst = self.options.tolerances.primal_feasibility;
+			for row in 1 .. self.tableau.rows() {
+				if let Some(col) = self.basis[row] {
+					let value = self.tableau[[row, rhs_index]];
+					let violation = (-value).max(value - self.upper[col]);
+					if violation > worst {
+						leaving_row = row;
+						worst = violation;
+					}
+				}
+			}
+			if leaving_row == 0 {
+				return Ok(());
+			}
+			if let Some(status) = self.check_limits() {
+				return Err(status);
+			}
+
+			let leaving = self.basis[leaving_row].expect("Leaving row has a basic var");
+			if self.tableau[[leaving_row, rhs_index]] > 0. {
+				self.complement(leaving);
+				for col in 1 .. self.tableau.cols() {								// Restoring the unit coeff of the basic var
+					self.tableau[[leaving_row, col]] = -self.tableau[[leaving_row, col]];
+				}
+			}
+
+			// The entering var is the one whose reduced cost limits the pivot,
+			// keeping every reduced cost non-negative
+			let mut entering = None;
+			let mut min_ratio = INFINITY;
+			for col in 1 .. rhs_index {
+				let coeff = self.tableau[[leaving_row, col]];
+				if self.is_basic(col) || coeff >= -self.options.tolerances.pivot {
+					continue;
+				}
+				let ratio = self.tableau[[0, col]].max(0.) / -coeff;
+				if ratio < min_ratio {
+					entering = Some(col);
+					min_ratio = ratio;
+				}
+			}
+
+			match entering {
+				Some(col) => self.pivot(leaving_row, col),
+				None => return Err(Status::Infeasible)								// The row has no non-negative solution
+			}
+			self.iterations += 1;
+			if !self.is_finite() {
+				return Err(Status::NumericalFailure);
+			}
+		}
+	}
+
+	// Returns the status to stop with if a limit has been reached
+	fn check_limits(&self) -> Option<Status> {
+		if let Some(ref cancel) = self.options.cancel {
+			if cancel.is_cancelled() {
+				return Some(Status::Cancelled);
+			}
+		}
+
+		if let Some(max_iterations) = self.options.max_iterations {
+			if self.iterations >= max_iterations {
+				return Some(Status::IterationLimit);
+			}
+		}
+
+		match self.deadline {
+			Some(deadline) if Instant::now() >= deadline => Some(Status::TimeLimit),
+			_ => None
+		}
+	}
+
+	// Objective value of the lower bounds, which the tableau is shifted by
+	fn get_lower_objective(&self) -> f64 {
+		self.lp.c.iter().zip(self.lp.lower.iter()).map(|(c, lower)| c * lower).sum()
+	}
+
 	fn get_objective(&self) -> f64 {
 		unsafe {
 			return *self.tableau.get_unchecked([0, self.tableau.cols() - 1]);
 		}
 	}
 
-	fn find_bfs(&mut self) -> bool {
+	// Returns the status that prevented a bfs from being found on failure
+	fn find_bfs(&mut self) -> Result<(), Status> {
 		// println!("find_bfs");
 		unsafe {
 			match self.find_unspanned_rows() {
@@ -344,15 +1327,25 @@ impl SimplexSolver {
 					// println!("!! Unspanned rows found. Entering Phase I");
 					// println!("{:?}", &unspanned_rows);
 					let mut phase_one = self.generate_phase_one(&unspanned_rows);
-					phase_one.find_bfs();
-					let _ = phase_one.optimize();
+					let start_basis = phase_one.basis.clone();
+					phase_one.find_bfs()?;
+					let phase_one_status = phase_one.optimize();
+					self.iterations = phase_one.iterations;
+					match phase_one_status {
+						Status::Optimal => {},
+						Status::Unbounded => return Err(Status::NumericalFailure),	// Phase I is bounded below by 0
+						status => return Err(status)
+					}
 					
 					let phase_one_obj = phase_one.get_objective();					// If the objective of the optmized Phase I problem
 					// print_matrix(&phase_one.tableau);
-					if !(  relative_eq!(phase_one_obj,  0., epsilon = 0.0000001) 	// is non-zero, then no bfs exists (problem is infeasible)
-						|| relative_eq!(phase_one_obj, -0., epsilon = 0.0000001)) { // kinda hacky way of testing due to f64 precision											
-						return false
+					if phase_one_obj.abs() > self.options.tolerances.primal_feasibility {	// is non-zero, then no bfs exists (problem is infeasible)
+						self.certificate = Some(self.get_farkas_ray(&phase_one, &start_basis));
+						return Err(Status::Infeasible)
 					} else {														// Bfs exists. Converting to Phase II by copying over
+						let first_artificial = self.tableau.cols() - 1;
+						phase_one.drive_out_artificials(first_artificial);
+
 						for row in 1 .. self.tableau.rows() {						// new bfs
 							for col in 0 .. self.tableau.cols() - 1 {
 								*self.tableau.get_unchecked_mut([row, col]) =
@@ -361,17 +1354,87 @@ impl SimplexSolver {
 							let rhs_index = self.tableau.cols() - 1;
 							*self.tableau.get_unchecked_mut([row, rhs_index]) =
 									*phase_one.tableau.get_unchecked([row, phase_one.tableau.cols() - 1]);
+
+							self.basis[row] = match phase_one.basis[row] {			// Rows still spanned by an artificial var are
+								Some(col) if col < first_artificial => Some(col),	// redundant and are left unspanned
+								_ => None
+							};
 						}
+						self.complemented[.. first_artificial].copy_from_slice(&phase_one.complemented[.. first_artificial]);
 					}
 					// println!("!! Leaving Phase I");
 				}
 			}
 
 			// print_matrix(&self.tableau);
-			self.write_obj_in_nb_vars();											// Tableau fully spanned, now want to write objective 
+			self.write_obj_in_nb_vars()?;											// Tableau fully spanned, now want to write objective 
 																					// function in terms of non-basic vars
 			// print_matrix(&self.tableau);
-			true
+			Ok(())
+		}
+	}
+
+	// The starting Phase I basis is a unit column per row, so the final
+	// Phase I objective row holds the multipliers pi of the rows there: its
+	// entry in a row's starting column is pi_i less that column's cost,
+	// negated if the column is complemented. Then pi^T b' is the negative
+	// Phase I optimum plus the least value of pi^T A' x over the shifted
+	// bounds, where A' and b' are A and the shifted b with the negative-RHS
+	// rows negated.
+	fn get_farkas_ray(&self, phase_one: &SimplexSolver, start_basis: &Vec<Option<usize>>) -> Certificate {
+		let first_artificial = self.tableau.cols() - 1;
+		let zero = self.options.tolerances.zero;
+		let farkas = (1 .. self.tableau.rows()).map(|row| {
+			let y = match start_basis[row] {
+				Some(col) => {
+					let cost = if col >= first_artificial { -1. } else { 0. };
+					let sign = if phase_one.complemented[col] { -1. } else { 1. };
+					sign * phase_one.tableau[[0, col]] + cost
+				},
+				None => 0.
+			};
+			let sign = if SimplexSolver::get_shifted_rhs(&self.lp, row - 1) < 0. { -1. } else { 1. };
+			if y.abs() < zero { 0. } else { sign * y }
+		}).collect();
+
+		Certificate::Infeasible { farkas: farkas }
+	}
+
+	// Moving along col by one unit moves each basic var by minus its
+	// coefficient in col, none of which are positive when no pivot row exists
+	fn get_unbounded_ray(&self, col: usize) -> Certificate {
+		let mut ray = vec![0.; self.tableau.cols() - 2];
+		ray[col - 1] = 1.;
+		for row in 1 .. self.tableau.rows() {
+			if let Some(basic) = self.basis[row] {
+				let sign = if self.complemented[basic] { -1. } else { 1. };
+				ray[basic - 1] = sign * (-self.tableau[[row, col]]).max(0.);
+			}
+		}
+
+		Certificate::Unbounded {
+			point: self.get_basic_feasible_solution(),
+			ray: ray,
+			entering: col - 1
+		}
+	}
+
+	// Pivots artificial vars left in the basis at zero level out of it, so
+	// that Phase II starts from a basis of real vars only
+	fn drive_out_artificials(&mut self, first_artificial: usize) {
+		for row in 1 .. self.tableau.rows() {
+			match self.basis[row] {
+				Some(col) if col >= first_artificial => {},
+				_ => continue
+			}
+
+			let replacement = (1 .. first_artificial).find(|&col| unsafe {
+				!self.is_basic(col) && self.tableau.get_unchecked([row, col]).abs() > self.options.tolerances.pivot
+			});
+
+			if let Some(col) = replacement {
+				self.pivot(row, col);
+			}
 		}
 	}
 
@@ -382,6 +1445,11 @@ impl SimplexSolver {
 			let mut phase_one = Matrix::new(new_rows, 								
 											new_cols, 
 											vec![0.; new_rows * new_cols]);
+			let mut basis = self.basis.clone();
+			let mut upper = self.upper[.. self.tableau.cols() - 1].to_vec();		// Artificial vars and the RHS are unbounded
+			upper.resize(new_cols, INFINITY);
+			let mut complemented = self.complemented[.. self.tableau.cols() - 1].to_vec();
+			complemented.resize(new_cols, false);
 
 			*phase_one.get_unchecked_mut([0, 0]) = 1.;
 
@@ -399,17 +1467,29 @@ impl SimplexSolver {
 			for &row in unspanned_rows {											// column specific to Phase I. Objective coeff for each Phase I
 				*phase_one.get_unchecked_mut([row, col]) = 1.;						// artificial var is -1 b/c want to minimize them out of the
 				*phase_one.get_unchecked_mut([0, col]) = -1.;						// basis
+				basis[row] = Some(col);
 				col += 1;															
 			}
 			
 			SimplexSolver {
 				tableau: phase_one,
-				lp: self.lp.clone()													// Don't really need LP here but makes it simpler to keep as
-			}																		// SimplexSolver struct
+				lp: self.lp.clone(),												// Don't really need LP here but makes it simpler to keep as
+				options: self.options.clone(),										// SimplexSolver struct
+				warm_start: None,
+				pricing: self.options.pricing,
+				lex_cols: vec![],
+				basis: basis,
+				iterations: self.iterations,
+				deadline: self.deadline,
+				certificate: None,
+				upper: upper,
+				complemented: complemented,
+				solved: false
+			}
 		}
 	}
 
-	fn write_obj_in_nb_vars(&mut self) {
+	fn write_obj_in_nb_vars(&mut self) -> Result<(), Status> {
 		// println!("Starting write_obj_in_nb_vars");
 		unsafe {
 			let mut obj_function = Vec::with_capacity(self.tableau.cols());			// Keeping same size as top row to make indexing simpler
@@ -420,21 +1500,24 @@ impl SimplexSolver {
 				*self.tableau.get_unchecked_mut([0, col]) = 0.;						// Then setting the entry to 0
 			}
 
+			let mut offset = 0.;													// A complemented var contributes its coeff times
+			for col in 1 .. self.tableau.cols() - 1 {								// its upper bound, and the negated coeff per unit
+				if self.complemented[col] {
+					offset += obj_function[col] * self.upper[col];
+					obj_function[col] = -obj_function[col];
+				}
+			}
+
 			for col in 1 .. self.tableau.cols() - 1 {								// Can ignore first and last elements of obj row
 				let obj_coeff = obj_function[col];
 				if !self.is_basic(col) {
 					*self.tableau.get_unchecked_mut([0, col]) -= obj_coeff;			// Basic vars are already written in terms of themselves
 				} else {
-					let mut row = 0;												// Finding the row that the basic var spans					
-					for r in 1 .. self.tableau.rows() {
-						if *self.tableau.get_unchecked([r, col]) == 1. {
-							row = r; 
-							break;
-						}
-					}
+					let row = self.get_basic_row(col);								// Finding the row that the basic var spans
 
-					if row == 0 {													// Row cannot be 0 since variable is non-basic
-						panic!("Row cannot be 0");									// Should never happen, but cant hurt to check
+					let unit = *self.tableau.get_unchecked([row, col]);				// Basic var must have a unit coeff in its row
+					if !((unit - 1.).abs() <= self.options.tolerances.pivot) {
+						return Err(Status::NumericalFailure);						// Should never happen, but cant hurt to check
 					}
 
 					for c in 1 .. self.tableau.cols() {							// Iterate through non-basic variables again and 
@@ -447,8 +1530,52 @@ impl SimplexSolver {
 
 				}
 			}
+
+			let rhs_index = self.tableau.cols() - 1;
+			*self.tableau.get_unchecked_mut([0, rhs_index]) += offset;
+		}
+
+		Ok(())
+	}
+}
+
+// Flags the columns that take integer values in every integral solution
+fn integral_columns(lp: &Lp) -> Vec<bool> {
+	let is_integer = |value: f64| value.fract() == 0.;
+	let split: Vec<usize> = lp.substitutions.iter().filter_map(|substitution| match *substitution {
+		Substitution::Split { positive, .. } => Some(positive),
+		_ => None
+	}).collect();
+	let has_integral_bounds = |col: usize| is_integer(lp.lower[col]) && (lp.upper[col] == INFINITY || is_integer(lp.upper[col]));
+
+	let mut integral: Vec<bool> = (0 .. lp.A.cols())
+		.map(|col| lp.integers.contains(&col) && !split.contains(&col) && has_integral_bounds(col))
+		.collect();
+
+	// A slack or excess column is integral once the rest of its row is,
+	// which may only follow from other slack columns, e.g. those of cuts
+	let mut changed = true;
+	while changed {
+		changed = false;
+		for col in 0 .. lp.A.cols() {
+			let entries: Vec<(usize, f64)> = lp.A.column(col).collect();
+			if integral[col] || entries.len() != 1 || entries[0].1.abs() != 1. {
+				continue;
+			}
+
+			let row = entries[0].0;
+			let rest_integral = (0 .. lp.A.cols()).all(|other| {
+				let coeff = lp.A[[row, other]];
+				other == col || coeff == 0. || (integral[other] && is_integer(coeff))
+			});
+			if rest_integral && is_integer(lp.b[row]) && has_integral_bounds(col) {
+				integral[col] = true;
+				changed = true;
+			}
 		}
 	}
+
+	integral
 }
 
 #[cfg(test)]
@@ -479,19 +1606,29 @@ mod solve_tests {
 		vars.push("x3".to_string());
 		vars.push("x4".to_string());
 		let Lp1 = Lp {
-				A: A.clone(),
+				A: A.clone().into(),
 				b: b.clone(),
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars.clone(),
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 5],
+				upper: vec![INFINITY; 5],
+				substitutions: vec![],
+				integers: vec![],
 				num_artificial_vars: 0,
 		};
 		let Lp2 = Lp {
-				A: A,
+				A: A.into(),
 				b: b,
 				c: c2,
 				optimization: Optimization::Max,
 				vars: vars,
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 5],
+				upper: vec![INFINITY; 5],
+				substitutions: vec![],
+				integers: vec![],
 				num_artificial_vars: 0
 		};
 		let not_optimal = SimplexSolver::new(Lp1);
@@ -512,21 +1649,20 @@ mod solve_tests {
 	}
 
 	#[test]
-	#[should_panic]
 	fn is_basic_z_test() {
 	    let lp = create_dummy_lp();
 		let simplex_1 = SimplexSolver::new(lp);
 
-	    assert!(simplex_1.is_basic(0));
+	    assert!(!simplex_1.is_basic(0));
 	}
 
 	#[test]
-	#[should_panic]
 	fn is_basic_rhs_test() {
 	    let lp = create_dummy_lp();
 		let simplex = SimplexSolver::new(lp);
 
-	    assert!(simplex.is_basic(5));
+	    assert!(!simplex.is_basic(5));
+	    assert!(!simplex.is_basic(6));
 	}
 
 	#[test]
@@ -559,16 +1695,324 @@ mod solve_tests {
 		vars.push("x3".to_string());
 		vars.push("x4".to_string());
 		let lp = Lp {
-				A: A.clone(),
+				A: A.clone().into(),
 				b: b.clone(),
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars.clone(),
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 5],
+				upper: vec![INFINITY; 5],
+				substitutions: vec![],
+				integers: vec![],
 				num_artificial_vars: 0
 		};
 		let simplex = SimplexSolver::new(lp);
 
-	   	assert_eq!(simplex.choose_pivot_col(), 1);
+	   	assert_eq!(simplex.choose_pivot_col(), Some(1));
+	}
+
+	#[test]
+	fn basis_tracking_test() {
+		let lp = create_dummy_lp();
+		let mut simplex = SimplexSolver::new(lp);
+		assert_eq!(simplex.basis, vec![Some(0), Some(3), Some(4)]);
+
+		simplex.pivot(1, 1);
+		assert_eq!(simplex.basis, vec![Some(0), Some(1), Some(4)]);
+		assert!(simplex.is_basic(1));
+		assert!(!simplex.is_basic(3));
+		assert_eq!(simplex.get_basic_row(1), 1);
+		assert_eq!(simplex.get_basic_feasible_solution(), vec![2., 0., 0., 1.]);
+	}
+
+	#[test]
+	fn get_basis_test() {
+		let lp = create_dummy_lp();
+		let mut simplex = SimplexSolver::new(lp.clone());
+		simplex.pivot(1, 1);
+
+		let basis = simplex.get_basis();
+		assert_eq!(basis.statuses, vec![VarStatus::Basic, VarStatus::AtLower, VarStatus::AtLower, VarStatus::Basic]);
+		assert_eq!(basis.basic_vars(), vec![0, 3]);
+
+		let mut warm = SimplexSolver::new(lp.clone());
+		warm.load_basis(&basis);
+		assert_eq!(warm.basis, simplex.basis);
+		assert_eq!(warm.get_basic_feasible_solution(), simplex.get_basic_feasible_solution());
+
+		let mut infeasible = SimplexSolver::new(lp);					// x1 and x3 basic gives x3 < 0
+		let cold_tableau = infeasible.tableau.clone();
+		infeasible.load_basis(&Basis {
+			statuses: vec![VarStatus::Basic, VarStatus::AtLower, VarStatus::Basic, VarStatus::AtLower]
+		});
+		assert_matrix_eq!(infeasible.tableau, cold_tableau);
+	}
+
+	#[test]
+	fn dual_simplex_test() {
+		// max x + y s.t. x + 2y <= 4, 3x + y <= 6 is optimal at x = 1.6, y = 1.2
+		let lp = Lp {
+				A: matrix![1., 2., 1., 0.;
+						   3., 1., 0., 1.].into(),
+				b: vec![4., 6.],
+				c: vec![1., 1., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x", "y", "s1", "s2"].iter().map(|v| v.to_string()).collect(),
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 4],
+				upper: vec![INFINITY; 4],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 2
+		};
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_approx_eq!(solution.objective.unwrap(), 2.8);
+		let mut statuses = solution.basis.unwrap().statuses;
+		statuses.push(VarStatus::Basic);
+
+		// Adding x + y <= 2 cuts off the optimum but keeps the basis dual feasible
+		let mut cut = lp.clone();
+		cut.A = matrix![1., 2., 1., 0., 0.;
+						3., 1., 0., 1., 0.;
+						1., 1., 0., 0., 1.].into();
+		cut.b.push(2.);
+		cut.c.push(0.);
+		cut.vars.push("s3".to_string());
+		cut.constraints.push("c3".to_string());
+		cut.lower.push(0.);
+		cut.upper.push(INFINITY);
+		cut.num_artificial_vars = 3;
+
+		let mut warm = SimplexSolver::new(cut.clone());
+		assert!(warm.load_basis(&Basis { statuses: statuses.clone() }));
+		assert!(warm.is_optimal());
+
+		let mut solver = SimplexSolver::new(cut.clone());
+		solver.warm_start = Some(Basis { statuses: statuses.clone() });
+		let solution = solver.solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 2.);
+		assert_eq!(solution.iterations, 1);
+
+		// Asking for x + y >= 5 instead leaves no feasible point
+		let mut infeasible = cut;
+		infeasible.A.set(2, 4, -1.);
+		infeasible.b[2] = 5.;
+		let mut solver = SimplexSolver::new(infeasible.clone());
+		solver.warm_start = Some(Basis { statuses: statuses });
+		let solution = solver.solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&infeasible, 1e-9));
+	}
+
+	#[test]
+	fn reoptimize_test() {
+		let lp = Lp {
+				A: matrix![1., 2., 1., 0.;
+						   3., 1., 0., 1.].into(),
+				b: vec![4., 6.],
+				c: vec![1., 1., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x", "y", "s1", "s2"].iter().map(|v| v.to_string()).collect(),
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 4],
+				upper: vec![INFINITY; 4],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 2
+		};
+		let mut solver = SimplexSolver::new(lp);
+		let solution = solver.reoptimize().unwrap();
+		assert_approx_eq!(solution.objective.unwrap(), 2.8);
+		assert_eq!(solver.warm_start, solution.basis);
+
+		// x + 2y <= 1 moves the optimum to x = 1, y = 0
+		solver.set_rhs(0, 1.).unwrap();
+		let solution = solver.reoptimize().unwrap();
+		assert_approx_eq!(solution.objective.unwrap(), 1.);
+		assert_eq!(solution.iterations, 1);
+
+		// Fixing y = 0.5 leaves x = 0
+		solver.add_constraint("fix", vec![0., 1., 0., 0.], Relation::Equal, 0.5).unwrap();
+		let solution = solver.reoptimize().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 0.5);
+		assert_eq!(solution.lp.vars[4], "slack_2");
+		assert_eq!(solution.lp.upper[4], 0.);
+
+		// x >= 1.5 is then out of reach
+		solver.add_constraint("low", vec![1., 0., 0., 0., 0.], Relation::GreaterThanOrEqual, 1.5).unwrap();
+		let solution = solver.reoptimize().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&solution.lp, 1e-9));
+
+		assert_eq!(solver.add_constraint("short", vec![1.], Relation::Equal, 0.),
+			Err(SolveError::CoefficientsLength { cols: 6, len: 1 }));
+		assert_eq!(solver.set_rhs(4, 0.), Err(SolveError::InvalidRow { row: 4, rows: 4 }));
+	}
+
+	#[test]
+	fn reoptimize_bounds_test() {
+		// max 3x + 2y s.t. x + y <= 6 with x in [1, 4] and y in [0, 3] ends
+		// with x at its upper bound, so the kept tableau holds it complemented.
+		// Minimizing -3x - 2y must take the same steps.
+		for &(ref optimization, sign) in &[(Optimization::Max, 1.), (Optimization::Min, -1.)] {
+			let lp = Lp {
+					A: matrix![1., 1., 1.].into(),
+					b: vec![6.],
+					c: vec![3. * sign, 2. * sign, 0.],
+					optimization: optimization.clone(),
+					vars: vec!["x", "y", "s1"].iter().map(|v| v.to_string()).collect(),
+					constraints: vec!["c1".to_string()],
+					lower: vec![1., 0., 0.],
+					upper: vec![4., 3., INFINITY],
+					substitutions: vec![],
+					integers: vec![],
+					num_artificial_vars: 1
+			};
+			let mut solver = SimplexSolver::new(lp);
+			let solution = solver.reoptimize().unwrap();
+			assert_approx_eq!(solution.objective.unwrap(), 16. * sign);
+			assert!(solver.solved);
+			assert!(solver.complemented[1]);
+
+			let check = |solver: &mut SimplexSolver, objective: f64| {
+				let solution = solver.reoptimize().unwrap();
+				let expected = SimplexSolver::new(solution.lp.clone()).solve().unwrap();
+				assert_eq!(solution.status, Status::Optimal);
+				assert_approx_eq!(solution.objective.unwrap(), objective * sign);
+				assert_approx_eq!(expected.objective.unwrap(), objective * sign);
+				for (value, expected) in solution.values.unwrap().iter().zip(expected.values.unwrap()) {
+					assert_approx_eq!(value, expected);
+				}
+				assert!(solver.solved);
+				solution.iterations
+			};
+
+			// 2x + y <= 9 moves the optimum to x = 3, y = 3 in one dual pivot
+			solver.add_constraint("c2", vec![2., 1., 0.], Relation::LessThanOrEqual, 9.).unwrap();
+			assert_eq!(check(&mut solver, 15.), 1);
+
+			// x + y <= 5 then gives x = 4, y = 1
+			solver.set_rhs(0, 5.).unwrap();
+			check(&mut solver, 14.);
+
+			// x - y >= 3.5 gives x = 4, y = 0.5
+			solver.add_constraint("c3", vec![1., -1., 0., 0.], Relation::GreaterThanOrEqual, 3.5).unwrap();
+			check(&mut solver, 13.);
+
+			// Loosening x + y <= 8 needs no pivots
+			solver.set_rhs(0, 8.).unwrap();
+			assert_eq!(check(&mut solver, 13.), 0);
+		}
+	}
+
+	#[test]
+	fn gomory_cuts_test() {
+		// max 5a + 4b s.t. 6a + 4b <= 24, a + 2b <= 6 is optimal at a = 3, b = 1.5
+		let mut lp = Lp {
+				A: matrix![6., 4., 1., 0.;
+						   1., 2., 0., 1.].into(),
+				b: vec![24., 6.],
+				c: vec![5., 4., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["a", "b", "s1", "s2"].iter().map(|v| v.to_string()).collect(),
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 4],
+				upper: vec![INFINITY; 4],
+				substitutions: vec![],
+				integers: vec![0, 1],
+				num_artificial_vars: 2
+		};
+		let solver = SimplexSolver::new(lp.clone());
+		let solution = solver.solve().unwrap();
+		let optimum = solution.values.unwrap();
+		let basis = solution.basis.unwrap();
+		let cuts = solver.gomory_cuts(&basis, 1e-6).unwrap();
+		assert!(!cuts.is_empty());
+
+		// Every cut separates the optimum but keeps all integral points
+		let lhs = |cut: &Cut, x: &Vec<f64>| cut.coefficients.iter().zip(x.iter()).map(|(coeff, x)| coeff * x).sum::<f64>();
+		for cut in &cuts {
+			assert!(cut.coefficients.iter().all(|coeff| coeff.fract() == 0.));
+			assert!(lhs(cut, &optimum) > cut.rhs + 1e-6);
+			for a in 0 .. 5 {
+				for b in 0 .. 4 {
+					let (a, b) = (a as f64, b as f64);
+					let point = vec![a, b, 24. - 6. * a - 4. * b, 6. - a - 2. * b];
+					if point[2] >= 0. && point[3] >= 0. {
+						assert!(lhs(cut, &point) <= cut.rhs + 1e-9);
+					}
+				}
+			}
+		}
+
+		// A basis other than the optimum gives no cuts
+		let slack = Basis { statuses: vec![VarStatus::AtLower, VarStatus::AtLower, VarStatus::Basic, VarStatus::Basic] };
+		assert_eq!(solver.gomory_cuts(&slack, 1e-6), Ok(vec![]));
+
+		// Neither does a continuous b, which the row of b depends on
+		lp.integers = vec![0];
+		let solver = SimplexSolver::new(lp);
+		assert_eq!(solver.gomory_cuts(&basis, 1e-6), Ok(vec![]));
+	}
+
+	#[test]
+	fn tolerances_test() {
+		let mut lp = create_dummy_lp();
+		lp.A.set(0, 2, 1. + 1e-14);									// Round-off sized error still spans row 1
+		lp.c = vec![-1e-9, -1., 0., 0.];							// Within optimality tolerance of 0
+		let simplex = SimplexSolver::new(lp);
+
+		assert_eq!(simplex.basis, vec![Some(0), Some(3), Some(4)]);
+		assert_eq!(simplex.choose_pivot_col(), Some(2));
+
+		let mut strict = simplex.clone();
+		strict.options.tolerances.optimality = 0.;
+		strict.pricing = PricingRule::Bland;
+		assert_eq!(strict.choose_pivot_col(), Some(1));
+	}
+
+	#[test]
+	fn pricing_rule_test() {
+		let mut lp = create_dummy_lp();
+		lp.c = vec![-1., -3., 0., 0.];
+		let mut simplex = SimplexSolver::new(lp);
+
+		assert_eq!(simplex.choose_pivot_col(), Some(2));
+		simplex.pricing = PricingRule::Bland;
+		assert_eq!(simplex.choose_pivot_col(), Some(1));
+	}
+
+	#[test]
+	fn ratio_test_tie_test() {
+		// Rows 1 and 2 tie on column 1; row 2 is spanned by x3, row 1 by x4
+		let A = matrix![1., 0., 0., 1.;
+						2., 1., 1., 0.];
+		let lp = Lp {
+				A: A.into(),
+				b: vec![0., 0.],
+				c: vec![1., 1., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x1".to_string(), "x2".to_string(), "x3".to_string(), "x4".to_string()],
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 4],
+				upper: vec![INFINITY; 4],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 0
+		};
+		let mut simplex = SimplexSolver::new(lp);
+		assert_eq!(simplex.choose_pivot_row(1), 1);
+
+		simplex.pricing = PricingRule::Bland;
+		assert_eq!(simplex.choose_pivot_row(1), 2);
+
+		simplex.pricing = PricingRule::Dantzig;
+		simplex.options.ratio_test = RatioTest::Lexicographic;
+		simplex.lex_cols = vec![4, 3];
+		assert_eq!(simplex.choose_pivot_row(1), 2);
 	}
 
 	#[test]
@@ -649,13 +2093,313 @@ mod solve_tests {
 		let lp = create_dummy_lp();
 		let simplex = SimplexSolver::new(lp);
 		let expected = vec![0., 0., 4., 3.];
-		let solution = simplex.solve();
+		let solution = simplex.solve().unwrap();
 		print!("{:}", &solution);
 		assert_eq!(solution.status, Status::Optimal);
 		assert_eq!(solution.values.unwrap(), expected);
 		assert_eq!(solution.objective.unwrap(), 0.);
 	}
 
+	#[test]
+	fn invalid_lp_test() {
+		let mut lp = create_dummy_lp();
+		lp.b.push(1.);
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::RhsLength { rows: 2, len: 3 }));
+
+		let mut lp = create_dummy_lp();
+		lp.c.pop();
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::ObjectiveLength { cols: 4, len: 3 }));
+
+		let mut lp = create_dummy_lp();
+		lp.vars.pop();
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::VariablesLength { cols: 4, len: 3 }));
+
+		let mut lp = create_dummy_lp();
+		lp.constraints.pop();
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::ConstraintsLength { rows: 2, len: 1 }));
+
+		let mut lp = create_dummy_lp();
+		lp.A.set(1, 2, ::std::f64::NAN);
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::NonFiniteEntry { location: "A[1, 2]".to_string() }));
+
+		let mut lp = create_dummy_lp();
+		lp.c[0] = INFINITY;
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::NonFiniteEntry { location: "c[0]".to_string() }));
+
+		let mut lp = create_dummy_lp();
+		lp.num_artificial_vars = 3;
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::InvalidArtificialVars {
+			num_artificial_vars: 3,
+			rows: 2,
+			cols: 4
+		}));
+	}
+
+	#[test]
+	fn duals_test() {
+		// max 3x + 5y s.t. x <= 4, 2y <= 12, 3x + 2y <= 18
+		let A = matrix![1., 0., 1., 0., 0.;
+						0., 2., 0., 1., 0.;
+						3., 2., 0., 0., 1.];
+		let mut lp = Lp {
+				A: A.into(),
+				b: vec![4., 12., 18.],
+				c: vec![3., 5., 0., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x".to_string(), "y".to_string(), "s1".to_string(), "s2".to_string(), "s3".to_string()],
+				constraints: vec!["c1".to_string(), "c2".to_string(), "c3".to_string()],
+				lower: vec![0.; 5],
+				upper: vec![INFINITY; 5],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 3
+		};
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		let expected_duals = vec![0., 1.5, 1.];
+		let duals = solution.duals.clone().unwrap();
+		for i in 0 .. expected_duals.len() {
+			assert_approx_eq!(duals[i], expected_duals[i]);
+		}
+		let expected_costs = vec![0., 0., 0., -1.5, -1.];
+		let costs = solution.reduced_costs.clone().unwrap();
+		for i in 0 .. expected_costs.len() {
+			assert_approx_eq!(costs[i], expected_costs[i]);
+		}
+		assert_approx_eq!(solution.dual("c2").unwrap(), 1.5);
+		assert_approx_eq!(solution.reduced_cost("s3").unwrap(), -1.);
+		assert_eq!(solution.dual("missing"), None);
+
+		// Minimizing the negated objective flips every sign
+		lp.c = lp.c.iter().map(|c| -c).collect();
+		lp.optimization = Optimization::Min;
+		let solution = SimplexSolver::new(lp).solve().unwrap();
+		let duals = solution.duals.unwrap();
+		for i in 0 .. expected_duals.len() {
+			assert_approx_eq!(duals[i], -expected_duals[i]);
+		}
+		let costs = solution.reduced_costs.unwrap();
+		for i in 0 .. expected_costs.len() {
+			assert_approx_eq!(costs[i], -expected_costs[i]);
+		}
+	}
+
+	#[test]
+	fn sensitivity_test() {
+		let A = matrix![1., 0., 1., 0., 0.;
+						0., 2., 0., 1., 0.;
+						3., 2., 0., 0., 1.];
+		let mut lp = Lp {
+				A: A.into(),
+				b: vec![4., 12., 18.],
+				c: vec![3., 5., 0., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x".to_string(), "y".to_string(), "s1".to_string(), "s2".to_string(), "s3".to_string()],
+				constraints: vec!["c1".to_string(), "c2".to_string(), "c3".to_string()],
+				lower: vec![0.; 5],
+				upper: vec![INFINITY; 5],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 3
+		};
+		let report = SimplexSolver::new(lp.clone()).solve().unwrap().sensitivity.unwrap();
+		let expected_objective = vec![(0., 7.5), (2., INFINITY), (-4.5, 3.), (NEG_INFINITY, 1.5), (NEG_INFINITY, 1.)];
+		for (range, &(lower, upper)) in report.objective_ranges.iter().zip(expected_objective.iter()) {
+			assert_range_eq(range, lower, upper);
+		}
+		let expected_rhs = vec![(2., INFINITY), (6., 18.), (12., 24.)];
+		for (range, &(lower, upper)) in report.rhs_ranges.iter().zip(expected_rhs.iter()) {
+			assert_range_eq(range, lower, upper);
+		}
+		assert_eq!(report.rhs_range("c2"), Some(Range { lower: 6., upper: 18. }));
+
+		// Minimizing the negated objective mirrors the objective ranges
+		lp.c = lp.c.iter().map(|c| -c).collect();
+		lp.optimization = Optimization::Min;
+		let report = SimplexSolver::new(lp).solve().unwrap().sensitivity.unwrap();
+		for (range, &(lower, upper)) in report.objective_ranges.iter().zip(expected_objective.iter()) {
+			assert_range_eq(range, -upper, -lower);
+		}
+		for (range, &(lower, upper)) in report.rhs_ranges.iter().zip(expected_rhs.iter()) {
+			assert_range_eq(range, lower, upper);
+		}
+	}
+
+	fn assert_range_eq(range: &Range, lower: f64, upper: f64) {
+		for &(actual, expected) in [(range.lower, lower), (range.upper, upper)].iter() {
+			if expected.is_infinite() {
+				assert_eq!(actual, expected);
+			} else {
+				assert_approx_eq!(actual, expected);
+			}
+		}
+	}
+
+	#[test]
+	fn certificate_test() {
+		// x + s = 5 and x - e = 10 cannot both hold
+		let lp = Lp {
+				A: matrix![1., 1., 0.;
+						   1., 0., -1.].into(),
+				b: vec![5., 10.],
+				c: vec![1., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x".to_string(), "s".to_string(), "e".to_string()],
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 3],
+				upper: vec![INFINITY; 3],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 1
+		};
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+
+		let mut simplex = SimplexSolver::new(lp.clone());
+		simplex.warm_start = Some(Basis { statuses: vec![VarStatus::Basic, VarStatus::AtLower, VarStatus::AtLower] });
+		let solution = simplex.solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+
+		let bogus = Certificate::Infeasible { farkas: vec![1., 1.] };
+		assert!(!bogus.verify(&lp, 1e-9));
+		let short = Certificate::Infeasible { farkas: vec![-1.] };
+		assert!(!short.verify(&lp, 1e-9));
+	}
+
+	#[test]
+	fn bounds_test() {
+		// max 3x + 5y s.t. 3x + 2y <= 18, x <= 4, y <= 6: y flips to its bound
+		let mut lp = Lp {
+				A: matrix![3., 2., 1.].into(),
+				b: vec![18.],
+				c: vec![3., 5., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x".to_string(), "y".to_string(), "s".to_string()],
+				constraints: vec!["c1".to_string()],
+				lower: vec![0.; 3],
+				upper: vec![4., 6., INFINITY],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 1
+		};
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 36.);
+		let values = solution.values.clone().unwrap();
+		assert_approx_eq!(values[0], 2.);
+		assert_approx_eq!(values[1], 6.);
+		let basis = solution.basis.clone().unwrap();
+		assert_eq!(basis.statuses, vec![VarStatus::Basic, VarStatus::AtUpper, VarStatus::AtLower]);
+		assert_approx_eq!(solution.duals.clone().unwrap()[0], 1.);
+		assert_approx_eq!(solution.reduced_costs.clone().unwrap()[1], 3.);
+
+		let report = solution.sensitivity.unwrap();
+		assert_range_eq(&report.objective_ranges[0], 0., 7.5);
+		assert_range_eq(&report.objective_ranges[1], 2., INFINITY);
+		assert_range_eq(&report.rhs_ranges[0], 12., 24.);
+
+		// Starting from the final basis needs no pivots
+		let mut simplex = SimplexSolver::new(lp.clone());
+		simplex.warm_start = Some(basis);
+		let warm = simplex.solve().unwrap();
+		assert_eq!(warm.iterations, 0);
+		assert_approx_eq!(warm.objective.unwrap(), 36.);
+
+		// Shifted lower bounds: max 3x + 5y with x >= 3 leaves 4.5 for 2y
+		lp.lower[0] = 3.;
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		let values = solution.values.unwrap();
+		assert_approx_eq!(values[0], 3.);
+		assert_approx_eq!(values[1], 4.5);
+		assert_approx_eq!(solution.objective.unwrap(), 31.5);
+
+		// x >= 7 cannot fit under 3x + 2y <= 18
+		lp.lower[0] = 7.;
+		lp.upper[0] = 8.;
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+	}
+
+	#[test]
+	fn leave_at_upper_test() {
+		// max x s.t. x - y <= 1, x <= 10, y <= 20: x leaves at its bound
+		let mut lp = Lp {
+				A: matrix![1., -1., 1.].into(),
+				b: vec![1.],
+				c: vec![1., 0., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x".to_string(), "y".to_string(), "s".to_string()],
+				constraints: vec!["c1".to_string()],
+				lower: vec![0.; 3],
+				upper: vec![10., 20., INFINITY],
+				substitutions: vec![],
+				integers: vec![],
+				num_artificial_vars: 1
+		};
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_approx_eq!(solution.objective.unwrap(), 10.);
+		let values = solution.values.unwrap();
+		assert_approx_eq!(values[0], 10.);
+		assert_approx_eq!(values[1], 9.);
+		assert_eq!(solution.basis.unwrap().statuses[0], VarStatus::AtUpper);
+
+		// Without a bound on x it is y that stops at its bound
+		lp.upper[0] = INFINITY;
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_approx_eq!(solution.objective.unwrap(), 21.);
+
+		// and without either bound the Lp is unbounded
+		lp.upper[1] = INFINITY;
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+
+		let mut lp = lp;
+		lp.upper[0] = -1.;
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::InvalidBounds {
+			var: "x".to_string(),
+			lower: 0.,
+			upper: -1.
+		}));
+	}
+
+	#[test]
+	fn substitutions_test() {
+		// max x + 2y s.t. x + y <= 3, y <= 1, x free: x is split over
+		// columns 0 and 2 and column 1 holds -y
+		let mut lp = Lp {
+				A: matrix![1., -1., -1., 1.].into(),
+				b: vec![3.],
+				c: vec![1., -2., -1., 0.],
+				optimization: Optimization::Max,
+				vars: vec!["x".to_string(), "y".to_string(), "negative_0".to_string(), "slack_0".to_string()],
+				constraints: vec!["cap".to_string()],
+				lower: vec![0., -1., 0., 0.],
+				upper: vec![INFINITY; 4],
+				substitutions: vec![
+					Substitution::Split { positive: 0, negative: 2 },
+					Substitution::Negated { col: 1 }
+				],
+				integers: vec![],
+				num_artificial_vars: 1
+		};
+		let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+		assert_approx_eq!(solution.objective.unwrap(), 4.);
+		let values = solution.values.clone().unwrap();
+		assert_approx_eq!(values[0], 2.);
+		assert_approx_eq!(values[1], 1.);
+		assert_approx_eq!(solution.reduced_cost("y").unwrap(), 1.);
+
+		let report = solution.sensitivity.unwrap();
+		assert_range_eq(&report.objective_range("x").unwrap(), 0., 2.);
+		assert_range_eq(&report.objective_range("y").unwrap(), 1., INFINITY);
+
+		lp.substitutions[0] = Substitution::Split { positive: 2, negative: 2 };
+		assert_eq!(SimplexSolver::new(lp).solve(), Err(SolveError::InvalidSubstitution { col: 2 }));
+	}
+
 	fn create_dummy_lp() -> Lp {
 		let A = matrix![2., 1., 1., 0.;
 						1., 2., 0., 1.];
@@ -667,11 +2411,16 @@ mod solve_tests {
 		vars.push("x3".to_string());
 		vars.push("x4".to_string());
 		Lp {
-				A: A,
+				A: A.into(),
 				b: b,
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars,
+				constraints: vec!["c1".to_string(), "c2".to_string()],
+				lower: vec![0.; 4],
+				upper: vec![INFINITY; 4],
+				substitutions: vec![],
+				integers: vec![],
 				num_artificial_vars: 0
 		}
 	}
@@ -697,15 +2446,20 @@ mod solve_tests {
 			"slack_4".to_string(),
 		];
 		let lp = Lp {
-				A: A,
+				A: A.into(),
 				b: b,
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars,
+				constraints: vec!["c1".to_string(), "c2".to_string(), "c3".to_string(), "c4".to_string()],
+				lower: vec![0.; 7],
+				upper: vec![INFINITY; 7],
+				substitutions: vec![],
+				integers: vec![],
 				num_artificial_vars: 4
 		};
 		let simplex = SimplexSolver::new(lp);
-		let solution = simplex.solve();
+		let solution = simplex.solve().unwrap();
 		let res = solution.values.unwrap();
 		let expected = vec![4., 10., 14.];
 		assert_eq!(solution.status, Status::Optimal);
@@ -0,0 +1,248 @@
+//! Sparse LU factorization of a simplex basis, kept up to date across
+//! basis changes by product form updates.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+// One elimination step: the pivot, the multipliers that clear the rest of
+// the pivot column, and the rest of the pivot row of U
+#[derive(Debug, PartialEq, Clone)]
+struct Step {
+	row: usize,
+	col: usize,
+	pivot: f64,
+	lower: Vec<(usize, f64)>,
+	upper: Vec<(usize, f64)>
+}
+
+// A basis change at `position` to a column whose representation in the
+// previous basis is `pivot` at that position and `column` elsewhere
+#[derive(Debug, PartialEq, Clone)]
+struct Eta {
+	position: usize,
+	pivot: f64,
+	column: Vec<(usize, f64)>
+}
+
+/// Factorization `B = L U` of a square basis matrix with a product of
+/// eta matrices for each column replaced since.
+///
+/// Columns of B are numbered by their position in the basis and rows by
+/// the constraints of the Lp.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BasisFactor {
+	size: usize,
+	steps: Vec<Step>,
+	etas: Vec<Eta>
+}
+
+impl BasisFactor {
+	/// Factorizes the matrix whose columns are given as `(row, value)`
+	/// entries. Returns None if some pivot is no larger than `pivot_tolerance`.
+	/// Fill-in no larger than `zero_tolerance` is dropped.
+	///
+	/// Pivots are picked from the sparsest column left, preferring the
+	/// sparsest row among entries within a tenth of the column's largest.
+	pub fn new(columns: &Vec<Vec<(usize, f64)>>, pivot_tolerance: f64, zero_tolerance: f64) -> Option<Self> {
+		let size = columns.len();
+		let mut cols: Vec<BTreeMap<usize, f64>> = columns.iter()
+			.map(|column| column.iter().cloned().filter(|&(_, value)| value != 0.).collect())
+			.collect();
+		let mut rows: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); size];
+		for (col, column) in cols.iter().enumerate() {
+			for &row in column.keys() {
+				rows[row].insert(col);
+			}
+		}
+
+		let mut active = vec![true; size];
+		let mut steps = Vec::with_capacity(size);
+		for _ in 0 .. size {
+			let col = (0 .. size).filter(|&col| active[col]).min_by_key(|&col| cols[col].len())?;
+			let largest = cols[col].values().fold(0., |largest: f64, value| largest.max(value.abs()));
+			if largest <= pivot_tolerance {
+				return None;
+			}
+			let row = *cols[col].iter()
+				.filter(|&(_, value)| value.abs() >= 0.1 * largest)
+				.min_by_key(|&(&row, _)| rows[row].len())?.0;
+
+			let pivot = cols[col][&row];
+			let lower: Vec<(usize, f64)> = cols[col].iter()
+				.filter(|&(&other, _)| other != row)
+				.map(|(&other, value)| (other, value / pivot))
+				.collect();
+			let upper: Vec<(usize, f64)> = rows[row].iter()
+				.filter(|&&other| other != col)
+				.map(|&other| (other, cols[other][&row]))
+				.collect();
+
+			for &(other, coeff) in &upper {
+				cols[other].remove(&row);
+				for &(i, multiplier) in &lower {
+					let value = cols[other].get(&i).cloned().unwrap_or(0.) - multiplier * coeff;
+					if value.abs() <= zero_tolerance {
+						cols[other].remove(&i);
+						rows[i].remove(&other);
+					} else {
+						cols[other].insert(i, value);
+						rows[i].insert(other);
+					}
+				}
+			}
+			for &(i, _) in &lower {
+				rows[i].remove(&col);
+			}
+			rows[row].clear();
+			cols[col].clear();
+			active[col] = false;
+
+			steps.push(Step {
+				row: row,
+				col: col,
+				pivot: pivot,
+				lower: lower,
+				upper: upper
+			});
+		}
+
+		Some(BasisFactor {
+			size: size,
+			steps: steps,
+			etas: vec![]
+		})
+	}
+
+	/// Number of columns replaced since the matrix was factorized.
+	pub fn num_updates(&self) -> usize {
+		self.etas.len()
+	}
+
+	/// Solves `B x = rhs`, where rhs has one entry per row and x has one
+	/// entry per basis position.
+	pub fn ftran(&self, rhs: &Vec<f64>) -> Vec<f64> {
+		let mut work = rhs.clone();
+		for step in &self.steps {
+			let value = work[step.row];
+			if value != 0. {
+				for &(i, multiplier) in &step.lower {
+					work[i] -= multiplier * value;
+				}
+			}
+		}
+
+		let mut x = vec![0.; self.size];
+		for step in self.steps.iter().rev() {
+			let value = step.upper.iter().fold(work[step.row], |value, &(col, coeff)| value - coeff * x[col]);
+			x[step.col] = value / step.pivot;
+		}
+
+		for eta in &self.etas {
+			let value = x[eta.position] / eta.pivot;
+			x[eta.position] = value;
+			if value != 0. {
+				for &(i, coeff) in &eta.column {
+					x[i] -= coeff * value;
+				}
+			}
+		}
+
+		x
+	}
+
+	/// Solves `B^T y = rhs`, where rhs has one entry per basis position and
+	/// y has one entry per row.
+	pub fn btran(&self, rhs: &Vec<f64>) -> Vec<f64> {
+		let mut work = rhs.clone();
+		for eta in self.etas.iter().rev() {
+			let value = eta.column.iter().fold(work[eta.position], |value, &(i, coeff)| value - coeff * work[i]);
+			work[eta.position] = value / eta.pivot;
+		}
+
+		let mut y = vec![0.; self.size];
+		for step in &self.steps {
+			let value = work[step.col] / step.pivot;
+			y[step.row] = value;
+			if value != 0. {
+				for &(col, coeff) in &step.upper {
+					work[col] -= coeff * value;
+				}
+			}
+		}
+
+		for step in self.steps.iter().rev() {
+			let value = step.lower.iter().fold(y[step.row], |value, &(i, multiplier)| value - multiplier * y[i]);
+			y[step.row] = value;
+		}
+
+		y
+	}
+
+	/// Replaces the column at `position` by one whose `ftran` is `alpha`.
+	pub fn update(&mut self, position: usize, alpha: &Vec<f64>) {
+		self.etas.push(Eta {
+			position: position,
+			pivot: alpha[position],
+			column: alpha.iter().cloned().enumerate().filter(|&(i, value)| i != position && value != 0.).collect()
+		});
+	}
+}
+
+#[cfg(test)]
+mod lu_tests {
+	use super::*;
+	use assert_approx_eq::*;
+
+	fn multiply(columns: &Vec<Vec<(usize, f64)>>, x: &Vec<f64>) -> Vec<f64> {
+		let mut product = vec![0.; x.len()];
+		for (col, column) in columns.iter().enumerate() {
+			for &(row, value) in column {
+				product[row] += value * x[col];
+			}
+		}
+		product
+	}
+
+	fn transpose_multiply(columns: &Vec<Vec<(usize, f64)>>, y: &Vec<f64>) -> Vec<f64> {
+		columns.iter().map(|column| column.iter().map(|&(row, value)| value * y[row]).sum()).collect()
+	}
+
+	#[test]
+	fn solve_test() {
+		// [2 0 1; 1 3 0; 0 1 4] by columns, whose elimination fills in
+		let mut columns = vec![vec![(0, 2.), (1, 1.)], vec![(1, 3.), (2, 1.)], vec![(0, 1.), (2, 4.)]];
+		let mut factor = BasisFactor::new(&columns, 1e-9, 1e-12).unwrap();
+
+		let rhs = vec![1., -2., 5.];
+		let x = factor.ftran(&rhs);
+		for (lhs, rhs) in multiply(&columns, &x).iter().zip(rhs.iter()) {
+			assert_approx_eq!(lhs, rhs);
+		}
+		let y = factor.btran(&rhs);
+		for (lhs, rhs) in transpose_multiply(&columns, &y).iter().zip(rhs.iter()) {
+			assert_approx_eq!(lhs, rhs);
+		}
+
+		// Swapping in a new second column keeps both solves exact
+		let entering = vec![(0, 1.), (1, 1.), (2, 1.)];
+		let dense: Vec<f64> = vec![1., 1., 1.];
+		let alpha = factor.ftran(&dense);
+		factor.update(1, &alpha);
+		columns[1] = entering;
+		assert_eq!(factor.num_updates(), 1);
+
+		let x = factor.ftran(&rhs);
+		for (lhs, rhs) in multiply(&columns, &x).iter().zip(rhs.iter()) {
+			assert_approx_eq!(lhs, rhs);
+		}
+		let y = factor.btran(&rhs);
+		for (lhs, rhs) in transpose_multiply(&columns, &y).iter().zip(rhs.iter()) {
+			assert_approx_eq!(lhs, rhs);
+		}
+	}
+
+	#[test]
+	fn singular_test() {
+		let columns = vec![vec![(0, 1.), (1, 2.)], vec![(0, 2.), (1, 4.)]];
+		assert_eq!(BasisFactor::new(&columns, 1e-9, 1e-12), None);
+	}
+}
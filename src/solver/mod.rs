@@ -3,37 +3,484 @@
 //! This code provides the rulp library with
 //! linear program-solving capabilities.
 //! This is implemented via the Simplex Tableau
-//! method.
+//! method, via the revised simplex method on an
+//! LU factorized basis, and via a primal-dual
+//! interior point method. An exact simplex method
+//! over rationals gives certified answers, and a
+//! presolve pass shrinks an Lp before any of them.
+use std::error::Error;
+use std::f64::{INFINITY, NEG_INFINITY};
 use std::fmt;
-use rulinalg::matrix::{Matrix};
-use lp::Lp;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use rulinalg::matrix::{BaseMatrix, Matrix};
+use builder::Relation;
+use lp::{ExactLp, Lp, Optimization, Substitution};
+use num_rational::BigRational;
 
+mod impl_exact;
+mod impl_interior;
+mod impl_presolve;
+mod impl_revised;
 mod impl_solver;
+mod lu;
+mod scaling;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Status {
 	Optimal,
 	Infeasible,
-	Unbounded
+	Unbounded,
+	/// The solver hit a pivot or value it could not work with, e.g. a
+	/// zero pivot element or a non-finite entry produced by round-off.
+	NumericalFailure,
+	/// `SimplexOptions::max_iterations` pivots were made without reaching optimality.
+	IterationLimit,
+	/// `SimplexOptions::time_limit` passed without reaching optimality.
+	TimeLimit,
+	/// The `SimplexOptions::cancel` token was cancelled during the solve.
+	Cancelled,
+	/// `MipOptions::node_limit` relaxations were solved without proving
+	/// the best integral solution optimal.
+	NodeLimit
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Solution {
 	lp: Lp,
+	/// Value of each column of `A`. A variable replaced through
+	/// `Lp::substitutions` is reported with its own value, in the column
+	/// that stands in for it.
 	pub values: Option<Vec<f64>>, 
 	pub objective: Option<f64>,
-	pub status: Status
+	pub status: Status,
+	/// The final basis, whenever `values` is set.
+	pub basis: Option<Basis>,
+	/// Shadow price of each constraint at an optimal solution: the rate at
+	/// which the optimal objective changes as the constraint's RHS grows.
+	///
+	/// For a Max problem `<=` rows have non-negative and `>=` rows
+	/// non-positive duals; for a Min problem the signs are reversed.
+	/// `==` rows may take either sign.
+	pub duals: Option<Vec<f64>>,
+	/// Reduced cost of each column of `A` at an optimal solution: the rate
+	/// at which the objective changes as the variable is increased. Basic
+	/// variables have a reduced cost of zero. For a Max problem nonbasic
+	/// variables at their lower bound have non-positive and those at their
+	/// upper bound non-negative reduced costs; for a Min problem the signs
+	/// are reversed.
+	pub reduced_costs: Option<Vec<f64>>,
+	/// Ranging information for an optimal solution.
+	pub sensitivity: Option<SensitivityReport>,
+	/// Proof of infeasibility or unboundedness, for those statuses.
+	pub certificate: Option<Certificate>,
+	/// Number of pivots made over both phases. For an InteriorPointSolver,
+	/// the number of interior point iterations plus any simplex pivots.
+	pub iterations: usize
+}
+
+/// A Solution of an ExactLp, with every number exact.
+///
+/// Fields mean the same as those of `Solution`. An `Optimal` status is
+/// only given once the values and duals have been checked exactly against
+/// the optimality conditions.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExactSolution {
+	lp: ExactLp,
+	pub values: Option<Vec<BigRational>>,
+	pub objective: Option<BigRational>,
+	pub status: Status,
+	pub basis: Option<Basis>,
+	pub duals: Option<Vec<BigRational>>,
+	pub reduced_costs: Option<Vec<BigRational>>,
+	pub iterations: usize
+}
+
+/// Where a variable sits relative to the basis.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VarStatus {
+	Basic,
+	/// Nonbasic at its lower bound.
+	AtLower,
+	/// Nonbasic at its upper bound.
+	AtUpper
+}
+
+/// A simplex basis, with one status for every column of `Lp::A`. The
+/// columns include the slack and excess variables added by the Builder.
+///
+/// A Basis taken from one Solution can seed the solve of a similar Lp
+/// through `SimplexSolver::warm_start`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Basis {
+	pub statuses: Vec<VarStatus>
+}
+
+/// Evidence that an Lp has no optimal solution, checkable with `verify`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Certificate {
+	/// A Farkas ray `y`, one entry per constraint, with `y^T b` below the
+	/// least value of `y^T A x` over the bounds on x, so no x within its
+	/// bounds has `Ax = b`. With the default bounds this is `y^T A >= 0`
+	/// and `y^T b < 0`.
+	Infeasible { farkas: Vec<f64> },
+	/// A feasible `point` and a `ray` with `A ray = 0` along which the
+	/// objective improves without bound and no bound is ever reached.
+	/// `entering` is the column of `A` that could not leave the basis.
+	Unbounded { point: Vec<f64>, ray: Vec<f64>, entering: usize }
+}
+
+/// An inequality `coefficients^T x <= rhs` over the columns of an Lp that
+/// every integral solution of the Lp satisfies.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cut {
+	pub coefficients: Vec<f64>,
+	pub rhs: f64
+}
+
+/// A closed interval whose ends may be infinite.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Range {
+	pub lower: f64,
+	pub upper: f64
+}
+
+/// Sensitivity of an optimal solution to changes in `Lp::c` and `Lp::b`.
+///
+/// Each range holds the values a single coefficient can take, all others
+/// fixed, while the optimal basis is unchanged. Within an objective range
+/// the optimal `values` stay the same; within an RHS range the basis stays
+/// feasible and the `duals` stay the same.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SensitivityReport {
+	pub vars: Vec<String>,
+	pub constraints: Vec<String>,
+	/// Range of each entry of `c` over which the basis stays optimal.
+	pub objective_ranges: Vec<Range>,
+	/// Range of each entry of `b` over which the basis stays feasible.
+	pub rhs_ranges: Vec<Range>
+}
+
+/// Describes why an Lp was rejected before solving.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SolveError {
+	/// `b` does not have one entry per row of `A`.
+	RhsLength { rows: usize, len: usize },
+	/// `c` does not have one entry per column of `A`.
+	ObjectiveLength { cols: usize, len: usize },
+	/// `vars` does not have one name per column of `A`.
+	VariablesLength { cols: usize, len: usize },
+	/// `constraints` does not have one name per row of `A`.
+	ConstraintsLength { rows: usize, len: usize },
+	/// `lower` or `upper` does not have one bound per column of `A`.
+	BoundsLength { cols: usize, len: usize },
+	/// A variable's lower bound is not finite or is above its upper bound.
+	InvalidBounds { var: String, lower: f64, upper: f64 },
+	/// A substitution names a column outside `A`, or splits a column into itself.
+	InvalidSubstitution { col: usize },
+	/// `integers` names a column outside `A`.
+	InvalidInteger { col: usize },
+	/// An entry of `A`, `b` or `c` is NaN or infinite.
+	NonFiniteEntry { location: String },
+	/// There are more artificial variables than rows or columns of `A`.
+	InvalidArtificialVars { num_artificial_vars: usize, rows: usize, cols: usize },
+	/// The warm start basis does not have one status per column of `A`.
+	InvalidBasis { cols: usize, len: usize },
+	/// A new constraint does not have one coefficient per column of `A`.
+	CoefficientsLength { cols: usize, len: usize },
+	/// A row index is outside `A`.
+	InvalidRow { row: usize, rows: usize },
 }
 
 pub trait SolverBase {
 	fn new(lp: Lp) -> Self;
-	fn solve(&self) -> Solution;
+	fn solve(&self) -> Result<Solution, SolveError>;
+}
+
+/// Rule used by the SimplexSolver to choose the entering variable.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PricingRule {
+	/// Enter the variable with the most negative reduced cost.
+	Dantzig,
+	/// Enter the lowest-indexed variable with a negative reduced cost.
+	/// Combined with its matching leaving rule this never cycles.
+	Bland
+}
+
+/// Rule used by the SimplexSolver to break ties in the minimum ratio test.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RatioTest {
+	/// Take the first row with the minimum ratio, or the row whose basic
+	/// variable has the lowest index while Bland's rule is in effect.
+	MinRatio,
+	/// Break ties by comparing the tied rows, scaled by their pivot
+	/// elements, lexicographically over the columns of the starting basis.
+	/// Never cycles regardless of the pricing rule.
+	Lexicographic
+}
+
+/// Method used to scale the rows and columns of `Lp::A` before solving, so
+/// that its entries lie closer to one in magnitude. Every scale factor is
+/// a power of two, so scaling adds no round-off of its own.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Scaling {
+	/// Divides each row, then each column, by the geometric mean of its
+	/// largest and smallest entries, over passes that stop once the spread
+	/// of the entries no longer shrinks.
+	GeometricMean,
+	/// Divides each row, then each column, by its largest entry.
+	Equilibration,
+	/// Geometric mean scaling followed by equilibration.
+	GeometricMeanEquilibration
+}
+
+/// A flag shared between threads that asks a running solve to stop.
+///
+/// Clones share the same flag, so a token can be handed to a solver
+/// and cancelled from elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+	flag: Arc<AtomicBool>
+}
+
+/// Numerical tolerances used by the SimplexSolver in place of exact
+/// floating point comparisons.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tolerances {
+	/// Amount a basic variable may fall below zero and still be feasible.
+	/// Also bounds the phase one objective of a feasible problem and the
+	/// spread of ratios treated as tied in the ratio test.
+	pub primal_feasibility: f64,
+	/// Amount a reduced cost may fall below zero at an optimal tableau.
+	pub optimality: f64,
+	/// Smallest magnitude accepted as a pivot element.
+	pub pivot: f64,
+	/// Magnitude below which tableau entries are treated as exactly zero.
+	pub zero: f64
+}
+
+/// Options controlling how a SimplexSolver pivots and when it gives up.
+///
+/// When a limit is hit the Solution holds the basic feasible solution
+/// reached so far, or no values if phase one had not finished.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SimplexOptions {
+	pub pricing: PricingRule,
+	pub ratio_test: RatioTest,
+	/// Number of consecutive pivots that leave the objective unchanged
+	/// before the solver switches to Bland's rule. `None` never switches.
+	pub stall_limit: Option<usize>,
+	/// Maximum number of pivots over both phases.
+	pub max_iterations: Option<usize>,
+	/// Maximum wall-clock time spent in `solve`.
+	pub time_limit: Option<Duration>,
+	/// Token checked before every pivot.
+	pub cancel: Option<CancelToken>,
+	/// Scaling applied to the Lp before solving. The Solution is given in
+	/// terms of the Lp as it was, though the tolerances apply to the scaled Lp.
+	pub scaling: Option<Scaling>,
+	pub tolerances: Tolerances
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SimplexSolver {
 	lp: Lp,
-	pub tableau: Matrix<f64>
+	pub tableau: Matrix<f64>,
+	pub options: SimplexOptions,
+	/// Basis to start from instead of the slack basis, e.g. the basis of
+	/// a previous Solution. A basis that is not primal feasible is ignored,
+	/// unless it spans every row and is dual feasible, in which case the
+	/// dual simplex method starts from it.
+	pub warm_start: Option<Basis>,
+	pricing: PricingRule,
+	lex_cols: Vec<usize>,
+	basis: Vec<Option<usize>>,
+	iterations: usize,
+	deadline: Option<Instant>,
+	certificate: Option<Certificate>,
+	// Distance from the lower to the upper bound of each tableau column
+	upper: Vec<f64>,
+	// Columns holding upper - x in place of x
+	complemented: Vec<bool>,
+	// The tableau is optimal for the Lp before the changes add_constraint
+	// and set_rhs have since made to it, which reoptimize starts from
+	solved: bool
+}
+
+/// A simplex solver that keeps an LU factorization of the basis in place
+/// of a dense tableau, and prices each column from the duals when needed.
+///
+/// Solutions carry no sensitivity report. Ties in the ratio test go to the
+/// largest pivot element, so `RatioTest::Lexicographic` is not used.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RevisedSimplexSolver {
+	lp: Lp,
+	pub options: SimplexOptions
+}
+
+/// A simplex solver over exact rationals, for answers that are provably
+/// optimal rather than optimal up to a tolerance.
+///
+/// Pivots follow Bland's rule, so the solve never cycles and compares
+/// every number exactly. Expect it to be far slower than the SimplexSolver.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExactSimplexSolver {
+	lp: ExactLp,
+	/// Maximum number of pivots over both phases.
+	pub max_iterations: Option<usize>
+}
+
+/// Options controlling an InteriorPointSolver.
+///
+/// When a limit is hit the Solution holds no values, as the iterates need
+/// not be feasible before they converge.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InteriorPointOptions {
+	/// Largest relative primal residual, dual residual and duality gap
+	/// accepted as optimal.
+	pub tolerance: f64,
+	/// Maximum number of interior point iterations.
+	pub max_iterations: Option<usize>,
+	/// Maximum wall-clock time spent on interior point iterations.
+	pub time_limit: Option<Duration>,
+	/// Token checked before every iteration.
+	pub cancel: Option<CancelToken>,
+	/// Whether to move from the interior solution to an optimal basic
+	/// solution, which gives the basis, duals and sensitivity report of
+	/// the SimplexSolver.
+	pub crossover: bool,
+	/// Options of the simplex pivots made by crossover, or when the
+	/// iterates diverge.
+	pub simplex: SimplexOptions
+}
+
+/// A primal-dual interior point solver using Mehrotra's predictor-corrector
+/// method, for large or highly degenerate LPs where simplex iteration
+/// counts explode.
+///
+/// An Lp that is infeasible or unbounded shows up as iterates that diverge
+/// or stop making progress; the SimplexSolver then decides the status and
+/// gives the certificate.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InteriorPointSolver {
+	lp: Lp,
+	pub options: InteriorPointOptions
+}
+
+/// An Lp with its easy reductions made, and the steps that carry a
+/// Solution of the reduced Lp back to the original.
+///
+/// Presolve removes empty rows, rows of a single column (which fix it),
+/// rows of a single column and its slack (which bound it), rows that are a
+/// multiple of another, fixed columns and columns in no row. Infeasibility
+/// found along the way, or unboundedness once no rows remain, decides the
+/// status without solving.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Presolve {
+	original: Lp,
+	/// The reduced Lp, to be handed to a solver.
+	pub lp: Lp,
+	/// The status presolve decided on its own: Infeasible, or Optimal or
+	/// Unbounded when nothing is left of the Lp.
+	pub status: Option<Status>,
+	// Row and column of the original Lp behind each row and column of `lp`
+	rows: Vec<usize>,
+	cols: Vec<usize>,
+	// The postsolve stack, in the order the reductions were made
+	reductions: Vec<Reduction>,
+	// A column in no row along which the objective improves without bound
+	unbounded: Option<usize>
+}
+
+// A reduction made by presolve, with what postsolve needs to undo it.
+// Rows and columns are those of the original Lp and every rhs is taken
+// after the columns removed before it.
+#[derive(Debug, PartialEq, Clone)]
+enum Reduction {
+	EmptyRow { row: usize },
+	// A column in no row, or with equal bounds, set to `value`
+	FixedColumn { col: usize, value: f64 },
+	// A row whose one entry `coefficient` fixes `col` to `value`
+	SingletonRow { row: usize, col: usize, coefficient: f64, value: f64 },
+	// A row `coefficient * col + slack_coefficient * slack = rhs` in which
+	// the slack is in no other row, replaced by bounds on `col`
+	BoundRow { row: usize, col: usize, coefficient: f64, slack: usize, slack_coefficient: f64, rhs: f64,
+		before: (f64, f64), after: (f64, f64) },
+	// A row whose entries other than its slack, `entries`, are `ratio` times
+	// those of row `kept`. Any slack of the kept row has its bounds
+	// narrowed to those the removed row allows.
+	DuplicateRow { row: usize, kept: usize, ratio: f64, entries: Vec<(usize, f64)>, rhs: f64,
+		slack: Option<(usize, f64)>, kept_slack: Option<(usize, f64)>, before: (f64, f64), after: (f64, f64) }
+}
+
+impl Default for SimplexOptions {
+	fn default() -> Self {
+		SimplexOptions {
+			pricing: PricingRule::Dantzig,
+			ratio_test: RatioTest::MinRatio,
+			stall_limit: Some(50),
+			max_iterations: None,
+			time_limit: None,
+			cancel: None,
+			scaling: None,
+			tolerances: Tolerances::default()
+		}
+	}
+}
+
+impl Default for InteriorPointOptions {
+	fn default() -> Self {
+		InteriorPointOptions {
+			tolerance: 1e-8,
+			max_iterations: None,
+			time_limit: None,
+			cancel: None,
+			crossover: true,
+			simplex: SimplexOptions::default()
+		}
+	}
+}
+
+impl Default for Tolerances {
+	fn default() -> Self {
+		Tolerances {
+			primal_feasibility: 1e-7,
+			optimality: 1e-7,
+			pivot: 1e-9,
+			zero: 1e-12
+		}
+	}
+}
+
+impl CancelToken {
+	pub fn new() -> Self {
+		CancelToken::default()
+	}
+
+	/// Asks every solve holding this token to stop at its next pivot.
+	pub fn cancel(&self) {
+		self.flag.store(true, Ordering::SeqCst);
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.flag.load(Ordering::SeqCst)
+	}
+}
+
+impl From<Arc<AtomicBool>> for CancelToken {
+	fn from(flag: Arc<AtomicBool>) -> Self {
+		CancelToken {
+			flag: flag
+		}
+	}
+}
+
+/// Tokens are equal when they share the same flag.
+impl PartialEq for CancelToken {
+	fn eq(&self, other: &CancelToken) -> bool {
+		Arc::ptr_eq(&self.flag, &other.flag)
+	}
 }
 
 impl fmt::Display for Solution {
@@ -49,25 +496,341 @@ impl fmt::Display for Solution {
                         writeln!(f, "Optimal objective: {:}", obj).unwrap();
                     }
                 }
-                match self.values {
+                self.write_values(f);
+            },  
+            Status::IterationLimit | Status::TimeLimit | Status::Cancelled | Status::NodeLimit => {
+                let reason = match self.status {
+                    Status::IterationLimit => "Iteration limit reached",
+                    Status::TimeLimit => "Time limit reached",
+                    Status::NodeLimit => "Node limit reached",
+                    _ => "Cancelled"
+                };
+                writeln!(f, "{}", reason).unwrap();
+                match self.objective {
                     None => {
 
                     },
-                    Some(ref vals) => {
-                        for i in 0 .. vals.len() - self.lp.num_artificial_vars {
-                            writeln!(f, "{:}: {:?}", self.lp.vars[i],vals[i]).unwrap();
-                        }
+                    Some(ref obj) => {
+                        writeln!(f, "Best objective: {:}", obj).unwrap();
                     }
                 }
-            },  
+                self.write_values(f);
+            },
             Status::Infeasible => {
                 writeln!(f, "Infeasible").unwrap();
             },
             Status::Unbounded => {
                 writeln!(f, "Unbounded").unwrap();
+            },
+            Status::NumericalFailure => {
+                writeln!(f, "Numerical failure").unwrap();
             }
         }
 
         write!(f, "")
     }
-}
\ No newline at end of file
+}
+
+impl Basis {
+	/// Indices of the basic columns, in increasing order.
+	pub fn basic_vars(&self) -> Vec<usize> {
+		self.statuses.iter()
+			.enumerate()
+			.filter(|&(_, status)| *status == VarStatus::Basic)
+			.map(|(i, _)| i)
+			.collect()
+	}
+}
+
+impl Certificate {
+	/// Checks the certificate against `lp`, allowing each violated
+	/// inequality or equation a slack of `tolerance`.
+	pub fn verify(&self, lp: &Lp, tolerance: f64) -> bool {
+		let rows = lp.A.rows();
+		let cols = lp.A.cols();
+		match *self {
+			Certificate::Infeasible { ref farkas } => {
+				if farkas.len() != rows || lp.b.len() != rows || lp.lower.len() != cols || lp.upper.len() != cols {
+					return false;
+				}
+				let y_b: f64 = (0 .. rows).map(|row| farkas[row] * lp.b[row]).sum();
+				let min_y_A_x: f64 = (0 .. cols).map(|col| {
+					let y_A = lp.A.column(col).map(|(row, value)| farkas[row] * value).sum::<f64>();
+					if y_A > tolerance {
+						y_A * lp.lower[col]
+					} else if y_A < -tolerance {
+						y_A * lp.upper[col]
+					} else {
+						0.
+					}
+				}).sum();
+				y_b < min_y_A_x - tolerance
+			},
+			Certificate::Unbounded { ref point, ref ray, entering } => {
+				if point.len() != cols || ray.len() != cols || lp.c.len() != cols || entering >= cols ||
+					lp.lower.len() != cols || lp.upper.len() != cols {
+					return false;
+				}
+				let product = |x: &Vec<f64>| -> Vec<f64> {
+					let mut product = vec![0.; rows];
+					for (row, col, value) in lp.A.triplets() {
+						product[row] += value * x[col];
+					}
+					product
+				};
+				let (A_point, A_ray) = (product(point), product(ray));
+				let feasible = (0 .. cols).all(|col| {
+						point[col] >= lp.lower[col] - tolerance && point[col] <= lp.upper[col] + tolerance
+					}) &&
+					(0 .. rows).all(|row| (A_point[row] - lp.b[row]).abs() <= tolerance);
+				let is_ray = (0 .. cols).all(|col| {
+						(ray[col] >= -tolerance || lp.lower[col] == NEG_INFINITY) &&
+						(ray[col] <= tolerance || lp.upper[col] == INFINITY)
+					}) &&
+					(0 .. rows).all(|row| A_ray[row].abs() <= tolerance);
+				let c_ray: f64 = (0 .. cols).map(|col| lp.c[col] * ray[col]).sum();
+				let improving = match lp.optimization {
+					Optimization::Max => c_ray > tolerance,
+					Optimization::Min => c_ray < -tolerance
+				};
+				feasible && is_ray && improving
+			}
+		}
+	}
+}
+
+impl Range {
+	pub fn contains(&self, value: f64) -> bool {
+		self.lower <= value && value <= self.upper
+	}
+}
+
+impl fmt::Display for Range {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "[{}, {}]", self.lower, self.upper)
+	}
+}
+
+impl SensitivityReport {
+	/// Returns the objective coefficient range of the variable with the given name.
+	pub fn objective_range(&self, var: &str) -> Option<Range> {
+		self.vars.iter().position(|name| name == var).map(|i| self.objective_ranges[i])
+	}
+
+	/// Returns the RHS range of the constraint with the given name.
+	pub fn rhs_range(&self, constraint: &str) -> Option<Range> {
+		self.constraints.iter().position(|name| name == constraint).map(|i| self.rhs_ranges[i])
+	}
+}
+
+impl fmt::Display for SensitivityReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Objective coefficient ranges:")?;
+		for (var, range) in self.vars.iter().zip(self.objective_ranges.iter()) {
+			writeln!(f, "{}: {}", var, range)?;
+		}
+		writeln!(f, "RHS ranges:")?;
+		for (constraint, range) in self.constraints.iter().zip(self.rhs_ranges.iter()) {
+			writeln!(f, "{}: {}", constraint, range)?;
+		}
+		Ok(())
+	}
+}
+
+impl Solution {
+    /// Returns the dual value of the constraint with the given name.
+    pub fn dual(&self, constraint: &str) -> Option<f64> {
+        let index = self.lp.constraints.iter().position(|name| name == constraint);
+        match (index, &self.duals) {
+            (Some(i), &Some(ref duals)) => Some(duals[i]),
+            _ => None
+        }
+    }
+
+    /// Returns the reduced cost of the variable with the given name.
+    pub fn reduced_cost(&self, var: &str) -> Option<f64> {
+        let index = self.lp.vars.iter().position(|name| name == var);
+        match (index, &self.reduced_costs) {
+            (Some(i), &Some(ref costs)) => Some(costs[i]),
+            _ => None
+        }
+    }
+
+    fn is_negative_part(&self, col: usize) -> bool {
+        self.lp.substitutions.iter().any(|substitution| match *substitution {
+            Substitution::Split { negative, .. } => negative == col,
+            _ => false
+        })
+    }
+
+    fn write_values(&self, f: &mut fmt::Formatter) {
+        match self.values {
+            None => {
+
+            },
+            Some(ref vals) => {
+                for i in 0 .. vals.len() - self.lp.num_artificial_vars {
+                    if self.is_negative_part(i) {
+                        continue;
+                    }
+                    writeln!(f, "{:}: {:?}", self.lp.vars[i],vals[i]).unwrap();
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			SolveError::RhsLength { rows, len } => {
+				write!(f, "b has {} entries but A has {} rows", len, rows)
+			},
+			SolveError::ObjectiveLength { cols, len } => {
+				write!(f, "c has {} entries but A has {} columns", len, cols)
+			},
+			SolveError::VariablesLength { cols, len } => {
+				write!(f, "{} variable names given but A has {} columns", len, cols)
+			},
+			SolveError::ConstraintsLength { rows, len } => {
+				write!(f, "{} constraint names given but A has {} rows", len, rows)
+			},
+			SolveError::BoundsLength { cols, len } => {
+				write!(f, "{} bounds given but A has {} columns", len, cols)
+			},
+			SolveError::InvalidBounds { ref var, lower, upper } => {
+				write!(f, "invalid bounds [{}, {}] on variable \"{}\"", lower, upper, var)
+			},
+			SolveError::InvalidSubstitution { col } => {
+				write!(f, "substitution on column {} does not fit A", col)
+			},
+			SolveError::InvalidInteger { col } => {
+				write!(f, "integer column {} is outside A", col)
+			},
+			SolveError::NonFiniteEntry { ref location } => {
+				write!(f, "{} is not finite", location)
+			},
+			SolveError::InvalidArtificialVars { num_artificial_vars, rows, cols } => {
+				write!(f, "{} artificial variables declared for an A with {} rows and {} columns", num_artificial_vars, rows, cols)
+			},
+			SolveError::InvalidBasis { cols, len } => {
+				write!(f, "basis has {} statuses but A has {} columns", len, cols)
+			},
+			SolveError::CoefficientsLength { cols, len } => {
+				write!(f, "constraint has {} coefficients but A has {} columns", len, cols)
+			},
+			SolveError::InvalidRow { row, rows } => {
+				write!(f, "row {} is outside A with {} rows", row, rows)
+			}
+		}
+	}
+}
+
+impl Error for SolveError {}
+
+/// Checks that the dimensions and entries of an Lp are consistent
+/// before handing it to a solver.
+fn validate_lp(lp: &Lp) -> Result<(), SolveError> {
+	let rows = lp.A.rows();
+	let cols = lp.A.cols();
+
+	if lp.b.len() != rows {
+		return Err(SolveError::RhsLength { rows: rows, len: lp.b.len() });
+	}
+	if lp.c.len() != cols {
+		return Err(SolveError::ObjectiveLength { cols: cols, len: lp.c.len() });
+	}
+	if lp.vars.len() != cols {
+		return Err(SolveError::VariablesLength { cols: cols, len: lp.vars.len() });
+	}
+	if lp.constraints.len() != rows {
+		return Err(SolveError::ConstraintsLength { rows: rows, len: lp.constraints.len() });
+	}
+	for bounds in &[&lp.lower, &lp.upper] {
+		if bounds.len() != cols {
+			return Err(SolveError::BoundsLength { cols: cols, len: bounds.len() });
+		}
+	}
+	for substitution in &lp.substitutions {
+		let col = match *substitution {
+			Substitution::Negated { col } if col >= cols => col,
+			Substitution::Split { positive, negative } if positive >= cols || positive == negative => positive,
+			Substitution::Split { negative, .. } if negative >= cols => negative,
+			_ => continue
+		};
+		return Err(SolveError::InvalidSubstitution { col: col });
+	}
+	if let Some(&col) = lp.integers.iter().find(|&&col| col >= cols) {
+		return Err(SolveError::InvalidInteger { col: col });
+	}
+	if lp.num_artificial_vars > rows || lp.num_artificial_vars > cols {
+		return Err(SolveError::InvalidArtificialVars {
+			num_artificial_vars: lp.num_artificial_vars,
+			rows: rows,
+			cols: cols
+		});
+	}
+
+	for (row, col, value) in lp.A.triplets() {
+		if !value.is_finite() {
+			return Err(SolveError::NonFiniteEntry { location: format!("A[{}, {}]", row, col) });
+		}
+	}
+	for (i, val) in lp.b.iter().enumerate() {
+		if !val.is_finite() {
+			return Err(SolveError::NonFiniteEntry { location: format!("b[{}]", i) });
+		}
+	}
+	for (i, val) in lp.c.iter().enumerate() {
+		if !val.is_finite() {
+			return Err(SolveError::NonFiniteEntry { location: format!("c[{}]", i) });
+		}
+	}
+	for col in 0 .. cols {
+		let (lower, upper) = (lp.lower[col], lp.upper[col]);
+		if !lower.is_finite() || upper.is_nan() || lower > upper {
+			return Err(SolveError::InvalidBounds { var: lp.vars[col].clone(), lower: lower, upper: upper });
+		}
+	}
+
+	Ok(())
+}
+
+// Reports each var the Builder replaced in the column it replaced. The
+// negative part of a split var keeps its own value, reduced cost and range.
+// `is_basic` tells whether a column of the Lp is basic at the solution.
+fn undo_substitutions<F>(lp: &Lp, is_basic: F, values: &mut Vec<f64>, reduced_costs: &mut Option<Vec<f64>>,
+		sensitivity: &mut Option<SensitivityReport>) where F: Fn(usize) -> bool {
+	let negate = |range: Range| Range { lower: -range.upper, upper: -range.lower };
+
+	for substitution in &lp.substitutions {
+		match *substitution {
+			Substitution::Negated { col } => {
+				values[col] = -values[col];
+				if let Some(ref mut costs) = *reduced_costs {
+					costs[col] = -costs[col];
+				}
+				if let Some(ref mut report) = *sensitivity {
+					report.objective_ranges[col] = negate(report.objective_ranges[col]);
+				}
+			},
+			Substitution::Split { positive, negative } => {
+				values[positive] -= values[negative];
+				if let Some(ref mut report) = *sensitivity {
+					// c_x is the cost of the positive part and minus the
+					// cost of the negative part; a nonbasic split var
+					// must stay optimal for both
+					let pos = report.objective_ranges[positive];
+					let neg = negate(report.objective_ranges[negative]);
+					report.objective_ranges[positive] = if is_basic(positive) {
+						pos
+					} else if is_basic(negative) {
+						neg
+					} else {
+						Range { lower: pos.lower.max(neg.lower), upper: pos.upper.min(neg.upper) }
+					};
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,294 @@
+//! Row and column scaling of an Lp, and the unscaling of a Solution of the
+//! scaled Lp.
+
+use super::{Certificate, Range, Scaling, Solution};
+use lp::{Lp, SparseMatrix, Substitution};
+
+// Most passes made by geometric mean scaling
+const MAX_PASSES: usize = 20;
+// Fraction of the spread of the entries a pass must leave to be followed
+// by another
+const MIN_IMPROVEMENT: f64 = 0.9;
+
+/// Factors `R` and `S` that scale an Lp to `R A S x' = R b` with objective
+/// `(S c)^T x'`, so that `x = S x'`. The duals of the scaled Lp are those
+/// of the original divided by `R`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Scale {
+	rows: Vec<f64>,
+	cols: Vec<f64>
+}
+
+impl Scale {
+	pub fn new(lp: &Lp, method: Scaling) -> Self {
+		let mut scale = Scale {
+			rows: vec![1.; lp.A.rows()],
+			cols: vec![1.; lp.A.cols()]
+		};
+		match method {
+			Scaling::GeometricMean => scale.geometric_mean(&lp.A),
+			Scaling::Equilibration => scale.equilibrate(&lp.A),
+			Scaling::GeometricMeanEquilibration => {
+				scale.geometric_mean(&lp.A);
+				scale.equilibrate(&lp.A);
+			}
+		}
+
+		for factor in scale.rows.iter_mut().chain(scale.cols.iter_mut()) {
+			*factor = 2f64.powi(factor.log2().round() as i32);
+		}
+		// Both parts of a split var must scale alike for their difference
+		// to be unscaled
+		for substitution in &lp.substitutions {
+			if let Substitution::Split { positive, negative } = *substitution {
+				scale.cols[negative] = scale.cols[positive];
+			}
+		}
+		scale
+	}
+
+	pub fn scale_lp(&self, lp: &Lp) -> Lp {
+		let triplets = lp.A.triplets()
+			.map(|(row, col, value)| (row, col, self.rows[row] * value * self.cols[col]))
+			.collect();
+
+		let mut scaled = lp.clone();
+		scaled.A = SparseMatrix::from_triplets(lp.A.rows(), lp.A.cols(), triplets);
+		for row in 0 .. lp.A.rows() {
+			scaled.b[row] *= self.rows[row];
+		}
+		for col in 0 .. lp.A.cols() {
+			scaled.c[col] *= self.cols[col];
+			scaled.lower[col] /= self.cols[col];
+			scaled.upper[col] /= self.cols[col];
+		}
+		scaled
+	}
+
+	/// Carries a Solution of the scaled Lp back to `lp`, the Lp as it was.
+	pub fn unscale(&self, mut solution: Solution, lp: &Lp) -> Solution {
+		let rows = &self.rows;
+		let cols = &self.cols;
+		let scale_range = |range: &mut Range, factor: f64| {
+			range.lower /= factor;
+			range.upper /= factor;
+		};
+
+		solution.lp = lp.clone();
+		if let Some(ref mut values) = solution.values {
+			for (value, factor) in values.iter_mut().zip(cols.iter()) {
+				*value *= *factor;
+			}
+		}
+		if let Some(ref mut duals) = solution.duals {
+			for (dual, factor) in duals.iter_mut().zip(rows.iter()) {
+				*dual *= *factor;
+			}
+		}
+		if let Some(ref mut costs) = solution.reduced_costs {
+			for (cost, factor) in costs.iter_mut().zip(cols.iter()) {
+				*cost /= *factor;
+			}
+		}
+		if let Some(ref mut report) = solution.sensitivity {
+			for (range, &factor) in report.objective_ranges.iter_mut().zip(cols.iter()) {
+				scale_range(range, factor);
+			}
+			for (range, &factor) in report.rhs_ranges.iter_mut().zip(rows.iter()) {
+				scale_range(range, factor);
+			}
+		}
+		match solution.certificate {
+			Some(Certificate::Infeasible { ref mut farkas }) => {
+				for (y, factor) in farkas.iter_mut().zip(rows.iter()) {
+					*y *= *factor;
+				}
+			},
+			Some(Certificate::Unbounded { ref mut point, ref mut ray, .. }) => {
+				for col in 0 .. cols.len() {
+					point[col] *= cols[col];
+					ray[col] *= cols[col];
+				}
+			},
+			None => {}
+		}
+		solution
+	}
+
+	// Alternately scales rows and columns by the geometric mean of their
+	// largest and smallest entries
+	fn geometric_mean(&mut self, A: &SparseMatrix) {
+		let mut spread = self.spread(A);
+		for _ in 0 .. MAX_PASSES {
+			let (row_min, row_max) = self.row_extremes(A);
+			for row in 0 .. self.rows.len() {
+				if row_max[row] > 0. {
+					self.rows[row] /= (row_min[row] * row_max[row]).sqrt();
+				}
+			}
+			let (col_min, col_max) = self.col_extremes(A);
+			for col in 0 .. self.cols.len() {
+				if col_max[col] > 0. {
+					self.cols[col] /= (col_min[col] * col_max[col]).sqrt();
+				}
+			}
+
+			let next = self.spread(A);
+			if next > MIN_IMPROVEMENT * spread {
+				return;
+			}
+			spread = next;
+		}
+	}
+
+	// Scales each row, then each column, to a largest entry of one
+	fn equilibrate(&mut self, A: &SparseMatrix) {
+		let (_, row_max) = self.row_extremes(A);
+		for row in 0 .. self.rows.len() {
+			if row_max[row] > 0. {
+				self.rows[row] /= row_max[row];
+			}
+		}
+		let (_, col_max) = self.col_extremes(A);
+		for col in 0 .. self.cols.len() {
+			if col_max[col] > 0. {
+				self.cols[col] /= col_max[col];
+			}
+		}
+	}
+
+	// Smallest and largest magnitude of the scaled entries of each row,
+	// zero for an empty row
+	fn row_extremes(&self, A: &SparseMatrix) -> (Vec<f64>, Vec<f64>) {
+		let mut min = vec![0.; self.rows.len()];
+		let mut max = vec![0.; self.rows.len()];
+		for (row, col, value) in A.triplets() {
+			let magnitude = (self.rows[row] * value * self.cols[col]).abs();
+			if max[row] == 0. || magnitude < min[row] {
+				min[row] = magnitude;
+			}
+			max[row] = magnitude.max(max[row]);
+		}
+		(min, max)
+	}
+
+	fn col_extremes(&self, A: &SparseMatrix) -> (Vec<f64>, Vec<f64>) {
+		let mut min = vec![0.; self.cols.len()];
+		let mut max = vec![0.; self.cols.len()];
+		for (row, col, value) in A.triplets() {
+			let magnitude = (self.rows[row] * value * self.cols[col]).abs();
+			if max[col] == 0. || magnitude < min[col] {
+				min[col] = magnitude;
+			}
+			max[col] = magnitude.max(max[col]);
+		}
+		(min, max)
+	}
+
+	// Ratio of the largest to the smallest magnitude of the scaled entries
+	fn spread(&self, A: &SparseMatrix) -> f64 {
+		let (min, max) = self.row_extremes(A);
+		let largest = max.iter().cloned().fold(0., f64::max);
+		let smallest = min.iter().cloned().filter(|&min| min > 0.).fold(largest, f64::min);
+		if smallest > 0. { largest / smallest } else { 1. }
+	}
+}
+
+#[cfg(test)]
+mod scaling_tests {
+	use super::*;
+	use lp::Optimization;
+	use rulinalg::matrix::{BaseMatrix, Matrix};
+	use solver::{SimplexOptions, SimplexSolver, SolverBase, Status};
+	use std::f64::INFINITY;
+
+	fn lp_from(A: Matrix<f64>, b: Vec<f64>, c: Vec<f64>, optimization: Optimization) -> Lp {
+		let rows = A.rows();
+		let cols = A.cols();
+		Lp {
+			A: A.into(),
+			b: b,
+			c: c,
+			optimization: optimization,
+			vars: (0 .. cols).map(|col| format!("x{}", col)).collect(),
+			constraints: (0 .. rows).map(|row| format!("c{}", row)).collect(),
+			lower: vec![0.; cols],
+			upper: vec![INFINITY; cols],
+			substitutions: vec![],
+			integers: vec![],
+			num_artificial_vars: 0
+		}
+	}
+
+	fn wide_lp() -> Lp {
+		// Rows with entries from 1e-4 to 1e6, and x1 <= 2e5
+		let mut lp = lp_from(matrix![1e5, 2e-4, 1., 0.;
+									 3e6, 1., 0., 1.],
+							 vec![4e5, 9e6], vec![2., 3e3, 0., 0.], Optimization::Max);
+		lp.upper[0] = 2e5;
+		lp
+	}
+
+	#[test]
+	fn scale_test() {
+		let lp = wide_lp();
+		for &method in &[Scaling::GeometricMean, Scaling::Equilibration, Scaling::GeometricMeanEquilibration] {
+			let scale = Scale::new(&lp, method);
+			for factor in scale.rows.iter().chain(scale.cols.iter()) {
+				assert_eq!(factor.log2().fract(), 0.);
+			}
+			let scaled = scale.scale_lp(&lp);
+			assert!(scale.spread(&lp.A) < Scale { rows: vec![1.; 2], cols: vec![1.; 4] }.spread(&lp.A));
+			assert!(scaled.A.triplets().all(|(_, _, value)| value.abs() <= 1. || method == Scaling::GeometricMean));
+			assert_eq!(scaled.upper[0], 2e5 / scale.cols[0]);
+			assert_eq!(scaled.upper[1], INFINITY);
+		}
+	}
+
+	#[test]
+	fn unscale_test() {
+		let lp = wide_lp();
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+		for &method in &[Scaling::GeometricMean, Scaling::Equilibration, Scaling::GeometricMeanEquilibration] {
+			let options = SimplexOptions { scaling: Some(method), .. SimplexOptions::default() };
+			let solution = SimplexSolver::with_options(lp.clone(), options).solve().unwrap();
+			assert_eq!(solution.status, Status::Optimal);
+			assert!((solution.objective.unwrap() - expected.objective.unwrap()).abs() < 1e-6);
+			for (value, expected) in solution.values.unwrap().iter().zip(expected.values.as_ref().unwrap()) {
+				assert!((value - expected).abs() < 1e-6 * (1. + expected.abs()));
+			}
+			for (dual, expected) in solution.duals.unwrap().iter().zip(expected.duals.as_ref().unwrap()) {
+				assert!((dual - expected).abs() < 1e-9 * (1. + expected.abs()));
+			}
+			for (cost, expected) in solution.reduced_costs.unwrap().iter().zip(expected.reduced_costs.as_ref().unwrap()) {
+				assert!((cost - expected).abs() < 1e-9 * (1. + expected.abs()));
+			}
+			let report = solution.sensitivity.unwrap();
+			let expected_report = expected.sensitivity.as_ref().unwrap();
+			for (range, expected) in report.rhs_ranges.iter().zip(expected_report.rhs_ranges.iter()) {
+				assert!((range.lower - expected.lower).abs() < 1e-6 * (1. + expected.lower.abs()) || range.lower == expected.lower);
+				assert!((range.upper - expected.upper).abs() < 1e-6 * (1. + expected.upper.abs()) || range.upper == expected.upper);
+			}
+		}
+	}
+
+	#[test]
+	fn certificate_test() {
+		let options = SimplexOptions { scaling: Some(Scaling::GeometricMeanEquilibration), .. SimplexOptions::default() };
+
+		// 1e5 x1 + 2e-4 x2 + s1 = -1 has no solution with x, s >= 0
+		let mut lp = wide_lp();
+		lp.b[0] = -1.;
+		let solution = SimplexSolver::with_options(lp.clone(), options.clone()).solve().unwrap();
+		assert_eq!(solution.status, Status::Infeasible);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-9));
+
+		// Without its bound, x1 grows along with s2 through the first row
+		let lp = lp_from(matrix![1e5, -2e-4, 1., 0.;
+								 0., 0., 0., 1.],
+						 vec![4e5, 9e6], vec![2., 3e3, 0., 0.], Optimization::Max);
+		let solution = SimplexSolver::with_options(lp.clone(), options).solve().unwrap();
+		assert_eq!(solution.status, Status::Unbounded);
+		assert!(solution.certificate.unwrap().verify(&lp, 1e-6));
+	}
+}
@@ -1,14 +1,65 @@
 extern crate rulinalg;
 extern crate assert_approx_eq;
 extern crate rulp;
+extern crate num_traits;
 
 #[allow(unused_imports)]
 use assert_approx_eq::*;
-use rulp::builder::{Builder, BuilderBase};
-use rulp::parser::{Parser, ParserBase};
-use rulp::solver::{SolverBase, SimplexSolver};
-use rulp::solver::Status;
+use rulp::builder::{Builder, BuilderBase, Variable, Constraint, Objective, Relation};
+use rulp::lp::{Lp, Substitution};
+use rulp::parser::{Parser, ParserBase, ParseError, CplexParser, MpsParser, FixedMpsParser, lp_to_cplex, lp_to_mps, format_components};
+use rulp::solver::{SolverBase, SimplexSolver, RevisedSimplexSolver, InteriorPointSolver, InteriorPointOptions, SimplexOptions, PricingRule, RatioTest, CancelToken};
+use rulp::solver::{Solution, Status, Certificate, ExactSimplexSolver, Presolve, Scaling};
+use rulp::mip::{BranchAndBound, MipSolverBase, MipOptions};
+use num_traits::ToPrimitive;
+use std::f64::NEG_INFINITY;
 use std::fs::File;
+use std::time::Duration;
+
+// The examples of tests/test_files every solver is checked on
+const EXAMPLES: &[&str] = &["advertisement", "degenerate", "free", "infeasible", "radiation", "unbounded"];
+
+fn example_file(name: &str) -> File {
+	File::open(format!("./tests/test_files/{}_example.lp", name)).unwrap()
+}
+
+fn example_lp(name: &str) -> Lp {
+	Parser::lp_from_file(&mut example_file(name), Builder::new()).unwrap()
+}
+
+// Solves each of the examples with `solve` and checks it against
+// SimplexSolver: the same status, and the same objective and variable
+// values to a relative 1e-6 when optimal or else a certificate that
+// holds. Returns the SimplexSolver and `solve` Solutions, in the order of
+// EXAMPLES.
+fn assert_solves_examples<F>(solve: F) -> Vec<(Solution, Solution)> where F: Fn(&Lp) -> Solution {
+	EXAMPLES.iter().map(|name| {
+		let lp = example_lp(name);
+		let expected = SimplexSolver::new(lp.clone()).solve().unwrap();
+		let solution = solve(&lp);
+		assert_eq!(solution.status, expected.status, "{}", name);
+		match solution.status {
+			Status::Optimal => {
+				let close = |value: f64, expected: f64| (value - expected).abs() < 1e-6 * (1. + expected.abs());
+				assert!(close(solution.objective.unwrap(), expected.objective.unwrap()), "{}", name);
+				// The parts of a split var may differ, though not their difference
+				let splits = lp.substitutions.iter().filter(|s| match **s {
+					Substitution::Split { .. } => true,
+					_ => false
+				}).count();
+				let vars = lp.A.cols() - lp.num_artificial_vars - splits;
+				let values = solution.values.as_ref().unwrap();
+				for (&value, &expected) in values[.. vars].iter().zip(expected.values.as_ref().unwrap()) {
+					assert!(close(value, expected), "{}", name);
+				}
+			},
+			_ => if let Some(ref certificate) = solution.certificate {
+				assert!(certificate.verify(&lp, 1e-9), "{}", name);
+			}
+		}
+		(expected, solution)
+	}).collect()
+}
 
 #[test]
 fn minimize_text_test() {
@@ -25,10 +76,10 @@ fn minimize_text_test() {
 	";
 
 	let builder = Builder::new();
-	let lp = Parser::lp_from_text(text_problem, builder);
+	let lp = Parser::lp_from_text(text_problem, builder).unwrap();
 	println!("{}", lp);
 	let solver = SimplexSolver::new(lp);
-	let solution = solver.solve();
+	let solution = solver.solve().unwrap();
 	print!("finished solving");
 	print!("{:?}", &solution);
 
@@ -60,10 +111,10 @@ fn maximize_text_test () {
 		# Nothing more to see here;
 	";
 	let builder = Builder::new();
-	let lp = Parser::lp_from_text(text_problem, builder);
+	let lp = Parser::lp_from_text(text_problem, builder).unwrap();
 	println!("{}", lp);
 	let simplex = SimplexSolver::new(lp);
-	let solution = simplex.solve();
+	let solution = simplex.solve().unwrap();
 	println!("{:?}", &solution);
 	let res = solution.values.unwrap();
 	let expected = vec![4., 10., 14.];
@@ -74,14 +125,55 @@ fn maximize_text_test () {
 	assert_eq!(solution.objective.unwrap(), 1052000.);
 }	
 
+#[test]
+fn bounds_builder_test() {
+	// The advertisement example with newspaper <= 10 as a bound instead of a row
+	let var = |name: &str, coefficient: f64| Variable { name: name.to_string(), coefficient: coefficient };
+	let mut builder = Builder::new();
+	for name in &["television", "newspaper", "radio"] {
+		builder.add_variable(var(name, 1.)).unwrap();
+	}
+	builder.set_bounds("newspaper", 0., 10.).unwrap();
+	builder.add_objective(Objective {
+		name: "objective".to_string(),
+		variables: vec![var("television", 100000.), var("newspaper", 40000.), var("radio", 18000.)],
+		maximize: true
+	}).unwrap();
+	let rows = vec![
+		("constraint_1", vec![var("television", 20.), var("newspaper", 6.), var("radio", 3.)], 182.),
+		("constraint_3", vec![var("television", -1.), var("newspaper", -1.), var("radio", 1.)], 0.),
+		("constraint_4", vec![var("television", -9.), var("newspaper", 1.), var("radio", 1.)], 0.)
+	];
+	for (name, variables, constant) in rows {
+		builder.add_constraint(Constraint {
+			name: name.to_string(),
+			variables: variables,
+			constant: constant,
+			relation: Relation::LessThanOrEqual
+		}).unwrap();
+	}
+
+	let lp = builder.build_lp().unwrap();
+	assert_eq!(lp.A.rows(), 3);
+
+	let solution = SimplexSolver::new(lp).solve().unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+	let res = solution.values.unwrap();
+	let expected = vec![4., 10., 14.];
+	for i in 0..expected.len() {
+		assert_approx_eq!(res[i], expected[i]);
+	}
+	assert_approx_eq!(solution.objective.unwrap(), 1052000.);
+}
+
 #[test]
 fn advertisement_file_test() {
 	let builder = Builder::new();
 	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
-	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
 
 	let solver = SimplexSolver::new(lp);
-	let solution = solver.solve();
+	let solution = solver.solve().unwrap();
 
 	let res = solution.values.unwrap();
 	let expected = vec![4., 10., 14.];
@@ -92,14 +184,58 @@ fn advertisement_file_test() {
 	assert_eq!(solution.objective.unwrap(), 1052000.);
 }
 
+#[test]
+fn knapsack_file_test() {
+	let mut input_file = File::open("./tests/test_files/knapsack_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, Builder::new()).unwrap();
+	assert_eq!(lp.integers, vec![0, 1, 2, 3]);
+
+	// The relaxation takes a third of the camera
+	let relaxation = SimplexSolver::new(lp.clone()).solve().unwrap();
+	assert_approx_eq!(relaxation.objective.unwrap(), 17. + 5. / 3.);
+
+	let solution = BranchAndBound::new(lp).solve().unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), 18.);
+	assert_eq!(solution.gap, Some(0.));
+	assert_eq!(&solution.values.unwrap()[.. 4], &[1., 0., 1., 1.]);
+}
+
+#[test]
+fn mip_limits_text_test() {
+	let text_problem = "
+		var x integer;
+		var y integer free;
+
+		minimize cost: 3*x + -2*y;
+
+		subject to mix: 2*x + -2*y >= 1;
+		subject to cap: 4*x + 2*y <= 13;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+
+	let solution = BranchAndBound::new(lp.clone()).solve().unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+	let values = solution.values.unwrap();
+	assert_eq!(&values[.. 2], &[0., -1.]);
+	assert_approx_eq!(solution.objective.unwrap(), 2.);
+
+	let mut options = MipOptions::default();
+	options.node_limit = Some(1);
+	let solution = BranchAndBound::with_options(lp, options).solve().unwrap();
+	assert_eq!(solution.status, Status::NodeLimit);
+	assert_eq!(solution.nodes, 1);
+	assert_approx_eq!(solution.best_bound.unwrap(), 1.);
+}
+
 #[test]
 fn radiation_file_test() {
 	let builder = Builder::new();
 	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
-	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
 
 	let solver = SimplexSolver::new(lp);
-	let solution = solver.solve();
+	let solution = solver.solve().unwrap();
 
 	let expected_sol = vec![7.5, 4.5];
 	let sol = solution.values.unwrap(); // returning [7.5, 0.3, 0, 4.5]
@@ -109,31 +245,509 @@ fn radiation_file_test() {
 	assert_approx_eq!(5.25, solution.objective.unwrap());
 }
 
+#[test]
+fn radiation_duals_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
+
+	let solution = SimplexSolver::new(lp).solve().unwrap();
+	assert_approx_eq!(solution.dual("critical_tissues").unwrap(), -0.5);
+	assert_approx_eq!(solution.dual("tumor_region").unwrap(), 1.1);
+	assert_approx_eq!(solution.dual("tumor_center").unwrap(), 0.);
+	assert_approx_eq!(solution.reduced_cost("x_1").unwrap(), 0.);
+	assert_approx_eq!(solution.reduced_cost("x_2").unwrap(), 0.);
+}
+
+#[test]
+fn radiation_sensitivity_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
+
+	let report = SimplexSolver::new(lp).solve().unwrap().sensitivity.unwrap();
+	// Lowering the cost of x_1 only pushes further along tumor_region
+	let x_1 = report.objective_range("x_1").unwrap();
+	assert_eq!(x_1.lower, NEG_INFINITY);
+	assert_approx_eq!(x_1.upper, 0.5);
+	let critical_tissues = report.rhs_range("critical_tissues").unwrap();
+	assert_approx_eq!(critical_tissues.lower, 2.4);
+	assert_approx_eq!(critical_tissues.upper, 3.6);
+	assert!(critical_tissues.contains(2.7));
+	assert!(report.to_string().contains("tumor_region: ["));
+}
+
 
 #[test]
 fn unbounded_file_test() {
 	let builder = Builder::new();
 	let mut input_file = File::open("./tests/test_files/unbounded_example.lp").unwrap();
-	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
 
-	let solver = SimplexSolver::new(lp);
-	let solution = solver.solve();
+	let solver = SimplexSolver::new(lp.clone());
+	let solution = solver.solve().unwrap();
 
 	assert_eq!(solution.objective, None);
 	assert_eq!(solution.values, None);
 	assert_eq!(solution.status, Status::Unbounded);
+
+	let certificate = solution.certificate.unwrap();
+	match certificate {
+		Certificate::Unbounded { ref ray, entering, .. } => {
+			assert_eq!(lp.vars[entering], "excess_0");
+			assert!(ray[0] > 0.);
+		},
+		_ => panic!("Expected an unbounded ray")
+	}
+	assert!(certificate.verify(&lp, 1e-9));
 }
 
 #[test]
 fn infeasible_file_test() {
 	let builder = Builder::new();
 	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
-	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
 
-	let solver = SimplexSolver::new(lp);
-	let solution = solver.solve();
+	let solver = SimplexSolver::new(lp.clone());
+	let solution = solver.solve().unwrap();
 	
 	assert_eq!(solution.objective, None);
 	assert_eq!(solution.values, None);
 	assert_eq!(solution.status, Status::Infeasible);
-}
\ No newline at end of file
+
+	let certificate = solution.certificate.unwrap();
+	assert!(certificate.verify(&lp, 1e-9));
+}
+
+#[test]
+fn infeasible_certificate_text_test() {
+	let text_problem = "
+		var x;
+		var y;
+
+		minimize cost: x + y;
+
+		subject to low: x + y <= -2;
+		subject to mixed: x + -1*y >= -1;
+		subject to high: 2*x + y == 4;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	let solution = SimplexSolver::new(lp.clone()).solve().unwrap();
+	assert_eq!(solution.status, Status::Infeasible);
+
+	let certificate = solution.certificate.unwrap();
+	assert!(certificate.verify(&lp, 1e-9));
+
+	// Negating the ray must not also prove infeasibility
+	let negated = match certificate {
+		Certificate::Infeasible { farkas } => Certificate::Infeasible { farkas: farkas.iter().map(|y| -y).collect() },
+		_ => panic!("Expected a Farkas ray")
+	};
+	assert!(!negated.verify(&lp, 1e-9));
+}
+#[test]
+fn free_variable_file_test() {
+	let lp = example_lp("free");
+	let solution = SimplexSolver::new(lp).solve().unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+
+	let values = solution.values.clone().unwrap();
+	assert_approx_eq!(values[0], -1.);
+	assert_approx_eq!(values[1], -4.);
+	assert_approx_eq!(values[2], 0.);
+	assert_approx_eq!(solution.objective.unwrap(), -9.);
+
+	assert_approx_eq!(solution.dual("floor").unwrap(), 1.5);
+	assert_approx_eq!(solution.dual("ceiling").unwrap(), -0.5);
+	assert_approx_eq!(solution.reduced_cost("z").unwrap(), -1.);
+
+	let text = format!("{}", solution);
+	assert!(text.contains("x: -1"));
+	assert!(!text.contains("negative_0"));
+}
+
+#[test]
+fn parse_error_text_test() {
+	let text_problem = "
+		var x;
+		maximize val: x;
+		subject to cap: x <= 1O;
+	";
+
+	let builder = Builder::new();
+	match Parser::lp_from_text(text_problem, builder) {
+		Err(ParseError::BadNumber { statement, span, text }) => {
+			assert_eq!(statement, 2);
+			assert_eq!((span.line, span.column), (4, 24));
+			assert_eq!(text, "1O");
+		},
+		other => panic!("Expected a bad number error, got {:?}", other)
+	}
+}
+
+#[test]
+fn degenerate_file_test() {
+	let mut pivot_options = vec![];
+	pivot_options.push(SimplexOptions::default());
+	pivot_options.push(SimplexOptions { pricing: PricingRule::Bland, ratio_test: RatioTest::MinRatio, stall_limit: None, .. SimplexOptions::default() });
+	pivot_options.push(SimplexOptions { pricing: PricingRule::Dantzig, ratio_test: RatioTest::Lexicographic, stall_limit: None, .. SimplexOptions::default() });
+	pivot_options.push(SimplexOptions { pricing: PricingRule::Dantzig, ratio_test: RatioTest::MinRatio, stall_limit: Some(1), .. SimplexOptions::default() });
+
+	for options in pivot_options {
+		let builder = Builder::new();
+		let mut input_file = File::open("./tests/test_files/degenerate_example.lp").unwrap();
+		let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
+
+		let solver = SimplexSolver::with_options(lp, options);
+		let solution = solver.solve().unwrap();
+
+		let expected_sol = vec![1., 0., 1., 0.];
+		let sol = solution.values.unwrap();
+		assert_eq!(solution.status, Status::Optimal);
+		for i in 0 .. expected_sol.len() {
+			assert_approx_eq!(expected_sol[i], sol[i]);
+		}
+		assert_approx_eq!(1.25, solution.objective.unwrap());
+	}
+}
+
+#[test]
+fn iteration_limit_file_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/degenerate_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
+
+	// Cycles forever without a limit
+	let options = SimplexOptions { stall_limit: None, max_iterations: Some(25), .. SimplexOptions::default() };
+	let solver = SimplexSolver::with_options(lp, options);
+	let solution = solver.solve().unwrap();
+
+	assert_eq!(solution.status, Status::IterationLimit);
+	assert_eq!(solution.values.unwrap().len(), 7);
+	assert_approx_eq!(0., solution.objective.unwrap());
+}
+
+#[test]
+fn limits_in_phase_one_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
+
+	let options = SimplexOptions { max_iterations: Some(0), .. SimplexOptions::default() };
+	let solution = SimplexSolver::with_options(lp.clone(), options).solve().unwrap();
+	assert_eq!(solution.status, Status::IterationLimit);
+	assert_eq!(solution.values, None);
+
+	let options = SimplexOptions { time_limit: Some(Duration::from_secs(0)), .. SimplexOptions::default() };
+	let solution = SimplexSolver::with_options(lp.clone(), options).solve().unwrap();
+	assert_eq!(solution.status, Status::TimeLimit);
+
+	let cancel = CancelToken::new();
+	let options = SimplexOptions { cancel: Some(cancel.clone()), .. SimplexOptions::default() };
+	let solver = SimplexSolver::with_options(lp, options);
+	cancel.cancel();
+	let solution = solver.solve().unwrap();
+	assert_eq!(solution.status, Status::Cancelled);
+}
+
+#[test]
+fn negative_rhs_text_test() {
+	let text_problem = "
+		var x;
+		var y;
+
+		minimize cost: 2*x + 3*y;
+		subject to x_min: -1*x <= -3;
+		subject to total: x + y >= 5;
+	";
+
+	let builder = Builder::new();
+	let lp = Parser::lp_from_text(text_problem, builder).unwrap();
+	let solution = SimplexSolver::new(lp).solve().unwrap();
+
+	let expected_sol = vec![5., 0.];
+	let sol = solution.values.unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+	for i in 0 .. expected_sol.len() {
+		assert_approx_eq!(expected_sol[i], sol[i]);
+	}
+	assert_approx_eq!(10., solution.objective.unwrap());
+}
+
+#[test]
+fn warm_start_file_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder).unwrap();
+
+	let cold = SimplexSolver::new(lp.clone()).solve().unwrap();
+	let basis = cold.basis.clone().unwrap();
+	assert!(cold.iterations > 0);
+	assert_eq!(basis.statuses.len(), 4);
+	assert_eq!(basis.basic_vars().len(), 3);
+
+	let mut solver = SimplexSolver::new(lp.clone());
+	solver.warm_start = Some(basis.clone());
+	let warm = solver.solve().unwrap();
+	assert_eq!(warm.status, Status::Optimal);
+	assert_eq!(warm.iterations, 0);
+	assert_eq!(warm.basis, Some(basis.clone()));
+	assert_approx_eq!(5.25, warm.objective.unwrap());
+
+	// A nearby objective keeps the old vertex feasible, so no Phase I is needed
+	let mut nearby = lp.clone();
+	nearby.c[1] = 0.45;
+	let mut solver = SimplexSolver::new(nearby);
+	solver.warm_start = Some(basis);
+	let solution = solver.solve().unwrap();
+	let expected_sol = vec![7.5, 4.5];
+	let sol = solution.values.unwrap();
+	for i in 0 .. expected_sol.len() {
+		assert_approx_eq!(expected_sol[i], sol[i]);
+	}
+	assert_approx_eq!(5.025, solution.objective.unwrap());
+	assert!(solution.iterations < cold.iterations);
+}
+
+#[test]
+fn reoptimize_file_test() {
+	let lp = example_lp("radiation");
+	let mut solver = SimplexSolver::new(lp.clone());
+	let cold = solver.reoptimize().unwrap();
+	assert_eq!(cold.iterations, 3);
+
+	// x_1 <= 7 cuts off the optimum, which one dual pivot from the kept
+	// tableau restores
+	solver.add_constraint("x_1_max", vec![1., 0., 0., 0.], Relation::LessThanOrEqual, 7.).unwrap();
+	let warm = solver.reoptimize().unwrap();
+	assert_eq!(warm.status, Status::Optimal);
+	assert_approx_eq!(warm.objective.unwrap(), 5.3);
+	assert_eq!(warm.iterations, 1);
+
+	// Loosening it to x_1 <= 8 gives back the old optimum
+	let row = lp.A.rows();
+	solver.set_rhs(row, 8.).unwrap();
+	let solution = solver.reoptimize().unwrap();
+	assert_approx_eq!(solution.objective.unwrap(), 5.25);
+	assert!(solution.iterations <= 1);
+}
+
+#[test]
+fn revised_simplex_file_test() {
+	// The LU factorized basis reaches the tableau's basis, in no more
+	// pivots than the tableau, whose anti-cycling slows it down on the
+	// degenerate example
+	let solutions = assert_solves_examples(|lp| RevisedSimplexSolver::new(lp.clone()).solve().unwrap());
+	for &(ref expected, ref solution) in &solutions {
+		assert_eq!(solution.basis, expected.basis);
+		assert!(solution.iterations <= expected.iterations);
+	}
+	let (ref expected, ref solution) = solutions[1];
+	assert!(solution.iterations < expected.iterations);
+}
+
+#[test]
+fn interior_point_file_test() {
+	// Crossover ends on the tableau's basis, which gives the same duals
+	let solutions = assert_solves_examples(|lp| InteriorPointSolver::new(lp.clone()).solve().unwrap());
+	for &(ref expected, ref solution) in &solutions {
+		assert_eq!(solution.basis, expected.basis);
+	}
+	assert_eq!(solutions[4].1.duals, solutions[4].0.duals);
+
+	// Without it the values are only those of the interior point, and no
+	// basis is given
+	let mut options = InteriorPointOptions::default();
+	options.crossover = false;
+	let solutions = assert_solves_examples(|lp| InteriorPointSolver::with_options(lp.clone(), options.clone()).solve().unwrap());
+	for &(_, ref solution) in &solutions {
+		assert_eq!(solution.basis, None);
+	}
+	assert!((solutions[4].1.dual("critical_tissues").unwrap() + 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn exact_file_test() {
+	// The optima as fractions, with 0.4 in the radiation example read as
+	// 2/5 rather than its nearest f64
+	let optima = [
+		("advertisement", Some((1052000, 1))),
+		("degenerate", Some((5, 4))),
+		("free", Some((-9, 1))),
+		("infeasible", None),
+		("radiation", Some((21, 4))),
+		("unbounded", None)
+	];
+	for &(name, optimum) in optima.iter() {
+		let exact_lp = Parser::exact_lp_from_file(&mut example_file(name), Builder::new()).unwrap();
+		let solution = ExactSimplexSolver::new(exact_lp).solve().unwrap();
+		match optimum {
+			Some((numer, denom)) => {
+				assert_eq!(solution.status, Status::Optimal);
+				let objective = solution.objective.unwrap();
+				assert_eq!((objective.numer().to_i64().unwrap(), objective.denom().to_i64().unwrap()), (numer, denom));
+			},
+			None => assert!(solution.status == Status::Infeasible || solution.status == Status::Unbounded)
+		}
+	}
+}
+
+#[test]
+fn presolve_file_test() {
+	let solutions = assert_solves_examples(|lp| Presolve::new(lp).unwrap().solve::<RevisedSimplexSolver>().unwrap());
+
+	// Rows presolve leaves, and the status it settles on its own
+	let reduced = [
+		("advertisement", 3, None),
+		("degenerate", 2, None),
+		("free", 2, None),
+		("infeasible", 1, Some(Status::Infeasible)),
+		("radiation", 3, None),
+		("unbounded", 0, Some(Status::Unbounded))
+	];
+	for &(name, rows, ref status) in reduced.iter() {
+		let presolve = Presolve::new(&example_lp(name)).unwrap();
+		assert_eq!((presolve.lp.A.rows(), presolve.status), (rows, status.clone()), "{}", name);
+	}
+
+	// newspaper <= 10 becomes a bound, and postsolve gives it back its dual
+	let (ref expected, ref solution) = solutions[0];
+	assert_eq!(solution.duals.as_ref().unwrap().len(), 4);
+	for (dual, expected_dual) in solution.duals.as_ref().unwrap().iter().zip(expected.duals.as_ref().unwrap().iter()) {
+		assert_approx_eq!(dual, expected_dual);
+	}
+}
+
+#[test]
+fn scaling_file_test() {
+	let methods = [Scaling::GeometricMean, Scaling::Equilibration, Scaling::GeometricMeanEquilibration];
+	for &method in methods.iter() {
+		let options = SimplexOptions { scaling: Some(method), .. SimplexOptions::default() };
+		assert_solves_examples(|lp| SimplexSolver::with_options(lp.clone(), options.clone()).solve().unwrap());
+		assert_solves_examples(|lp| RevisedSimplexSolver::with_options(lp.clone(), options.clone()).solve().unwrap());
+	}
+
+	// The radiation example with critical_tissues multiplied by 1e6, whose
+	// dual is unscaled back to the original's divided by 1e6
+	let text_problem = "
+		var x_1;
+		var x_2;
+
+		minimize healthy_anatomy_exposure: 0.4*x_1 + 0.5*x_2;
+		subject to critical_tissues: 300000*x_1 + 100000*x_2 <= 2700000;
+		subject to tumor_region: 0.5*x_1 + 0.5*x_2 == 6;
+		subject to tumor_center: 0.6*x_1 + 0.4*x_2 >= 6;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new()).unwrap();
+	for &method in methods.iter() {
+		let options = SimplexOptions { scaling: Some(method), .. SimplexOptions::default() };
+		for solution in vec![
+			SimplexSolver::with_options(lp.clone(), options.clone()).solve().unwrap(),
+			RevisedSimplexSolver::with_options(lp.clone(), options).solve().unwrap()
+		] {
+			let values = solution.values.clone().unwrap();
+			assert_approx_eq!(values[0], 7.5);
+			assert_approx_eq!(values[1], 4.5);
+			assert!((solution.dual("critical_tissues").unwrap() * 1e6 + 0.5).abs() < 1e-9);
+			assert_approx_eq!(solution.dual("tumor_region").unwrap(), 1.1);
+		}
+	}
+}
+
+#[test]
+fn cplex_file_test() {
+	let expected = example_lp("advertisement");
+	let mut input_file = File::open("./tests/test_files/advertisement_cplex_example.lp").unwrap();
+	let lp = CplexParser::lp_from_file(&mut input_file, Builder::new()).unwrap();
+	assert_eq!(lp, expected);
+
+	// Writing and reading back gives the same Lp, bounds and integers too
+	for name in EXAMPLES.iter().chain(["knapsack"].iter()) {
+		let lp = example_lp(name);
+		assert_eq!(CplexParser::lp_from_text(&lp_to_cplex(&lp), Builder::new()).unwrap(), lp);
+	}
+
+	// Columns keep their order when a free variable is not in the objective
+	// and a variable is in no row at all
+	let text = "var a;\nvar f free;\nvar b <= 0;\nvar c;\nmaximize obj: c;\n\
+				subject to c1: c + -1*f <= 4;\nsubject to c2: f + a <= 2;";
+	let lp = Parser::lp_from_text(text, Builder::new()).unwrap();
+	assert_eq!(CplexParser::lp_from_text(&lp_to_cplex(&lp), Builder::new()).unwrap(), lp);
+}
+
+#[test]
+fn mps_file_test() {
+	let mut input_file = File::open("./tests/test_files/testprob_example.mps").unwrap();
+	let lp = FixedMpsParser::lp_from_file(&mut input_file, Builder::new()).unwrap();
+	let mut input_file = File::open("./tests/test_files/testprob_example.mps").unwrap();
+	assert_eq!(MpsParser::lp_from_file(&mut input_file, Builder::new()).unwrap(), lp);
+	let solution = SimplexSolver::new(lp).solve().unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), 16.);
+
+	// Every row is ranged, and the objective constant of 100 is dropped
+	let mut input_file = File::open("./tests/test_files/ranges_example.mps").unwrap();
+	let lp = MpsParser::lp_from_file(&mut input_file, Builder::new()).unwrap();
+	assert_eq!(lp.integers, vec![1]);
+	let relaxation = RevisedSimplexSolver::new(lp.clone()).solve().unwrap();
+	assert_approx_eq!(relaxation.objective.unwrap(), 104.);
+	let solution = BranchAndBound::new(lp.clone()).solve().unwrap();
+	assert_approx_eq!(solution.objective.unwrap(), 104.);
+	assert_eq!(MpsParser::lp_from_text(&lp_to_mps(&lp), Builder::new()).unwrap().A.cols(), lp.A.cols() + 3);
+
+	// The rows of the infeasible and unbounded examples share names, which
+	// MPS rows may not
+	let files = ["advertisement", "degenerate", "free", "knapsack", "radiation"];
+	for name in files.iter() {
+		let lp = example_lp(name);
+		assert_eq!(MpsParser::lp_from_text(&lp_to_mps(&lp), Builder::new()).unwrap(), lp);
+	}
+}
+
+#[test]
+fn format_file_test() {
+	for name in EXAMPLES.iter().chain(["knapsack"].iter()) {
+		let components = Parser::parse_components_from_file(&mut example_file(name)).unwrap();
+		let formatted = format_components(&components);
+		assert_eq!(Parser::parse_components_from_text(&formatted).unwrap(), components);
+		assert_eq!(format_components(&Parser::parse_components_from_text(&formatted).unwrap()), formatted);
+	}
+
+	// The MPS bounds, ranges and integer markers carry over as well
+	let mut input_file = File::open("./tests/test_files/ranges_example.mps").unwrap();
+	let components = MpsParser::parse_components_from_file(&mut input_file).unwrap();
+	let formatted = format_components(&components);
+	let lp = Parser::lp_from_text(&formatted, Builder::new()).unwrap();
+
+	// Each ranged row comes back as two rows, but the bounds are the same
+	let mut input_file = File::open("./tests/test_files/ranges_example.mps").unwrap();
+	let expected = MpsParser::lp_from_file(&mut input_file, Builder::new()).unwrap();
+	assert_eq!(lp.lower[.. 5], expected.lower[.. 5]);
+	assert_eq!(lp.upper[.. 5], expected.upper[.. 5]);
+	assert_eq!(lp.substitutions, expected.substitutions);
+	assert_eq!(lp.integers, expected.integers);
+	let solution = BranchAndBound::new(lp).solve().unwrap();
+	assert_approx_eq!(solution.objective.unwrap(), 104.);
+}
+
+// Netlib instances in tests/test_files/netlib, checked against the optima
+// Netlib lists
+#[test]
+fn netlib_file_test() {
+	let optima = [
+		("afiro", -4.6475314286e2)
+	];
+	for &(name, optimum) in optima.iter() {
+		let mut input_file = File::open(format!("./tests/test_files/netlib/{}.mps", name)).unwrap();
+		let lp = FixedMpsParser::lp_from_file(&mut input_file, Builder::new()).unwrap();
+		let solutions = [
+			SimplexSolver::new(lp.clone()).solve().unwrap(),
+			RevisedSimplexSolver::new(lp.clone()).solve().unwrap(),
+			InteriorPointSolver::new(lp).solve().unwrap()
+		];
+		for solution in solutions.iter() {
+			assert_eq!(solution.status, Status::Optimal, "{}", name);
+			assert!((solution.objective.unwrap() - optimum).abs() < 1e-8 * (1. + optimum.abs()), "{}", name);
+		}
+	}
+}
@@ -0,0 +1,34 @@
+* Free MPS with a maximization, ranges on each kind of row, negative RHS,
+* integer markers and every kind of bound
+NAME ranges
+OBJSENSE
+    MAX
+ROWS
+ N profit
+ L cap
+ G floor
+ E balance
+ N spare
+COLUMNS
+ x profit 3 cap 1
+ x floor 1 balance 1
+ MARKER 'MARKER' 'INTORG'
+ y profit 2 cap 1
+ y balance -1 spare 9
+ MARKER 'MARKER' 'INTEND'
+ z profit -1 floor 1
+ z cap 2
+ w profit 1 balance 1
+RHS
+ rhs cap 10 floor -4
+ rhs balance -2 profit 100
+RANGES
+ rng cap 6 floor 3
+ rng balance -5
+BOUNDS
+ UP bnd x 8
+ MI bnd z
+ UP bnd z 3
+ FR bnd w
+ UI bnd y 1e30
+ENDATA
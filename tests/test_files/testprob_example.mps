@@ -0,0 +1,22 @@
+* The example of the MPS format in its original description, in fixed MPS
+NAME          TESTPROB
+ROWS
+ N  COST
+ L  LIM1
+ G  LIM2
+ E  MYEQN
+COLUMNS
+    XONE      COST                 1   LIM1                 1
+    XONE      LIM2                 1
+    YTWO      COST                 2   LIM1                 1
+    YTWO      MYEQN               -1
+    ZTHREE    COST                 3   LIM2                 1
+    ZTHREE    MYEQN                1
+RHS
+    RHS1      LIM1                 4   LIM2                 1
+    RHS1      MYEQN                7
+BOUNDS
+ UP BND1      XONE                 4
+ LO BND1      YTWO                -1
+ UP BND1      YTWO                 1
+ENDATA